@@ -12,4 +12,7 @@ pub enum EscrowError {
 
     #[msg("Invalid mint b")]
     InvalidMintB,
+
+    #[msg("Invalid taker")]
+    InvalidTaker,
 }
\ No newline at end of file
@@ -51,13 +51,14 @@ pub struct Make<'info> {
 }
 
 impl<'info> Make<'info> {
-    pub fn populate_escrow(&mut self,seed: u64,amount:u64,bump:u8) -> Result<()> {
+    pub fn populate_escrow(&mut self,seed: u64,amount:u64,taker:Option<Pubkey>,bump:u8) -> Result<()> {
         self.escrow.set_inner(Escrow {
             seed,
             maker:self.maker.key(),
             mint_a: self.mint_a.key(),
             mint_b: self.mint_b.key(),
             receive: amount,
+            taker,
             bump,
         });
         Ok(())
@@ -81,12 +82,12 @@ impl<'info> Make<'info> {
     }
 }
 
-pub fn handler(ctx: Context<Make>, seed: u64, receive: u64, amount: u64) -> Result<()> {
+pub fn handler(ctx: Context<Make>, seed: u64, receive: u64, amount: u64, taker: Option<Pubkey>) -> Result<()> {
     // Validate the amount
     require_gt!(receive, 0, EscrowError::InvalidAmount);
     require_gt!(amount, 0, EscrowError::InvalidAmount);
 
-    ctx.accounts.populate_escrow(seed, receive, ctx.bumps.escrow)?;
+    ctx.accounts.populate_escrow(seed, receive, taker, ctx.bumps.escrow)?;
 
     ctx.accounts.deposit_token(amount)?;
     Ok(())
@@ -19,6 +19,7 @@ pub struct Take<'info> {
     has_one = maker @ EscrowError::InvalidMaker,
     has_one = mint_a @ EscrowError::InvalidMintA,
     has_one = mint_b @ EscrowError::InvalidMintB,
+    constraint = escrow.taker.map_or(true, |taker_key| taker_key == taker.key()) @ EscrowError::InvalidTaker,
     )]
     pub escrow: Box<Account<'info, Escrow>>,
 
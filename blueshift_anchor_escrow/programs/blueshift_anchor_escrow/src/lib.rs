@@ -16,8 +16,8 @@ pub mod blueshift_anchor_escrow {
     use super::*;
 
     #[instruction(discriminator = 0)]
-    pub fn make(ctx: Context<Make>, seed: u64, receive: u64, amount: u64) -> Result<()> {
-        instructions::make::handler(ctx, seed, receive, amount)
+    pub fn make(ctx: Context<Make>, seed: u64, receive: u64, amount: u64, taker: Option<Pubkey>) -> Result<()> {
+        instructions::make::handler(ctx, seed, receive, amount, taker)
     }
 
     #[instruction(discriminator = 1)]
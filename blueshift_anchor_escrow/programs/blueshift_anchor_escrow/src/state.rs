@@ -19,4 +19,10 @@ pub struct Escrow {
     
     //缓存的bump字节；动态派生它会消耗计算资源，因此我们讲其保存一次。
     pub bump: u8,
+
+    //指定的接受者；为 None 时任何人都可以接受交易
+    //追加在 bump 之后，前面字段的偏移量与旧版布局相同。
+    //旧版托管账户没有这个字段，升级后无法反序列化，Take 和 Refund 都会失败，
+    //因此升级程序前必须先 Refund 所有已存在的托管
+    pub taker: Option<Pubkey>,
 }
\ No newline at end of file
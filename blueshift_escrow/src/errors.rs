@@ -49,6 +49,63 @@ pub enum EscrowError {
     /// 提供的地址不符合预期要求
     /// 例如：PDA 派生失败、地址不匹配等
     InvalidAddress = 4,
+
+    /// 非法的成交数量
+    /// 部分成交的代币 B 数量为 0、超过剩余数量，
+    /// 或者太小以至于按比例释放的代币 A 数量为 0
+    InvalidTakeAmount = 5,
+
+    /// 托管已过期
+    /// 到达过期时间后不能再接受交易，也不能创建过期时间已经过去的托管
+    EscrowExpired = 6,
+
+    /// 托管尚未过期
+    /// 只有设置了过期时间并且已经过期的托管才能被 Expire 关闭
+    EscrowNotExpired = 7,
+
+    /// 非法的接受者
+    /// 托管指定了接受者，而签名的 taker 不是这个地址
+    InvalidTaker = 8,
+
+    /// 托管已部分成交
+    /// 已经有接受者按原来的条款部分成交，不能再修改条款
+    EscrowPartiallyFilled = 9,
+
+    /// 非法的创建者
+    /// 传入的 maker 与托管中记录的创建者不一致
+    /// 对应 Anchor: has_one = maker @ EscrowError::InvalidMaker
+    InvalidMaker = 10,
+
+    /// 非法的代币 A
+    /// 传入的 mint_a 与托管中记录的不一致
+    /// 对应 Anchor: has_one = mint_a @ EscrowError::InvalidMintA
+    InvalidMintA = 11,
+
+    /// 非法的代币 B
+    /// 传入的 mint_b 与托管中记录的不一致
+    /// 对应 Anchor: has_one = mint_b @ EscrowError::InvalidMintB
+    InvalidMintB = 12,
+
+    /// 非法的代币程序
+    /// token_program 不是 Token / Token-2022 Program，或者不是 Mint 的所有者
+    /// 对应 Anchor: associated_token::token_program = token_program
+    InvalidTokenProgram = 13,
+
+    /// 非法的代币账户所有者
+    /// 已存在的代币账户的 owner 字段不是预期的钱包
+    /// 对应 Anchor: associated_token::authority = xxx
+    InvalidTokenAccountOwner = 14,
+
+    /// 非法的代币账户 Mint
+    /// 已存在的代币账户的 mint 字段不是预期的 Mint
+    /// 对应 Anchor: associated_token::mint = xxx
+    InvalidTokenAccountMint = 15,
+
+    /// 成交条款超出接受者的限定
+    /// Take 需要支付的代币 B 超过 max_pay，或者获得的代币 A 少于 min_receive；
+    /// TakePartial 获得的代币 A 少于 min_receive
+    /// 创建者在接受交易上链之前通过 Amend 修改了托管时返回此错误
+    SlippageExceeded = 16,
 }
 
 // =============================================================================
@@ -99,6 +156,18 @@ impl fmt::Display for EscrowError {
             EscrowError::InvalidOwner => write!(f, "非法的所有者"),
             EscrowError::InvalidAccountData => write!(f, "非法的账户数据"),
             EscrowError::InvalidAddress => write!(f, "非法的地址"),
+            EscrowError::InvalidTakeAmount => write!(f, "非法的成交数量"),
+            EscrowError::EscrowExpired => write!(f, "托管已过期"),
+            EscrowError::EscrowNotExpired => write!(f, "托管尚未过期"),
+            EscrowError::InvalidTaker => write!(f, "非法的接受者"),
+            EscrowError::EscrowPartiallyFilled => write!(f, "托管已部分成交"),
+            EscrowError::InvalidMaker => write!(f, "非法的创建者"),
+            EscrowError::InvalidMintA => write!(f, "非法的代币 A"),
+            EscrowError::InvalidMintB => write!(f, "非法的代币 B"),
+            EscrowError::InvalidTokenProgram => write!(f, "非法的代币程序"),
+            EscrowError::InvalidTokenAccountOwner => write!(f, "非法的代币账户所有者"),
+            EscrowError::InvalidTokenAccountMint => write!(f, "非法的代币账户 Mint"),
+            EscrowError::SlippageExceeded => write!(f, "成交条款超出接受者的限定"),
         }
     }
 }
\ No newline at end of file
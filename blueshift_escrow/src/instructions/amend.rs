@@ -0,0 +1,258 @@
+// =============================================================================
+// Amend 指令 - Pinocchio 版本
+// =============================================================================
+// 本指令用于修改一个尚未成交的托管交易
+// 只有创建者可以调用，可以同时：
+// - 修改希望获得的代币 B 数量（receive）
+// - 调整金库中的代币 A 数量：多于当前余额时从创建者补充，少于时退还差额
+// - 更换希望获得的代币 B（传入新的 mint_b，不更换时传入原来的 mint_b）
+//
+// 已经部分成交的托管不能修改，
+// 否则之前的接受者和之后的接受者会按不同的比例成交
+//
+// 与 Refund 的关系：
+// - 账户基本相同，多了 mint_b
+// - Refund 取回全部代币 A 并关闭托管，Amend 保留托管并更新条款
+
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::error::ProgramError;
+use pinocchio_token::instructions::Transfer;
+use solana_address::Address;
+use crate::errors::EscrowError;
+use crate::{AccountCheck, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit, Escrow, MintInterface, ProgramAccount, SignerAccount};
+
+// =============================================================================
+// AmendAccounts 账户结构体
+// =============================================================================
+pub struct AmendAccounts<'info> {
+    // 创建者账户（必须签名）
+    pub maker: &'info AccountView,
+
+    // 托管账户（PDA）
+    pub escrow: &'info AccountView,
+
+    // 代币 A 的 Mint 账户，必须与托管中记录的相同
+    pub mint_a: &'info AccountView,
+
+    // 代币 B 的 Mint 账户，与托管中记录的不同时更换
+    pub mint_b: &'info AccountView,
+
+    // 创建者的代币 A ATA（补充时转出，退还时转入）
+    pub maker_ata_a: &'info AccountView,
+
+    // 金库账户
+    pub vault: &'info AccountView,
+
+    // 系统程序
+    pub system_program: &'info AccountView,
+
+    // 代币程序
+    pub token_program: &'info AccountView,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for AmendAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        // 最后一个账户是 Associated Token Program，与 Make 相同
+        let [maker, escrow, mint_a, mint_b, maker_ata_a, vault, system_program, token_program, _] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // 验证 maker 是签名者
+        SignerAccount::check(maker)?;
+
+        // 验证 escrow 是本程序拥有的账户
+        ProgramAccount::check(escrow)?;
+
+        // 验证 mint_a 和 mint_b 是有效的 Mint 账户
+        MintInterface::check(mint_a)?;
+        MintInterface::check(mint_b)?;
+
+        // 验证 vault 是 escrow 的代币 A ATA
+        AssociatedTokenAccount::check(vault, escrow, mint_a, token_program)?;
+
+        Ok(Self {
+            maker,
+            escrow,
+            mint_a,
+            mint_b,
+            maker_ata_a,
+            vault,
+            system_program,
+            token_program,
+        })
+    }
+}
+
+// =============================================================================
+// AmendInstructionData 指令数据结构体
+// =============================================================================
+pub struct AmendInstructionData {
+    // 新的希望获得的代币 B 数量
+    pub receive: u64,
+
+    // 修改后金库中的代币 A 数量
+    // 部分成交按 deposit / receive 的比例计算，这里同时更新 deposit
+    pub amount: u64,
+}
+
+impl<'info> TryFrom<&'info [u8]> for AmendInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        // 验证数据长度：2 个 u64 = 16 字节
+        if data.len() != size_of::<u64>() * 2 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let receive = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let amount = u64::from_le_bytes(data[8..16].try_into().unwrap());
+
+        // 对应 Anchor: require_gt!(receive, 0, EscrowError::InvalidAmount);
+        // receive 为 0 时接受者不用支付任何代币 B 就能取走金库
+        if receive == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // 与 Make 相同，金库中必须有代币 A
+        // 需要取回全部代币 A 时使用 Refund
+        if amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            receive,
+            amount,
+        })
+    }
+}
+
+// =============================================================================
+// Amend 指令主结构体
+// =============================================================================
+pub struct Amend<'info> {
+    pub accounts: AmendAccounts<'info>,
+    pub instruction_data: AmendInstructionData,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for Amend<'info> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = AmendAccounts::try_from(accounts)?;
+        let instruction_data = AmendInstructionData::try_from(data)?;
+
+        // 创建创建者的代币 A ATA（如果不存在）
+        // 减少金库中的代币 A 时需要转入这个账户
+        AssociatedTokenAccount::init_if_needed(
+            accounts.maker_ata_a,     // 要创建/验证的账户
+            accounts.mint_a,          // mint 账户
+            accounts.maker,           // payer：创建者
+            accounts.maker,           // owner：创建者
+            accounts.system_program,  // System Program
+            accounts.token_program,   // Token Program
+        )?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+// =============================================================================
+// Amend 指令的业务逻辑实现
+// =============================================================================
+impl<'info> Amend<'info> {
+    // 指令判别器
+    pub const DISCRIMINATOR: &'info u8 = &5;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let data = &self.instruction_data;
+
+        // =====================================================================
+        // 验证并更新托管账户
+        // =====================================================================
+        // 条款在 CPI 之前写入，代码块结束时释放可变借用
+        // 之后的转账失败时整条指令回滚，写入的条款也会回滚
+        let (seed, bump) = {
+            let mut escrow_data = self.accounts.escrow.try_borrow_mut()?;
+            let escrow = Escrow::load_mut(escrow_data.as_mut())?;
+
+            // 重新计算 PDA 地址，同时验证签名的 maker 是这个托管的创建者
+            let escrow_key = Address::create_program_address(
+                &[
+                    b"escrow",                                    // 固定前缀
+                    self.accounts.maker.address().as_ref(),     // 创建者地址
+                    &escrow.seed.to_le_bytes(),                  // 从账户中读取的 seed
+                    &escrow.bump,                                // 从账户中读取的 bump
+                ],
+                &crate::ID  // 程序 ID
+            )?;
+            if &escrow_key != self.accounts.escrow.address() {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+
+            // 金库必须是托管中记录的代币 A
+            if &escrow.mint_a != self.accounts.mint_a.address() {
+                return Err(EscrowError::InvalidMintA.into());
+            }
+
+            // 部分成交之后不能修改条款
+            if escrow.is_partially_filled() {
+                return Err(EscrowError::EscrowPartiallyFilled.into());
+            }
+
+            // 更新条款，remaining 与新的 receive 保持一致
+            escrow.set_mint_b(*self.accounts.mint_b.address());
+            escrow.set_receive(data.receive);
+            escrow.set_remaining(data.receive);
+            escrow.set_deposit(data.amount);
+
+            (escrow.seed, escrow.bump)
+        }; // ← escrow_data 在这里自动释放，借用结束
+
+        // =====================================================================
+        // 调整金库中的代币 A
+        // =====================================================================
+        // 以金库的实际余额为准计算差额
+        let vault_amount = {
+            let vault_data = self.accounts.vault.try_borrow()?;
+
+            // Token Account 的 amount 字段（偏移量 64，长度 8）
+            u64::from_le_bytes(vault_data[64..72].try_into().unwrap())
+        }; // ← vault_data 在这里自动释放
+
+        if data.amount > vault_amount {
+            // 补充：从创建者转入差额，创建者已签名
+            Transfer {
+                from: self.accounts.maker_ata_a,    // 从：创建者的代币 A ATA
+                to: self.accounts.vault,            // 到：金库账户
+                authority: self.accounts.maker,     // 权限：创建者必须签名
+                amount: data.amount - vault_amount, // 转账数量：差额
+            }.invoke()?;
+        } else if data.amount < vault_amount {
+            // 退还：从金库转出差额，需要 escrow PDA 签名
+            let seed_binding = seed.to_le_bytes();
+            let bump_binding = bump;
+            let escrow_seeds = [
+                Seed::from(b"escrow"),                           // 种子 1: "escrow"
+                Seed::from(self.accounts.maker.address().as_ref()),  // 种子 2: maker 地址
+                Seed::from(&seed_binding),                       // 种子 3: seed 的字节数组
+                Seed::from(&bump_binding),                       // 种子 4: bump
+            ];
+            let signer = [Signer::from(&escrow_seeds)];
+
+            Transfer {
+                from: self.accounts.vault,          // 从：金库账户
+                to: self.accounts.maker_ata_a,      // 到：创建者的代币 A ATA
+                authority: self.accounts.escrow,    // 权限：escrow PDA（需要签名）
+                amount: vault_amount - data.amount, // 转账数量：差额
+            }.invoke_signed(&signer)?;
+        }
+
+        Ok(())
+    }
+}
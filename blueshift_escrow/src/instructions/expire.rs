@@ -0,0 +1,217 @@
+// =============================================================================
+// Expire 指令 - Pinocchio 版本
+// =============================================================================
+// 本指令用于关闭一个已经过期的托管交易
+// 任何人都可以调用，代币 A 和全部租金都退还给创建者，调用者得不到任何资金
+//
+// 执行流程：
+// 1. 验证托管设置了过期时间并且已经过期
+// 2. 从金库中将代币 A 转移回创建者的 ATA（不存在时由调用者支付创建费用）
+// 3. 关闭金库账户，将 lamports 返还给创建者
+// 4. 关闭托管账户，将租金返还给创建者
+//
+// 与 Refund 的区别：
+// - Refund 需要创建者签名，任何时候都可以调用
+// - Expire 不需要创建者签名，只能在过期之后调用
+//   因此需要额外验证金库和 mint_a 属于这个托管，防止传入其他账户
+
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use pinocchio_token::instructions::{CloseAccount, Transfer};
+use solana_address::Address;
+use crate::errors::EscrowError;
+use crate::{AccountCheck, AccountClose, AssociatedTokenAccount, AssociatedTokenAccountCheck, AssociatedTokenAccountInit, Escrow, MintInterface, ProgramAccount, SignerAccount};
+
+// =============================================================================
+// ExpireAccounts 账户结构体
+// =============================================================================
+pub struct ExpireAccounts<'info> {
+    // 调用者账户（必须签名）
+    // 可以是任何人，只在创建者的代币 A ATA 不存在时支付创建费用
+    pub caller: &'info AccountView,
+
+    // 创建者账户（不需要签名）
+    // 接收代币 A 以及金库和托管账户的租金
+    pub maker: &'info AccountView,
+
+    // 托管账户（PDA，将被关闭）
+    pub escrow: &'info AccountView,
+
+    // 代币 A 的 Mint 账户
+    pub mint_a: &'info AccountView,
+
+    // 金库账户（将被关闭）
+    pub vault: &'info AccountView,
+
+    // 创建者的代币 A ATA（可能不存在）
+    pub maker_ata_a: &'info AccountView,
+
+    // 系统程序
+    pub system_program: &'info AccountView,
+
+    // 代币程序
+    pub token_program: &'info AccountView,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for ExpireAccounts<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        // 最后一个账户是 Associated Token Program，与 Refund 相同
+        let [caller, maker, escrow, mint_a, vault, maker_ata_a, system_program, token_program, _] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // 验证调用者是签名者（支付 maker_ata_a 的创建费用）
+        SignerAccount::check(caller)?;
+
+        // 验证 escrow 是本程序拥有的账户
+        ProgramAccount::check(escrow)?;
+
+        // 验证 mint_a 是有效的 Mint 账户
+        MintInterface::check(mint_a)?;
+
+        // 验证 vault 是 escrow 的代币 A ATA
+        // 调用者不是创建者，不能信任传入的金库
+        AssociatedTokenAccount::check(vault, escrow, mint_a, token_program)?;
+
+        Ok(Self {
+            caller,
+            maker,
+            escrow,
+            mint_a,
+            vault,
+            maker_ata_a,
+            system_program,
+            token_program,
+        })
+    }
+}
+
+// =============================================================================
+// Expire 指令主结构体
+// =============================================================================
+pub struct Expire<'info> {
+    pub accounts: ExpireAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for Expire<'info> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = ExpireAccounts::try_from(accounts)?;
+
+        // 创建创建者的代币 A ATA（如果不存在），由调用者支付
+        AssociatedTokenAccount::init_if_needed(
+            accounts.maker_ata_a,     // 要创建/验证的账户
+            accounts.mint_a,          // mint 账户
+            accounts.caller,          // payer：调用者
+            accounts.maker,           // owner：创建者
+            accounts.system_program,  // System Program
+            accounts.token_program,   // Token Program
+        )?;
+
+        Ok(Self {
+            accounts,
+        })
+    }
+}
+
+// =============================================================================
+// Expire 指令的业务逻辑实现
+// =============================================================================
+impl<'info> Expire<'info> {
+    // 指令判别器
+    pub const DISCRIMINATOR: &'info u8 = &4;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // =====================================================================
+        // 读取托管账户数据
+        // =====================================================================
+        let (seed, bump) = {
+            let data = self.accounts.escrow.try_borrow()?;
+            let escrow = Escrow::load(&data)?;
+
+            // 重新计算 PDA 地址，同时验证 maker 是这个托管的创建者
+            let escrow_key = Address::create_program_address(
+                &[
+                    b"escrow",                                    // 固定前缀
+                    self.accounts.maker.address().as_ref(),     // 创建者地址
+                    &escrow.seed.to_le_bytes(),                  // 从账户中读取的 seed
+                    &escrow.bump,                                // 从账户中读取的 bump
+                ],
+                &crate::ID  // 程序 ID
+            )?;
+            if &escrow_key != self.accounts.escrow.address() {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+
+            // 金库必须是托管中记录的代币 A
+            if &escrow.mint_a != self.accounts.mint_a.address() {
+                return Err(EscrowError::InvalidMintA.into());
+            }
+
+            // 只有设置了过期时间并且已经过期的托管可以关闭
+            if !escrow.is_expired(Clock::get()?.unix_timestamp) {
+                return Err(EscrowError::EscrowNotExpired.into());
+            }
+
+            (escrow.seed, escrow.bump)
+        }; // ← data 在这里自动释放，借用结束
+
+        // =====================================================================
+        // 构造 PDA 签名种子
+        // =====================================================================
+        let seed_binding = seed.to_le_bytes();
+        let bump_binding = bump;
+        let escrow_seeds = [
+            Seed::from(b"escrow"),                           // 种子 1: "escrow"
+            Seed::from(self.accounts.maker.address().as_ref()),  // 种子 2: maker 地址
+            Seed::from(&seed_binding),                       // 种子 3: seed 的字节数组
+            Seed::from(&bump_binding),                       // 种子 4: bump
+        ];
+        let signer = [Signer::from(&escrow_seeds)];
+
+        // =====================================================================
+        // 读取金库中的代币数量
+        // =====================================================================
+        let amount = {
+            let vault_data = self.accounts.vault.try_borrow()?;
+
+            // Token Account 的 amount 字段（偏移量 64，长度 8）
+            u64::from_le_bytes(vault_data[64..72].try_into().unwrap())
+        }; // ← vault_data 在这里自动释放
+
+        // =====================================================================
+        // 步骤 1: 将金库中的全部代币 A 退还给创建者
+        // =====================================================================
+        Transfer {
+            from: self.accounts.vault,        // 从：金库账户
+            to: self.accounts.maker_ata_a,    // 到：创建者的代币 A ATA
+            authority: self.accounts.escrow,  // 权限：escrow PDA（需要签名）
+            amount,                           // 转账数量：金库中的全部代币
+        }.invoke_signed(&signer)?;
+
+        // =====================================================================
+        // 步骤 2: 关闭金库账户，lamports 返还给创建者
+        // =====================================================================
+        CloseAccount {
+            account: self.accounts.vault,       // 要关闭的账户：金库
+            destination: self.accounts.maker,   // 接收 lamports 的账户：创建者
+            authority: self.accounts.escrow,    // 权限：escrow PDA（金库的 owner）
+        }.invoke_signed(&signer)?;
+
+        // =====================================================================
+        // 步骤 3: 关闭托管账户，租金返还给创建者
+        // =====================================================================
+        ProgramAccount::close(
+            self.accounts.escrow,     // 要关闭的账户：托管账户
+            self.accounts.maker       // 接收 lamports 的账户：创建者
+        )?;
+
+        Ok(())
+    }
+}
@@ -227,6 +227,27 @@ impl AccountCheck for MintInterface {
     }
 }
 
+// =============================================================================
+// TokenProgramInterface - Token Program 验证
+// =============================================================================
+// 对应 Anchor 的约束：Interface<'info, TokenInterface>
+//
+// Anchor 版本：
+//   pub token_program: Interface<'info, TokenInterface>,
+//
+// 功能：
+// - 验证账户是 Token Program 或 Token-2022 Program
+pub struct TokenProgramInterface;
+
+impl AccountCheck for TokenProgramInterface {
+    fn check(account: &AccountView) -> Result<(), ProgramError> {
+        if account.address().ne(&pinocchio_token::ID) && account.address().ne(&TOKEN_2022_PROGRAM_ID) {
+            return Err(EscrowError::InvalidTokenProgram.into());
+        }
+        Ok(())
+    }
+}
+
 // =============================================================================
 // TokenAccountInterface - Token Account 验证
 // =============================================================================
@@ -421,6 +442,30 @@ impl AccountCheck for ProgramAccount {
     }
 }
 
+// =============================================================================
+// LegacyProgramAccount - 同时接受旧版布局的托管账户验证
+// =============================================================================
+// 与 ProgramAccount 相同，但数据长度也可以是旧版的 Escrow::LEGACY_LEN
+//
+// 只用于 Refund：旧版托管没有 deposit、remaining 等字段，不能再被接受，
+// 创建者仍然需要能取回金库中的代币 A
+pub struct LegacyProgramAccount;
+
+impl AccountCheck for LegacyProgramAccount {
+    fn check(account: &AccountView) -> Result<(), ProgramError> {
+        if !account.owned_by(&crate::ID) {
+            return Err(EscrowError::InvalidOwner.into());
+        }
+
+        // 当前布局或旧版布局
+        if !matches!(account.data_len(), crate::state::Escrow::LEN | crate::state::Escrow::LEGACY_LEN) {
+            return Err(EscrowError::InvalidAccountData.into());
+        }
+
+        Ok(())
+    }
+}
+
 // =============================================================================
 // ProgramAccountInit Trait - 程序账户初始化
 // =============================================================================
@@ -9,7 +9,10 @@
 use pinocchio::{Address, AccountView, ProgramResult};
 use pinocchio::cpi::Seed;
 use pinocchio::error::ProgramError;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
 use pinocchio_token::instructions::Transfer;
+use crate::errors::EscrowError;
 use crate::{AccountCheck, SignerAccount, MintInterface, AssociatedTokenAccount, AssociatedTokenAccountCheck, ProgramAccount, Escrow, ProgramAccountInit, AssociatedTokenAccountInit};
 
 // =============================================================================
@@ -263,6 +266,14 @@ pub struct MakeInstructionData {
     // 实际存入的代币 A 数量
     // 对应 Anchor: handler 参数 amount
     pub amount: u64,
+
+    // 过期时间（Unix 时间戳，秒），可选
+    // 指令数据中省略时为 0，表示永不过期
+    pub expiry: i64,
+
+    // 指定的接受者，可选
+    // 指令数据中省略时为全 0，表示任何人都可以接受
+    pub taker: Address,
 }
 
 // =============================================================================
@@ -275,9 +286,15 @@ impl<'info> TryFrom<&'info [u8]> for MakeInstructionData {
     // 从字节数组解析指令数据
     // 对应 Anchor 自动将 instruction_data 解析为函数参数
     fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
-        // 验证数据长度：3 个 u64 = 24 字节
+        // 验证数据长度：
+        // - 3 个 u64 = 24 字节
+        // - 再加上 8 字节的过期时间 = 32 字节
+        // - 再加上 32 字节的接受者地址 = 64 字节（不需要过期时间时传 0）
         // 对应 Anchor 自动验证参数类型
-        if data.len() != size_of::<u64>() * 3 {
+        const BASE_LEN: usize = size_of::<u64>() * 3;
+        const EXPIRY_LEN: usize = BASE_LEN + size_of::<i64>();
+        const TAKER_LEN: usize = EXPIRY_LEN + size_of::<Address>();
+        if !matches!(data.len(), BASE_LEN | EXPIRY_LEN | TAKER_LEN) {
             return Err(ProgramError::InvalidInstructionData);
         }
 
@@ -287,6 +304,18 @@ impl<'info> TryFrom<&'info [u8]> for MakeInstructionData {
         let receive = u64::from_le_bytes(data[8..16].try_into().unwrap());
         let amount = u64::from_le_bytes(data[16..24].try_into().unwrap());
 
+        // 可选的过期时间，省略时永不过期
+        let expiry = match data.get(24..32) {
+            Some(bytes) => i64::from_le_bytes(bytes.try_into().unwrap()),
+            None => 0,
+        };
+
+        // 可选的接受者，省略时不限制
+        let taker = match data.get(32..64) {
+            Some(bytes) => Address::new_from_array(bytes.try_into().unwrap()),
+            None => Address::new_from_array([0; 32]),
+        };
+
         // =====================================================================
         // 业务逻辑验证
         // =====================================================================
@@ -305,6 +334,8 @@ impl<'info> TryFrom<&'info [u8]> for MakeInstructionData {
             seed,
             receive,
             amount,
+            expiry,
+            taker,
         })
     }
 }
@@ -496,6 +527,12 @@ impl<'info> Make<'info> {
     // - 直接在 process 方法中实现业务逻辑
     // - 不需要单独的 populate_escrow 和 deposit_token 方法
     pub fn process(&mut self) -> ProgramResult {
+        // 设置了过期时间时，过期时间必须晚于当前时间
+        // 否则创建出来的托管立即过期，无法被接受
+        if self.instruction_data.expiry != 0 && Clock::get()?.unix_timestamp >= self.instruction_data.expiry {
+            return Err(EscrowError::EscrowExpired.into());
+        }
+
         // =====================================================================
         // 步骤 1: 初始化托管账户数据
         // =====================================================================
@@ -537,8 +574,11 @@ impl<'info> Make<'info> {
             self.instruction_data.seed,                   // seed：PDA 派生种子
             self.accounts.maker.address().clone(),        // maker：创建者地址
             self.accounts.mint_a.address().clone(),       // mint_a：代币 A mint
-            self.accounts.mint_b.address().clone(),       // mint_b：代币 B mint
+            *self.accounts.mint_b.address(),              // mint_b：代币 B mint
             self.instruction_data.receive.clone(),        // receive：期望数量
+            self.instruction_data.amount,                 // deposit：存入的代币 A 数量
+            self.instruction_data.expiry,                 // expiry：过期时间
+            self.instruction_data.taker,                  // taker：指定的接受者
             [self.bump],                                 // bump：PDA bump 种子
         );
 
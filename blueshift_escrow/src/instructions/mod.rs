@@ -1,9 +1,15 @@
 mod make;
 mod helpers;
 mod take;
+mod take_partial;
 mod refund;
+mod expire;
+mod amend;
 
 pub use refund::*;
+pub use expire::*;
+pub use amend::*;
 pub use helpers::*;
 pub use take::*;
+pub use take_partial::*;
 pub use make::*;
\ No newline at end of file
@@ -17,7 +17,7 @@ use pinocchio::cpi::{Seed, Signer};
 use pinocchio::error::ProgramError;
 use pinocchio_token::instructions::{CloseAccount, Transfer};
 use solana_address::Address;
-use crate::{AccountCheck, AccountClose, AssociatedTokenAccount, AssociatedTokenAccountInit, Escrow, MintInterface, ProgramAccount, SignerAccount};
+use crate::{AccountCheck, AccountClose, AssociatedTokenAccount, AssociatedTokenAccountInit, Escrow, LegacyProgramAccount, MintInterface, ProgramAccount, SignerAccount};
 
 // =============================================================================
 // RefundAccount 账户结构体
@@ -129,7 +129,8 @@ impl<'info> TryFrom<&'info [AccountView]> for RefundAccount<'info> {
         // 验证 escrow 是本程序拥有的账户
         // 对应 Anchor: pub escrow: Box<Account<'info, Escrow>>
         // Account<T> 自动验证 owner 和数据长度
-        ProgramAccount::check(escrow)?;
+        // 同时接受旧版布局，升级前创建的托管也可以退款
+        LegacyProgramAccount::check(escrow)?;
 
         // 验证 mint_a 是有效的 Mint 账户
         // 对应 Anchor: pub mint_a: InterfaceAccount<'info, Mint>
@@ -257,9 +258,9 @@ impl<'info> Refund<'info> {
             // 借用托管账户数据（只读）
             let data = self.accounts.escrow.try_borrow()?;
 
-            // 将字节数组解析为 Escrow 结构体
-            // unsafe transmute 将字节指针转换为结构体指针
-            let escrow = Escrow::load(&data)?;
+            // 读取 seed 和 bump
+            // 同时支持旧版布局，升级前创建的托管也可以退款
+            let (seed, bump) = Escrow::load_seeds(&data)?;
 
             // =================================================================
             // PDA 验证（额外安全检查）
@@ -286,8 +287,8 @@ impl<'info> Refund<'info> {
                 &[
                     b"escrow",                                    // 固定前缀
                     self.accounts.maker.address().as_ref(),     // 创建者地址
-                    &escrow.seed.to_le_bytes(),                  // 从账户中读取的 seed
-                    &escrow.bump,                                // 从账户中读取的 bump
+                    &seed.to_le_bytes(),                         // 从账户中读取的 seed
+                    &bump,                                       // 从账户中读取的 bump
                 ],
                 &crate::ID  // 程序 ID
             )?;
@@ -303,7 +304,7 @@ impl<'info> Refund<'info> {
 
             // 提取需要的字段
             // 注意：不需要 mint_b 和 receive 字段
-            (escrow.seed, escrow.bump)
+            (seed, bump)
         }; // ← data 在这里自动释放，借用结束
 
         // =====================================================================
@@ -9,8 +9,11 @@
 use pinocchio::{Address, AccountView, ProgramResult};
 use pinocchio::cpi::{Seed, Signer};
 use pinocchio::error::ProgramError;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
 use pinocchio_token::instructions::{CloseAccount, Transfer};
-use crate::{AccountCheck, SignerAccount, MintInterface, AssociatedTokenAccount, AssociatedTokenAccountCheck, ProgramAccount, AssociatedTokenAccountInit, Escrow, AccountClose};
+use crate::errors::EscrowError;
+use crate::{AccountCheck, SignerAccount, MintInterface, TokenAccountInterface, TokenProgramInterface, AssociatedTokenAccount, AssociatedTokenAccountCheck, ProgramAccount, AssociatedTokenAccountInit, Escrow, AccountClose};
 
 // =============================================================================
 // TakeAccounts 账户结构体
@@ -137,6 +140,10 @@ impl<'info> TryFrom<&'info [AccountView]> for TakeAccounts<'info> {
         // Account<T> 自动验证 owner 和数据长度
         ProgramAccount::check(escrow)?;
 
+        // 验证 token_program 是 Token / Token-2022 Program
+        // 对应 Anchor: pub token_program: Interface<'info, TokenInterface>
+        TokenProgramInterface::check(token_program)?;
+
         // 验证 mint_a 是有效的 Mint 账户
         // 对应 Anchor: pub mint_a: Box<InterfaceAccount<'info,Mint>>
         MintInterface::check(mint_a)?;
@@ -145,6 +152,48 @@ impl<'info> TryFrom<&'info [AccountView]> for TakeAccounts<'info> {
         // 对应 Anchor: pub mint_b: Box<InterfaceAccount<'info,Mint>>
         MintInterface::check(mint_b)?;
 
+        // =====================================================================
+        // has_one 约束验证
+        // =====================================================================
+        // 对应 Anchor:
+        //   has_one = maker @ EscrowError::InvalidMaker,
+        //   has_one = mint_a @ EscrowError::InvalidMintA,
+        //   has_one = mint_b @ EscrowError::InvalidMintB,
+        //
+        // Anchor 在反序列化 escrow 之后自动比较字段和传入的账户地址
+        // Pinocchio 手动读取托管账户数据进行比较
+        {
+            let data = escrow.try_borrow()?;
+            let escrow_state = Escrow::load(&data)?;
+
+            // 验证 maker 是托管中记录的创建者
+            if &escrow_state.maker != maker.address() {
+                return Err(EscrowError::InvalidMaker.into());
+            }
+
+            // 验证 mint_a 和 mint_b 是托管中记录的代币
+            if &escrow_state.mint_a != mint_a.address() {
+                return Err(EscrowError::InvalidMintA.into());
+            }
+            if &escrow_state.mint_b != mint_b.address() {
+                return Err(EscrowError::InvalidMintB.into());
+            }
+
+            // 验证 taker 是托管指定的接受者（如果指定了）
+            // 对应 Anchor: constraint = escrow.taker.map_or(true, |t| t == taker.key())
+            //              @ EscrowError::InvalidTaker
+            if !escrow_state.can_take(taker.address()) {
+                return Err(EscrowError::InvalidTaker.into());
+            }
+        } // ← data 在这里自动释放
+
+        // 验证两个 Mint 都由传入的 token_program 拥有
+        // 对应 Anchor: associated_token::token_program = token_program
+        // 所有 ATA 都按这个 token_program 派生，Mint 的所有者不一致时转账会失败
+        if !mint_a.owned_by(token_program.address()) || !mint_b.owned_by(token_program.address()) {
+            return Err(EscrowError::InvalidTokenProgram.into());
+        }
+
         // 验证 taker_ata_b 是正确的 ATA
         // 对应 Anchor: #[account(init_if_needed, payer = taker,
         //            associated_token::mint = mint_b,
@@ -157,8 +206,11 @@ impl<'info> TryFrom<&'info [AccountView]> for TakeAccounts<'info> {
         //            associated_token::authority = escrow, ...)]
         AssociatedTokenAccount::check(vault, escrow, mint_a, token_program)?;
 
-        // 注意：taker_ata_a 和 maker_ata_b 不在这里验证
-        // 因为它们可能不存在，会在 init_if_needed 中处理
+        // taker_ata_a 和 maker_ata_b 可能不存在，会在 init_if_needed 中创建
+        // 已经存在时验证 owner 和 mint 字段
+        // 对应 Anchor init_if_needed 对已存在账户的 associated_token::authority / mint 检查
+        check_existing_token_account(taker_ata_a, taker, mint_a)?;
+        check_existing_token_account(maker_ata_b, maker, mint_b)?;
 
         // 返回验证通过的账户结构
         Ok(Self {
@@ -178,32 +230,41 @@ impl<'info> TryFrom<&'info [AccountView]> for TakeAccounts<'info> {
 }
 
 // =============================================================================
-// Take 指令主结构体
+// 已存在的代币账户验证
 // =============================================================================
-// 对应 Anchor 的 Context<Take>
-pub struct Take<'info> {
-    pub accounts: TakeAccounts<'info>,
+// 账户不存在（没有数据）时跳过，交给 init_if_needed 创建
+// 存在时必须是代币账户，并且 owner 和 mint 字段与预期一致：
+//
+// Token Account 的数据结构：
+// - mint: 偏移量 0-32
+// - owner: 偏移量 32-64
+fn check_existing_token_account(
+    account: &AccountView,
+    owner: &AccountView,
+    mint: &AccountView,
+) -> Result<(), ProgramError> {
+    if account.data_len() == 0 {
+        return Ok(());
+    }
+
+    TokenAccountInterface::check(account)?;
+
+    let data = account.try_borrow()?;
+    if &data[32..64] != owner.address().as_ref() {
+        return Err(EscrowError::InvalidTokenAccountOwner.into());
+    }
+    if &data[0..32] != mint.address().as_ref() {
+        return Err(EscrowError::InvalidTokenAccountMint.into());
+    }
+    Ok(())
 }
 
 // =============================================================================
-// TryFrom 实现 - 指令完整解析与账户初始化
+// 条件账户初始化
 // =============================================================================
-// 对应 Anchor 的 Context 解析 + init_if_needed 约束处理
-impl<'info> TryFrom<&'info [AccountView]> for Take<'info> {
-    type Error = ProgramError;
-
-    // 从账户数组中解析完整的指令
-    // 对应 Anchor 自动进行的：
-    // 1. 账户验证（#[account] 宏）
-    // 2. init_if_needed 约束处理（如果账户不存在则创建）
-    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
-        // 步骤 1: 解析和验证账户
-        // 对应 Anchor 的账户验证阶段
-        let accounts = TakeAccounts::try_from(accounts)?;
-
-        // =====================================================================
-        // 条件账户初始化
-        // =====================================================================
+// Take 和 TakePartial 在账户验证之后都需要创建可能不存在的 ATA
+impl<'info> TakeAccounts<'info> {
+    pub fn init_if_needed(&self) -> ProgramResult {
         // 对应 Anchor 的 init_if_needed 约束
         //
         // Anchor 版本（take_anchor.rs:88-98）：
@@ -226,33 +287,101 @@ impl<'info> TryFrom<&'info [AccountView]> for Take<'info> {
         // - 先尝试验证账户（check）
         // - 如果验证失败，说明账户不存在，调用 init 创建
         AssociatedTokenAccount::init_if_needed(
-            accounts.taker_ata_a,     // 要创建/验证的账户
-            accounts.mint_a,          // mint 账户
-            accounts.taker,           // payer：对应 Anchor 的 payer = taker
-            accounts.taker,           // owner：对应 Anchor 的 authority = taker
-            accounts.system_program,  // System Program
-            accounts.token_program,   // Token Program
+            self.taker_ata_a,     // 要创建/验证的账户
+            self.mint_a,          // mint 账户
+            self.taker,           // payer：对应 Anchor 的 payer = taker
+            self.taker,           // owner：对应 Anchor 的 authority = taker
+            self.system_program,  // System Program
+            self.token_program,   // Token Program
         )?;
 
         // 创建创建者的代币 B ATA（如果不存在）
         // 对应 Anchor: pub maker_ata_b 的 init_if_needed 约束
         // （take_anchor.rs:112-119）
         AssociatedTokenAccount::init_if_needed(
-            accounts.maker_ata_b,     // 要创建/验证的账户
-            accounts.mint_b,          // mint 账户
-            accounts.taker,           // payer：对应 Anchor 的 payer = taker
-            accounts.maker,           // owner：对应 Anchor 的 authority = maker
-            accounts.system_program,  // System Program
-            accounts.token_program,   // Token Program
+            self.maker_ata_b,     // 要创建/验证的账户
+            self.mint_b,          // mint 账户
+            self.taker,           // payer：对应 Anchor 的 payer = taker
+            self.maker,           // owner：对应 Anchor 的 authority = maker
+            self.system_program,  // System Program
+            self.token_program,   // Token Program
         )?;
 
+        Ok(())
+    }
+}
+
+// =============================================================================
+// TakeInstructionData 指令数据结构体
+// =============================================================================
+// 创建者可以在接受交易上链之前通过 Amend 修改条款（提高期望数量、减少金库中的代币 A），
+// 接受者用这两个值限定愿意成交的条款，条款不符时返回 SlippageExceeded
+pub struct TakeInstructionData {
+    // 最多支付的代币 B 数量（托管的剩余数量）
+    pub max_pay: u64,
+
+    // 最少获得的代币 A 数量（金库中的代币数量）
+    pub min_receive: u64,
+}
+
+impl<'info> TryFrom<&'info [u8]> for TakeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        // 验证数据长度：2 个 u64 = 16 字节
+        if data.len() != size_of::<u64>() * 2 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let max_pay = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let min_receive = u64::from_le_bytes(data[8..16].try_into().unwrap());
+
+        Ok(Self {
+            max_pay,
+            min_receive,
+        })
+    }
+}
+
+// =============================================================================
+// Take 指令主结构体
+// =============================================================================
+// 对应 Anchor 的 Context<Take>
+pub struct Take<'info> {
+    pub accounts: TakeAccounts<'info>,
+    pub instruction_data: TakeInstructionData,
+}
+
+// =============================================================================
+// TryFrom 实现 - 指令完整解析与账户初始化
+// =============================================================================
+// 对应 Anchor 的 Context 解析 + init_if_needed 约束处理
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for Take<'info> {
+    type Error = ProgramError;
+
+    // 从指令数据和账户数组中解析完整的指令
+    // 对应 Anchor 自动进行的：
+    // 1. 账户验证（#[account] 宏）
+    // 2. init_if_needed 约束处理（如果账户不存在则创建）
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        // 步骤 1: 解析和验证账户
+        // 对应 Anchor 的账户验证阶段
+        let accounts = TakeAccounts::try_from(accounts)?;
+        let instruction_data = TakeInstructionData::try_from(data)?;
+
+        // 步骤 2: 创建可能不存在的 ATA
+        // 对应 Anchor 的 init_if_needed 约束
+        accounts.init_if_needed()?;
+
         // 返回完整的指令结构
         Ok(Self {
             accounts,
+            instruction_data,
         })
     }
 }
 
+
 // =============================================================================
 // Take 指令的业务逻辑实现
 // =============================================================================
@@ -286,7 +415,7 @@ impl<'info> Take<'info> {
 
         // 使用代码块来限制借用生命周期
         // 确保借用在步骤 2 开始前释放
-        let (seed, receive, bump) = {
+        let (seed, remaining, bump) = {
             // 借用托管账户数据（只读）
             let data = self.accounts.escrow.try_borrow()?;
 
@@ -333,9 +462,15 @@ impl<'info> Take<'info> {
                 return Err(ProgramError::InvalidAccountOwner);
             }
 
+            // 过期检查：到达过期时间后不能再接受，只能由 Expire 或 Refund 退还代币 A
+            if escrow.is_expired(Clock::get()?.unix_timestamp) {
+                return Err(EscrowError::EscrowExpired.into());
+            }
+
             // 提取需要的字段
             // 这些值在后续步骤中会用到
-            (escrow.seed, escrow.receive, escrow.bump)
+            // 托管可能已经部分成交（见 take_partial.rs），只需要支付剩余的代币 B
+            (escrow.seed, escrow.remaining, escrow.bump)
         }; // ← data 在这里自动释放，借用结束
 
         // =====================================================================
@@ -386,6 +521,15 @@ impl<'info> Take<'info> {
             u64::from_le_bytes(vault_data[64..72].try_into().unwrap())
         }; // ← vault_data 在这里自动释放
 
+        // =====================================================================
+        // 条款检查
+        // =====================================================================
+        // 创建者可能在这笔交易之前通过 Amend 提高了期望数量或取回了部分代币 A，
+        // 实际需要支付的代币 B 和获得的代币 A 必须在接受者给出的范围内
+        if remaining > self.instruction_data.max_pay || amount < self.instruction_data.min_receive {
+            return Err(EscrowError::SlippageExceeded.into());
+        }
+
         // =====================================================================
         // 业务逻辑执行
         // =====================================================================
@@ -481,12 +625,13 @@ impl<'info> Take<'info> {
         // Pinocchio 版本使用 Transfer 指令
 
         // 转账代币 B 从接受者到创建者
-        // 数量：托管账户中记录的期望数量（receive 字段）
+        // 数量：托管账户中记录的剩余数量（remaining 字段）
+        // 没有部分成交时等于期望数量（receive 字段）
         Transfer {
             from: self.accounts.taker_ata_b,    // 从：接受者的代币 B ATA
             to: self.accounts.maker_ata_b,      // 到：创建者的代币 B ATA
             authority: self.accounts.taker,     // 权限：接受者必须签名
-            amount: receive,                    // 转账数量：托管账户中记录的数量
+            amount: remaining,                  // 转账数量：托管账户中记录的剩余数量
         }.invoke()?;  // ← 普通调用，接受者已签名
 
         // =====================================================================
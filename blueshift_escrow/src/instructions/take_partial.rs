@@ -0,0 +1,207 @@
+// =============================================================================
+// TakePartial 指令 - Pinocchio 版本
+// =============================================================================
+// 本指令用于部分接受一个现有的托管交易
+// 接受者向创建者发送 amount 个代币 B，并按创建时的比例从金库中获得代币 A：
+//
+//   释放的代币 A = amount * deposit / receive（向下取整）
+//
+// 账户与 Take 指令完全相同，指令数据为 [amount: u64][min_receive: u64]
+//
+// 与 Take 的区别：
+// - Take 一次性支付全部剩余的代币 B，取走金库中的全部代币 A
+// - TakePartial 只支付一部分，托管账户记录剩余数量（remaining 字段），
+//   金库和托管账户保持打开，其他接受者可以继续成交
+// - 当 amount 等于剩余数量时完全成交，与 Take 一样取走金库中的全部代币 A
+//   并关闭金库和托管账户，租金返还给创建者
+
+use pinocchio::{Address, AccountView, ProgramResult};
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use pinocchio_token::instructions::{CloseAccount, Transfer};
+use crate::errors::EscrowError;
+use crate::{TakeAccounts, ProgramAccount, Escrow, AccountClose};
+
+// =============================================================================
+// TakePartialInstructionData 指令数据结构体
+// =============================================================================
+pub struct TakePartialInstructionData {
+    // 本次支付的代币 B 数量
+    // 必须大于 0，且不超过托管账户中记录的剩余数量
+    pub amount: u64,
+
+    // 最少获得的代币 A 数量
+    // 创建者可能在这笔交易之前通过 Amend 修改了比例，释放数量不足时返回 SlippageExceeded
+    pub min_receive: u64,
+}
+
+impl<'info> TryFrom<&'info [u8]> for TakePartialInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        // 验证数据长度：2 个 u64 = 16 字节
+        if data.len() != size_of::<u64>() * 2 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let min_receive = u64::from_le_bytes(data[8..16].try_into().unwrap());
+
+        Ok(Self { amount, min_receive })
+    }
+}
+
+// =============================================================================
+// TakePartial 指令主结构体
+// =============================================================================
+pub struct TakePartial<'info> {
+    pub accounts: TakeAccounts<'info>,
+    pub instruction_data: TakePartialInstructionData,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for TakePartial<'info> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        // 账户验证和 init_if_needed 与 Take 完全相同，直接复用
+        let accounts = TakeAccounts::try_from(accounts)?;
+        let instruction_data = TakePartialInstructionData::try_from(data)?;
+        accounts.init_if_needed()?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+// =============================================================================
+// TakePartial 指令的业务逻辑实现
+// =============================================================================
+impl<'info> TakePartial<'info> {
+    // 指令判别器
+    pub const DISCRIMINATOR: &'info u8 = &3;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let amount = self.instruction_data.amount;
+
+        // =====================================================================
+        // 读取并更新托管账户数据
+        // =====================================================================
+        // 与 Take 相同，先验证 PDA，再计算本次释放的代币 A 数量
+        // 剩余数量在 CPI 之前写入，代码块结束时释放可变借用
+        let (seed, bump, release, filled) = {
+            let mut data = self.accounts.escrow.try_borrow_mut()?;
+            let escrow = Escrow::load_mut(data.as_mut())?;
+
+            // 重新计算 PDA 地址以验证账户有效性
+            let escrow_key = Address::create_program_address(
+                &[
+                    b"escrow",                                    // 固定前缀
+                    self.accounts.maker.address().as_ref(),     // 创建者地址
+                    &escrow.seed.to_le_bytes(),                  // 从账户中读取的 seed
+                    &escrow.bump,                                // 从账户中读取的 bump
+                ],
+                &crate::ID  // 程序 ID
+            )?;
+            if &escrow_key != self.accounts.escrow.address() {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+
+            // 与 Take 相同，过期后不能再成交
+            if escrow.is_expired(Clock::get()?.unix_timestamp) {
+                return Err(EscrowError::EscrowExpired.into());
+            }
+
+            // 按创建时的比例计算释放数量
+            // amount 为 0、超过剩余数量或释放数量为 0 时返回 InvalidTakeAmount
+            let release = escrow.release_for(amount)?;
+
+            // 记录剩余数量，为 0 表示完全成交
+            let remaining = escrow.remaining - amount;
+            escrow.set_remaining(remaining);
+
+            (escrow.seed, escrow.bump, release, remaining == 0)
+        }; // ← data 在这里自动释放，借用结束
+
+        // =====================================================================
+        // 构造 PDA 签名种子
+        // =====================================================================
+        let seed_binding = seed.to_le_bytes();
+        let bump_binding = bump;
+        let escrow_seeds = [
+            Seed::from(b"escrow"),                           // 种子 1: "escrow"
+            Seed::from(self.accounts.maker.address().as_ref()),  // 种子 2: maker 地址
+            Seed::from(&seed_binding),                       // 种子 3: seed 的字节数组
+            Seed::from(&bump_binding),                       // 种子 4: bump
+        ];
+        let signer = [Signer::from(&escrow_seeds)];
+
+        // =====================================================================
+        // 步骤 1: 从接受者转账代币 B 给创建者
+        // =====================================================================
+        Transfer {
+            from: self.accounts.taker_ata_b,    // 从：接受者的代币 B ATA
+            to: self.accounts.maker_ata_b,      // 到：创建者的代币 B ATA
+            authority: self.accounts.taker,     // 权限：接受者必须签名
+            amount,                             // 转账数量：本次成交的代币 B
+        }.invoke()?;
+
+        // =====================================================================
+        // 步骤 2: 部分成交，按比例释放代币 A
+        // =====================================================================
+        // 金库和托管账户保持打开，等待后续成交或创建者退款
+        if !filled {
+            if release < self.instruction_data.min_receive {
+                return Err(EscrowError::SlippageExceeded.into());
+            }
+
+            Transfer {
+                from: self.accounts.vault,        // 从：金库账户
+                to: self.accounts.taker_ata_a,    // 到：接受者的代币 A ATA
+                authority: self.accounts.escrow,  // 权限：escrow PDA（需要签名）
+                amount: release,                  // 转账数量：按比例释放的代币 A
+            }.invoke_signed(&signer)?;
+
+            return Ok(());
+        }
+
+        // =====================================================================
+        // 步骤 3: 完全成交，与 Take 相同
+        // =====================================================================
+        // 释放金库中的全部代币 A（包括之前向下取整留下的零头），
+        // 然后关闭金库和托管账户
+        let vault_amount = {
+            let vault_data = self.accounts.vault.try_borrow()?;
+
+            // Token Account 的 amount 字段（偏移量 64，长度 8）
+            u64::from_le_bytes(vault_data[64..72].try_into().unwrap())
+        }; // ← vault_data 在这里自动释放
+
+        if vault_amount < self.instruction_data.min_receive {
+            return Err(EscrowError::SlippageExceeded.into());
+        }
+
+        Transfer {
+            from: self.accounts.vault,        // 从：金库账户
+            to: self.accounts.taker_ata_a,    // 到：接受者的代币 A ATA
+            authority: self.accounts.escrow,  // 权限：escrow PDA（需要签名）
+            amount: vault_amount,             // 转账数量：金库中的全部代币
+        }.invoke_signed(&signer)?;
+
+        CloseAccount {
+            account: self.accounts.vault,       // 要关闭的账户：金库
+            destination: self.accounts.maker,   // 接收 lamports 的账户：创建者
+            authority: self.accounts.escrow,    // 权限：escrow PDA（金库的 owner）
+        }.invoke_signed(&signer)?;
+
+        ProgramAccount::close(
+            self.accounts.escrow,     // 要关闭的账户：托管账户
+            self.accounts.maker       // 接收 lamports 的账户：创建者
+        )?;
+
+        Ok(())
+    }
+}
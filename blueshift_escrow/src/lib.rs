@@ -21,7 +21,7 @@ entrypoint!(process_instruction);
 // 模块声明与导出
 // =============================================================================
 
-// instructions 模块：包含所有指令处理器（Make, Take, Refund）
+// instructions 模块：包含所有指令处理器（Make, Take, Refund, TakePartial, Expire, Amend）
 pub mod instructions;
 pub use instructions::*;
 
@@ -146,13 +146,25 @@ fn process_instruction(
         Some((Make::DISCRIMINATOR, data)) => Make::try_from((data, accounts))?.process(),
 
         // Take 指令：接受托管交易
-        // - 无额外数据，只需要账户列表
-        Some((Take::DISCRIMINATOR, _)) => Take::try_from(accounts)?.process(),
+        // - 数据为最多支付的代币 B 数量和最少获得的代币 A 数量
+        Some((Take::DISCRIMINATOR, data)) => Take::try_from((data, accounts))?.process(),
 
         // Refund 指令：取消托管交易并退款
         // - 无额外数据，只需要账户列表
         Some((Refund::DISCRIMINATOR, _)) => Refund::try_from(accounts)?.process(),
 
+        // TakePartial 指令：部分接受托管交易
+        // - 数据为本次支付的代币 B 数量和最少获得的代币 A 数量，账户与 Take 相同
+        Some((TakePartial::DISCRIMINATOR, data)) => TakePartial::try_from((data, accounts))?.process(),
+
+        // Expire 指令：关闭已过期的托管并把代币 A 退还给创建者
+        // - 无额外数据，任何人都可以调用
+        Some((Expire::DISCRIMINATOR, _)) => Expire::try_from(accounts)?.process(),
+
+        // Amend 指令：创建者修改尚未成交的托管
+        // - 数据为新的期望数量和金库中代币 A 的目标数量
+        Some((Amend::DISCRIMINATOR, data)) => Amend::try_from((data, accounts))?.process(),
+
         // 如果判别器不匹配任何已知指令，返回错误
         _ => Err(ProgramError::InvalidInstructionData)
     }
@@ -6,6 +6,7 @@
 use pinocchio::Address;
 use pinocchio::error::ProgramError;
 use core::mem::size_of;
+use crate::errors::EscrowError;
 
 // =============================================================================
 // Escrow 托管账户结构体
@@ -44,6 +45,22 @@ pub struct Escrow {
     // 接受者必须发送至少这个数量的代币 B 才能接受交易
     pub receive: u64,
 
+    // 存入数量：创建托管时存入金库的代币 A 数量
+    // 部分成交时按 deposit / receive 的比例释放代币 A
+    pub deposit: u64,
+
+    // 剩余数量：尚未成交的代币 B 数量
+    // 创建时等于 receive，每次部分成交后减少，为 0 时托管完全成交
+    pub remaining: u64,
+
+    // 过期时间：Unix 时间戳（秒），0 表示永不过期
+    // 到达过期时间后不能再接受交易，任何人都可以调用 Expire 把代币 A 退还给创建者
+    pub expiry: i64,
+
+    // 指定的接受者：只有这个地址可以接受交易（Take / TakePartial）
+    // 全 0 表示不限制接受者，任何人都可以接受
+    pub taker: Address,
+
     // Bump 种子：PDA 派生时找到的有效 bump 值
     // Solana 使用 "find_program_address" 查找 PDA，会返回一个 bump 值
     // 验证签名时需要提供这个 bump 值（通常追加在 seeds 后面）
@@ -63,10 +80,28 @@ impl Escrow {
     // - u64: 8 字节
     // - Address: 32 字节
     // - [u8; 1]: 1 字节
-    // 总计：8 + 32 + 32 + 32 + 8 + 1 = 113 字节
+    // 总计：8 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 32 + 1 = 169 字节
     //
     // 用途：创建账户时需要指定空间大小，客户端和程序都需要知道这个值
     pub const LEN: usize = size_of::<u64>()                     // seed: 8 字节
+        + size_of::<Address>()                                  // maker: 32 字节
+        + size_of::<Address>()                                  // mint_a: 32 字节
+        + size_of::<Address>()                                  // mint_b: 32 字节
+        + size_of::<u64>()                                      // receive: 8 字节
+        + size_of::<u64>()                                      // deposit: 8 字节
+        + size_of::<u64>()                                      // remaining: 8 字节
+        + size_of::<i64>()                                      // expiry: 8 字节
+        + size_of::<Address>()                                  // taker: 32 字节
+        + size_of::<[u8;1]>();                                  // bump: 1 字节
+
+    // ------------------------------------------------------------------------
+    // 常量：旧版账户数据长度
+    // ------------------------------------------------------------------------
+    // 添加 deposit、remaining、expiry、taker 之前创建的托管账户使用旧版布局：
+    // seed + maker + mint_a + mint_b + receive + bump = 8 + 32 + 32 + 32 + 8 + 1 = 113 字节
+    //
+    // 旧版托管不能 Take / TakePartial / Amend，只能通过 Refund 取回代币 A
+    pub const LEGACY_LEN: usize = size_of::<u64>()              // seed: 8 字节
         + size_of::<Address>()                                  // maker: 32 字节
         + size_of::<Address>()                                  // mint_a: 32 字节
         + size_of::<Address>()                                  // mint_b: 32 字节
@@ -128,6 +163,23 @@ impl Escrow {
         Ok(unsafe { &*core::mem::transmute::<*const u8, *const Self>(bytes.as_ptr()) })
     }
 
+    // ------------------------------------------------------------------------
+    // 读取 PDA 种子
+    // ------------------------------------------------------------------------
+    // 从账户数据中读取 (seed, bump)，同时支持当前布局和旧版布局
+    //
+    // 两种布局的 seed 都在开头；旧版布局的 bump 在最后一个字节
+    // 用于 Refund，保证旧版托管中的代币 A 可以取回
+    #[inline(always)]
+    pub fn load_seeds(bytes: &[u8]) -> Result<(u64, [u8;1]), ProgramError> {
+        if bytes.len() == Escrow::LEGACY_LEN {
+            let seed = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+            return Ok((seed, [bytes[Escrow::LEGACY_LEN - 1]]));
+        }
+        let escrow = Escrow::load(bytes)?;
+        Ok((escrow.seed, escrow.bump))
+    }
+
     // ------------------------------------------------------------------------
     // Setter 方法：设置各个字段
     // ------------------------------------------------------------------------
@@ -164,6 +216,26 @@ impl Escrow {
         self.receive = receive;
     }
 
+    #[inline(always)]
+    pub fn set_deposit(&mut self, deposit: u64) {
+        self.deposit = deposit;
+    }
+
+    #[inline(always)]
+    pub fn set_remaining(&mut self, remaining: u64) {
+        self.remaining = remaining;
+    }
+
+    #[inline(always)]
+    pub fn set_expiry(&mut self, expiry: i64) {
+        self.expiry = expiry;
+    }
+
+    #[inline(always)]
+    pub fn set_taker(&mut self, taker: Address) {
+        self.taker = taker;
+    }
+
     #[inline(always)]
     pub fn set_bump(&mut self, bump: [u8;1]) {
         self.bump = bump;
@@ -180,18 +252,89 @@ impl Escrow {
     //   mint_a: 存入的代币 mint 地址
     //   mint_b: 请求的代币 mint 地址
     //   receive: 请求的代币数量
+    //   deposit: 存入金库的代币 A 数量
+    //   expiry: 过期时间，0 表示永不过期
+    //   taker: 指定的接受者，全 0 表示任何人都可以接受
     //   bump: PDA bump 种子
     //
+    // remaining 初始化为 receive，表示还没有任何成交
+    //
     // 用途：
     //   在创建托管账户时，一次性初始化所有字段
     //   比逐个调用 setter 方法更高效
     #[inline(always)]
-    pub fn set_inner(&mut self, seed: u64, maker: Address, mint_a: Address, mint_b: Address, receive: u64, bump: [u8;1]) {
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_inner(&mut self, seed: u64, maker: Address, mint_a: Address, mint_b: Address, receive: u64, deposit: u64, expiry: i64, taker: Address, bump: [u8;1]) {
         self.seed = seed;
         self.maker = maker;
         self.mint_a = mint_a;
         self.mint_b = mint_b;
         self.receive = receive;
+        self.deposit = deposit;
+        self.remaining = receive;
+        self.expiry = expiry;
+        self.taker = taker;
         self.bump = bump;
     }
+
+    // ------------------------------------------------------------------------
+    // 成交状态
+    // ------------------------------------------------------------------------
+    // 已经有接受者部分成交：剩余数量少于期望数量
+    // 部分成交之后条款（receive、deposit、mint_b）不能再修改
+    #[inline(always)]
+    pub fn is_partially_filled(&self) -> bool {
+        self.remaining != self.receive
+    }
+
+    // ------------------------------------------------------------------------
+    // 过期检查
+    // ------------------------------------------------------------------------
+    // now 为 Clock 中的 unix_timestamp
+    // 到达过期时间（now >= expiry）即视为过期，expiry 为 0 时永不过期
+    #[inline(always)]
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expiry != 0 && now >= self.expiry
+    }
+
+    // ------------------------------------------------------------------------
+    // 接受者检查
+    // ------------------------------------------------------------------------
+    // 没有指定接受者（全 0）时任何人都可以接受
+    #[inline(always)]
+    pub fn can_take(&self, taker: &Address) -> bool {
+        self.taker == Address::new_from_array([0; 32]) || &self.taker == taker
+    }
+
+    // ------------------------------------------------------------------------
+    // 部分成交
+    // ------------------------------------------------------------------------
+    // 计算接受者支付 amount 个代币 B 后可以获得的代币 A 数量
+    //
+    // 按累计成交量计算：成交后累计释放 filled * deposit / receive 个代币 A，
+    // 减去之前已经释放的部分，向下取整的误差不会在多次成交中累积
+    //
+    // 返回：
+    //   成功：本次释放的代币 A 数量
+    //   失败：amount 为 0、超过剩余数量，或者释放数量为 0
+    //
+    // 最后一次成交（amount == remaining）由调用者释放金库中的全部代币 A
+    #[inline(always)]
+    pub fn release_for(&self, amount: u64) -> Result<u64, ProgramError> {
+        if amount == 0 || amount > self.remaining {
+            return Err(EscrowError::InvalidTakeAmount.into());
+        }
+
+        // remaining <= receive，receive 不为 0
+        let filled_before = (self.receive - self.remaining) as u128;
+        let filled_after = filled_before + amount as u128;
+        let released = |filled: u128| filled * self.deposit as u128 / self.receive as u128;
+
+        // 释放数量不会超过 deposit，可以安全转换为 u64
+        let release = (released(filled_after) - released(filled_before)) as u64;
+        if release == 0 {
+            return Err(EscrowError::InvalidTakeAmount.into());
+        }
+        Ok(release)
+    }
 }
\ No newline at end of file
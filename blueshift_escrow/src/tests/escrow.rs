@@ -0,0 +1,207 @@
+// =============================================================================
+// 托管指令集成测试
+// =============================================================================
+// 部分成交后完全成交、Take 的账户验证、过期托管的关闭、创建者修改条款，以及旧版托管的退款
+
+use solana_sdk::instruction::Instruction;
+use solana_sdk::program_error::ProgramError;
+use solana_sdk::pubkey::Pubkey;
+
+use super::fixtures::{mint_account, token_account, Offer, DEPOSIT, MAKER_BALANCE, NOW, RECEIVE, TAKER_BALANCE};
+use crate::EscrowError;
+
+fn escrow_error(error: EscrowError) -> ProgramError {
+    ProgramError::Custom(error as u32)
+}
+
+// 把指令的第 index 个账户替换为 pubkey，其余账户和指令数据不变
+fn replace_account(mut instruction: Instruction, index: usize, pubkey: Pubkey) -> Instruction {
+    instruction.accounts[index].pubkey = pubkey;
+    instruction
+}
+
+// =============================================================================
+// TakePartial
+// =============================================================================
+
+#[test]
+fn test_partial_fill_then_take_pays_remaining() {
+    let mut offer = Offer::made();
+
+    // 支付 1/5 的代币 B，按比例获得 1/5 的代币 A
+    offer.process_ok(&offer.take_partial_ix(RECEIVE / 5, DEPOSIT / 5));
+
+    assert_eq!(offer.escrow_state().remaining, RECEIVE - RECEIVE / 5);
+    assert_eq!(offer.token_amount(&offer.vault), DEPOSIT - DEPOSIT / 5);
+    assert_eq!(offer.token_amount(&offer.taker_ata_a), DEPOSIT / 5);
+    assert_eq!(offer.token_amount(&offer.maker_ata_b), RECEIVE / 5);
+
+    // Take 只需要支付剩余的代币 B，取走金库中剩余的全部代币 A
+    offer.process_ok(&offer.take_ix(RECEIVE - RECEIVE / 5, DEPOSIT - DEPOSIT / 5));
+
+    assert_eq!(offer.token_amount(&offer.taker_ata_a), DEPOSIT);
+    assert_eq!(offer.token_amount(&offer.taker_ata_b), TAKER_BALANCE - RECEIVE);
+    assert_eq!(offer.token_amount(&offer.maker_ata_b), RECEIVE);
+    assert!(offer.is_closed(&offer.vault));
+    assert!(offer.is_closed(&offer.escrow));
+}
+
+// =============================================================================
+// Take 账户验证
+// =============================================================================
+// Take 的账户顺序：taker, maker, escrow, mint_a, mint_b, vault,
+// taker_ata_a, taker_ata_b, maker_ata_b, system_program, token_program, associated_token_program
+
+#[test]
+fn test_take_rejects_wrong_maker() {
+    let mut offer = Offer::made();
+    let take = replace_account(offer.take_ix(RECEIVE, DEPOSIT), 1, Pubkey::new_unique());
+
+    offer.process_err(&take, escrow_error(EscrowError::InvalidMaker));
+}
+
+#[test]
+fn test_take_rejects_wrong_mints() {
+    let mut offer = Offer::made();
+    let other_mint = Pubkey::new_unique();
+    let other_mint_account = mint_account(&offer.mollusk, &Pubkey::new_unique(), 0);
+    offer.accounts.insert(other_mint, other_mint_account);
+
+    let take = replace_account(offer.take_ix(RECEIVE, DEPOSIT), 3, other_mint);
+    offer.process_err(&take, escrow_error(EscrowError::InvalidMintA));
+
+    let take = replace_account(offer.take_ix(RECEIVE, DEPOSIT), 4, other_mint);
+    offer.process_err(&take, escrow_error(EscrowError::InvalidMintB));
+}
+
+#[test]
+fn test_take_rejects_wrong_token_program() {
+    let mut offer = Offer::made();
+    let (token_2022, token_2022_account) = mollusk_svm_programs_token::token2022::keyed_account();
+    offer.accounts.insert(token_2022, token_2022_account);
+
+    // 两个 Mint 都由 Token Program 拥有，传入 Token-2022 Program 时拒绝
+    let take = replace_account(offer.take_ix(RECEIVE, DEPOSIT), 10, token_2022);
+    offer.process_err(&take, escrow_error(EscrowError::InvalidTokenProgram));
+}
+
+#[test]
+fn test_take_rejects_foreign_existing_token_accounts() {
+    let mut offer = Offer::made();
+
+    // taker_ata_a 已经存在，但 owner 字段不是接受者
+    let foreign = token_account(&offer.mollusk, &offer.mint_a, &Pubkey::new_unique(), 0);
+    offer.accounts.insert(offer.taker_ata_a, foreign);
+    offer.process_err(&offer.take_ix(RECEIVE, DEPOSIT), escrow_error(EscrowError::InvalidTokenAccountOwner));
+    offer.accounts.remove(&offer.taker_ata_a);
+
+    // maker_ata_b 已经存在，但 mint 字段不是代币 B
+    let foreign = token_account(&offer.mollusk, &offer.mint_a, &offer.maker, 0);
+    offer.accounts.insert(offer.maker_ata_b, foreign);
+    offer.process_err(&offer.take_ix(RECEIVE, DEPOSIT), escrow_error(EscrowError::InvalidTokenAccountMint));
+}
+
+// =============================================================================
+// Expire
+// =============================================================================
+
+#[test]
+fn test_expire_before_expiry_fails() {
+    let mut offer = Offer::setup();
+    offer.process_ok(&offer.make_ix(RECEIVE, DEPOSIT, NOW + 60));
+
+    offer.process_err(&offer.expire_ix(offer.taker), escrow_error(EscrowError::EscrowNotExpired));
+    assert_eq!(offer.token_amount(&offer.vault), DEPOSIT);
+
+    // 没有过期时间的托管永远不能被 Expire
+    let mut offer = Offer::made();
+    offer.mollusk.sysvars.clock.unix_timestamp = i64::MAX;
+    offer.process_err(&offer.expire_ix(offer.taker), escrow_error(EscrowError::EscrowNotExpired));
+}
+
+#[test]
+fn test_expire_after_expiry_returns_token_a_to_maker() {
+    let mut offer = Offer::setup();
+    offer.process_ok(&offer.make_ix(RECEIVE, DEPOSIT, NOW + 60));
+    offer.mollusk.sysvars.clock.unix_timestamp = NOW + 60;
+
+    // 过期后不能再接受
+    offer.process_err(&offer.take_partial_ix(RECEIVE / 5, 0), escrow_error(EscrowError::EscrowExpired));
+
+    // 任何人都可以关闭，代币 A 和租金都退还给创建者
+    offer.process_ok(&offer.expire_ix(offer.taker));
+
+    assert_eq!(offer.token_amount(&offer.maker_ata_a), MAKER_BALANCE);
+    assert!(offer.is_closed(&offer.vault));
+    assert!(offer.is_closed(&offer.escrow));
+}
+
+// =============================================================================
+// Amend
+// =============================================================================
+
+#[test]
+fn test_amend_tops_up_and_withdraws_vault() {
+    let mut offer = Offer::made();
+
+    // 增加存入数量：差额从创建者转入金库
+    offer.process_ok(&offer.amend_ix(RECEIVE * 2, DEPOSIT * 2));
+
+    assert_eq!(offer.token_amount(&offer.vault), DEPOSIT * 2);
+    assert_eq!(offer.token_amount(&offer.maker_ata_a), MAKER_BALANCE - DEPOSIT * 2);
+    assert_eq!(offer.escrow_state().receive, RECEIVE * 2);
+    assert_eq!(offer.escrow_state().remaining, RECEIVE * 2);
+    assert_eq!(offer.escrow_state().deposit, DEPOSIT * 2);
+
+    // 减少存入数量：差额从金库退还给创建者
+    offer.process_ok(&offer.amend_ix(RECEIVE / 2, DEPOSIT / 2));
+
+    assert_eq!(offer.token_amount(&offer.vault), DEPOSIT / 2);
+    assert_eq!(offer.token_amount(&offer.maker_ata_a), MAKER_BALANCE - DEPOSIT / 2);
+    assert_eq!(offer.escrow_state().receive, RECEIVE / 2);
+    assert_eq!(offer.escrow_state().deposit, DEPOSIT / 2);
+}
+
+#[test]
+fn test_amend_after_partial_fill_fails() {
+    let mut offer = Offer::made();
+    offer.process_ok(&offer.take_partial_ix(RECEIVE / 5, DEPOSIT / 5));
+
+    offer.process_err(&offer.amend_ix(RECEIVE, DEPOSIT), escrow_error(EscrowError::EscrowPartiallyFilled));
+}
+
+#[test]
+fn test_take_rejects_terms_amended_after_quote() {
+    let mut offer = Offer::made();
+
+    // 接受者按 RECEIVE / DEPOSIT 报价，创建者抢先提高期望数量并取回大部分代币 A
+    offer.process_ok(&offer.amend_ix(RECEIVE * 2, 1));
+
+    offer.process_err(&offer.take_ix(RECEIVE, DEPOSIT), escrow_error(EscrowError::SlippageExceeded));
+    offer.process_err(&offer.take_ix(RECEIVE * 2, DEPOSIT), escrow_error(EscrowError::SlippageExceeded));
+    offer.process_err(
+        &offer.take_partial_ix(RECEIVE * 2, DEPOSIT / 5),
+        escrow_error(EscrowError::SlippageExceeded),
+    );
+    assert_eq!(offer.token_amount(&offer.taker_ata_b), TAKER_BALANCE);
+
+    // 接受新的条款时可以成交
+    offer.process_ok(&offer.take_ix(RECEIVE * 2, 1));
+    assert_eq!(offer.token_amount(&offer.taker_ata_a), 1);
+    assert_eq!(offer.token_amount(&offer.taker_ata_b), TAKER_BALANCE - RECEIVE * 2);
+}
+
+// =============================================================================
+// Refund
+// =============================================================================
+
+#[test]
+fn test_refund_closes_legacy_escrow() {
+    let mut offer = Offer::with_legacy_escrow();
+
+    offer.process_ok(&offer.refund_ix());
+
+    assert_eq!(offer.token_amount(&offer.maker_ata_a), MAKER_BALANCE);
+    assert!(offer.is_closed(&offer.vault));
+    assert!(offer.is_closed(&offer.escrow));
+}
@@ -0,0 +1,397 @@
+// =============================================================================
+// 托管集成测试夹具 - 使用 Mollusk 测试框架
+// =============================================================================
+// 运行前需要先构建程序：cargo build-sbf
+// Mollusk 从 target/deploy/blueshift_escrow.so 加载程序
+//
+// Offer 保存所有账户的当前状态，每条指令执行成功后用结果更新账户，
+// 因此可以像在链上一样按顺序执行 Make、TakePartial、Take 等一系列指令
+
+use std::collections::HashMap;
+
+use mollusk_svm::program::keyed_account_for_system_program;
+use mollusk_svm::result::{Check, InstructionResult};
+use mollusk_svm::Mollusk;
+use solana_account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::program_error::ProgramError;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::Escrow;
+
+// =============================================================================
+// 常量
+// =============================================================================
+pub const SEED: u64 = 42;
+pub const MINT_DECIMALS: u8 = 6;
+pub const MAKER_BALANCE: u64 = 1_000_000_000;
+pub const TAKER_BALANCE: u64 = 1_000_000_000;
+
+// 创建者存入 DEPOSIT 个代币 A，期望获得 RECEIVE 个代币 B
+pub const DEPOSIT: u64 = 1_000_000;
+pub const RECEIVE: u64 = 500_000;
+
+// cargo build-sbf 的输出，Mollusk 按路径加载（不带 .so 后缀）
+const PROGRAM_PATH: &str = "target/deploy/blueshift_escrow";
+
+// 测试中的当前时间
+pub const NOW: i64 = 1_700_000_000;
+
+// SPL Token 账户布局
+const MINT_LEN: usize = 82;
+const TOKEN_ACCOUNT_LEN: usize = 165;
+
+pub fn program_id() -> Pubkey {
+    Pubkey::new_from_array(crate::ID.to_bytes())
+}
+
+pub fn token_program_id() -> Pubkey {
+    mollusk_svm_programs_token::token::keyed_account().0
+}
+
+pub fn associated_token_program_id() -> Pubkey {
+    mollusk_svm_programs_token::associated_token::keyed_account().0
+}
+
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), token_program_id().as_ref(), mint.as_ref()],
+        &associated_token_program_id(),
+    )
+    .0
+}
+
+// =============================================================================
+// 账户数据
+// =============================================================================
+
+// 已初始化的 Mint：[mint_authority: COption<Pubkey>][supply][decimals][is_initialized][freeze_authority]
+pub fn mint_account(mollusk: &Mollusk, authority: &Pubkey, supply: u64) -> Account {
+    let mut data = vec![0u8; MINT_LEN];
+    data[0..4].copy_from_slice(&1u32.to_le_bytes());
+    data[4..36].copy_from_slice(authority.as_ref());
+    data[36..44].copy_from_slice(&supply.to_le_bytes());
+    data[44] = MINT_DECIMALS;
+    data[45] = 1;
+
+    Account {
+        lamports: mollusk.sysvars.rent.minimum_balance(MINT_LEN),
+        data,
+        owner: token_program_id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+// 已初始化的 Token Account：[mint][owner][amount][delegate][state][is_native][delegated_amount][close_authority]
+pub fn token_account(mollusk: &Mollusk, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+    let mut data = vec![0u8; TOKEN_ACCOUNT_LEN];
+    data[0..32].copy_from_slice(mint.as_ref());
+    data[32..64].copy_from_slice(owner.as_ref());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    data[108] = 1;
+
+    Account {
+        lamports: mollusk.sysvars.rent.minimum_balance(TOKEN_ACCOUNT_LEN),
+        data,
+        owner: token_program_id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+// =============================================================================
+// 托管夹具
+// =============================================================================
+pub struct Offer {
+    pub mollusk: Mollusk,
+    pub accounts: HashMap<Pubkey, Account>,
+
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub escrow: Pubkey,
+    pub vault: Pubkey,
+    pub maker_ata_a: Pubkey,
+    pub maker_ata_b: Pubkey,
+    pub taker_ata_a: Pubkey,
+    pub taker_ata_b: Pubkey,
+}
+
+impl Offer {
+    // 创建 Mollusk 环境、两个 Mint，创建者持有代币 A，接受者持有代币 B，托管尚未创建
+    pub fn setup() -> Self {
+        assert!(
+            std::path::Path::new(&format!("{PROGRAM_PATH}.so")).exists(),
+            "{PROGRAM_PATH}.so 不存在，运行集成测试前先执行 cargo build-sbf",
+        );
+        let mut mollusk = Mollusk::new(&program_id(), PROGRAM_PATH);
+        mollusk_svm_programs_token::token::add_program(&mut mollusk);
+        mollusk_svm_programs_token::associated_token::add_program(&mut mollusk);
+        mollusk.sysvars.clock.unix_timestamp = NOW;
+
+        let maker = Pubkey::new_unique();
+        let taker = Pubkey::new_unique();
+        let mint_a = Pubkey::new_unique();
+        let mint_b = Pubkey::new_unique();
+
+        let (escrow, _) = Pubkey::find_program_address(
+            &[b"escrow", maker.as_ref(), &SEED.to_le_bytes()],
+            &program_id(),
+        );
+        let vault = associated_token_address(&escrow, &mint_a);
+        let maker_ata_a = associated_token_address(&maker, &mint_a);
+        let maker_ata_b = associated_token_address(&maker, &mint_b);
+        let taker_ata_a = associated_token_address(&taker, &mint_a);
+        let taker_ata_b = associated_token_address(&taker, &mint_b);
+
+        let mut accounts = HashMap::new();
+        let mint_authority = Pubkey::new_unique();
+        accounts.insert(maker, Account::new(100_000_000_000, 0, &Pubkey::default()));
+        accounts.insert(taker, Account::new(100_000_000_000, 0, &Pubkey::default()));
+        accounts.insert(mint_a, mint_account(&mollusk, &mint_authority, MAKER_BALANCE));
+        accounts.insert(mint_b, mint_account(&mollusk, &mint_authority, TAKER_BALANCE));
+        accounts.insert(maker_ata_a, token_account(&mollusk, &mint_a, &maker, MAKER_BALANCE));
+        accounts.insert(taker_ata_b, token_account(&mollusk, &mint_b, &taker, TAKER_BALANCE));
+
+        let (system_program, system_program_account) = keyed_account_for_system_program();
+        let (token_program, token_program_account) = mollusk_svm_programs_token::token::keyed_account();
+        let (ata_program, ata_program_account) = mollusk_svm_programs_token::associated_token::keyed_account();
+        accounts.insert(system_program, system_program_account);
+        accounts.insert(token_program, token_program_account);
+        accounts.insert(ata_program, ata_program_account);
+
+        Self {
+            mollusk,
+            accounts,
+            maker,
+            taker,
+            mint_a,
+            mint_b,
+            escrow,
+            vault,
+            maker_ata_a,
+            maker_ata_b,
+            taker_ata_a,
+            taker_ata_b,
+        }
+    }
+
+    // 创建者存入 DEPOSIT 个代币 A，期望获得 RECEIVE 个代币 B，没有过期时间和指定接受者
+    pub fn made() -> Self {
+        let mut offer = Self::setup();
+        offer.process_ok(&offer.make_ix(RECEIVE, DEPOSIT, 0));
+        offer
+    }
+
+    // 写入升级前的程序创建的 113 字节托管：seed + maker + mint_a + mint_b + receive + bump
+    // 金库中有 DEPOSIT 个代币 A
+    pub fn with_legacy_escrow() -> Self {
+        let mut offer = Self::setup();
+        let (_, bump) = Pubkey::find_program_address(
+            &[b"escrow", offer.maker.as_ref(), &SEED.to_le_bytes()],
+            &program_id(),
+        );
+
+        let mut data = SEED.to_le_bytes().to_vec();
+        data.extend_from_slice(offer.maker.as_ref());
+        data.extend_from_slice(offer.mint_a.as_ref());
+        data.extend_from_slice(offer.mint_b.as_ref());
+        data.extend_from_slice(&RECEIVE.to_le_bytes());
+        data.push(bump);
+        assert_eq!(data.len(), Escrow::LEGACY_LEN);
+
+        let escrow = Account {
+            lamports: offer.mollusk.sysvars.rent.minimum_balance(data.len()),
+            data,
+            owner: program_id(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        offer.accounts.insert(offer.escrow, escrow);
+
+        let vault = token_account(&offer.mollusk, &offer.mint_a, &offer.escrow, DEPOSIT);
+        offer.accounts.insert(offer.vault, vault);
+        let maker_ata_a = token_account(&offer.mollusk, &offer.mint_a, &offer.maker, MAKER_BALANCE - DEPOSIT);
+        offer.accounts.insert(offer.maker_ata_a, maker_ata_a);
+        offer
+    }
+
+    // =========================================================================
+    // 执行指令
+    // =========================================================================
+
+    pub fn process(&mut self, instruction: &Instruction, checks: &[Check]) -> InstructionResult {
+        let accounts: Vec<(Pubkey, Account)> = instruction
+            .accounts
+            .iter()
+            .map(|meta| {
+                let account = self.accounts.get(&meta.pubkey).cloned().unwrap_or_default();
+                (meta.pubkey, account)
+            })
+            .collect();
+
+        let result = self
+            .mollusk
+            .process_and_validate_instruction(instruction, &accounts, checks);
+
+        // 只有成功的指令才会改变账户
+        if result.program_result.is_ok() {
+            for (key, account) in &result.resulting_accounts {
+                self.accounts.insert(*key, account.clone());
+            }
+        }
+        result
+    }
+
+    pub fn process_ok(&mut self, instruction: &Instruction) -> InstructionResult {
+        self.process(instruction, &[Check::success()])
+    }
+
+    pub fn process_err(&mut self, instruction: &Instruction, error: ProgramError) -> InstructionResult {
+        self.process(instruction, &[Check::err(error)])
+    }
+
+    // =========================================================================
+    // 读取状态
+    // =========================================================================
+
+    pub fn token_amount(&self, account: &Pubkey) -> u64 {
+        let data = &self.accounts[account].data;
+        u64::from_le_bytes(data[64..72].try_into().unwrap())
+    }
+
+    // 托管账户和金库是否已经关闭
+    pub fn is_closed(&self, account: &Pubkey) -> bool {
+        self.accounts
+            .get(account)
+            .is_none_or(|account| account.lamports == 0)
+    }
+
+    pub fn escrow_state(&self) -> &Escrow {
+        Escrow::load(&self.accounts[&self.escrow].data).unwrap()
+    }
+
+    // =========================================================================
+    // 指令构造
+    // =========================================================================
+
+    pub fn make_ix(&self, receive: u64, amount: u64, expiry: i64) -> Instruction {
+        let mut data = vec![*crate::Make::DISCRIMINATOR];
+        data.extend_from_slice(&SEED.to_le_bytes());
+        data.extend_from_slice(&receive.to_le_bytes());
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&expiry.to_le_bytes());
+
+        Instruction::new_with_bytes(
+            program_id(),
+            &data,
+            vec![
+                AccountMeta::new(self.maker, true),
+                AccountMeta::new(self.escrow, false),
+                AccountMeta::new_readonly(self.mint_a, false),
+                AccountMeta::new_readonly(self.mint_b, false),
+                AccountMeta::new(self.maker_ata_a, false),
+                AccountMeta::new(self.vault, false),
+                AccountMeta::new_readonly(keyed_account_for_system_program().0, false),
+                AccountMeta::new_readonly(token_program_id(), false),
+                AccountMeta::new_readonly(associated_token_program_id(), false),
+            ],
+        )
+    }
+
+    // Take 和 TakePartial 使用相同的账户
+    fn take_accounts(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.taker, true),
+            AccountMeta::new(self.maker, false),
+            AccountMeta::new(self.escrow, false),
+            AccountMeta::new_readonly(self.mint_a, false),
+            AccountMeta::new_readonly(self.mint_b, false),
+            AccountMeta::new(self.vault, false),
+            AccountMeta::new(self.taker_ata_a, false),
+            AccountMeta::new(self.taker_ata_b, false),
+            AccountMeta::new(self.maker_ata_b, false),
+            AccountMeta::new_readonly(keyed_account_for_system_program().0, false),
+            AccountMeta::new_readonly(token_program_id(), false),
+            AccountMeta::new_readonly(associated_token_program_id(), false),
+        ]
+    }
+
+    // Take：最多支付 max_pay 个代币 B，至少获得 min_receive 个代币 A
+    pub fn take_ix(&self, max_pay: u64, min_receive: u64) -> Instruction {
+        let mut data = vec![*crate::Take::DISCRIMINATOR];
+        data.extend_from_slice(&max_pay.to_le_bytes());
+        data.extend_from_slice(&min_receive.to_le_bytes());
+
+        Instruction::new_with_bytes(program_id(), &data, self.take_accounts())
+    }
+
+    // TakePartial：支付 amount 个代币 B，至少获得 min_receive 个代币 A
+    pub fn take_partial_ix(&self, amount: u64, min_receive: u64) -> Instruction {
+        let mut data = vec![*crate::TakePartial::DISCRIMINATOR];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&min_receive.to_le_bytes());
+
+        Instruction::new_with_bytes(program_id(), &data, self.take_accounts())
+    }
+
+    // Amend：期望数量改为 receive，金库中的代币 A 调整为 amount，代币 B 保持不变
+    pub fn amend_ix(&self, receive: u64, amount: u64) -> Instruction {
+        let mut data = vec![*crate::Amend::DISCRIMINATOR];
+        data.extend_from_slice(&receive.to_le_bytes());
+        data.extend_from_slice(&amount.to_le_bytes());
+
+        Instruction::new_with_bytes(
+            program_id(),
+            &data,
+            vec![
+                AccountMeta::new(self.maker, true),
+                AccountMeta::new(self.escrow, false),
+                AccountMeta::new_readonly(self.mint_a, false),
+                AccountMeta::new_readonly(self.mint_b, false),
+                AccountMeta::new(self.maker_ata_a, false),
+                AccountMeta::new(self.vault, false),
+                AccountMeta::new_readonly(keyed_account_for_system_program().0, false),
+                AccountMeta::new_readonly(token_program_id(), false),
+                AccountMeta::new_readonly(associated_token_program_id(), false),
+            ],
+        )
+    }
+
+    pub fn refund_ix(&self) -> Instruction {
+        Instruction::new_with_bytes(
+            program_id(),
+            &[*crate::Refund::DISCRIMINATOR],
+            vec![
+                AccountMeta::new(self.maker, true),
+                AccountMeta::new(self.escrow, false),
+                AccountMeta::new_readonly(self.mint_a, false),
+                AccountMeta::new(self.vault, false),
+                AccountMeta::new(self.maker_ata_a, false),
+                AccountMeta::new_readonly(keyed_account_for_system_program().0, false),
+                AccountMeta::new_readonly(token_program_id(), false),
+                AccountMeta::new_readonly(associated_token_program_id(), false),
+            ],
+        )
+    }
+    // Expire：任何人都可以调用，caller 在创建者的代币 A ATA 不存在时支付租金
+    pub fn expire_ix(&self, caller: Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            program_id(),
+            &[*crate::Expire::DISCRIMINATOR],
+            vec![
+                AccountMeta::new(caller, true),
+                AccountMeta::new(self.maker, false),
+                AccountMeta::new(self.escrow, false),
+                AccountMeta::new_readonly(self.mint_a, false),
+                AccountMeta::new(self.vault, false),
+                AccountMeta::new(self.maker_ata_a, false),
+                AccountMeta::new_readonly(keyed_account_for_system_program().0, false),
+                AccountMeta::new_readonly(token_program_id(), false),
+                AccountMeta::new_readonly(associated_token_program_id(), false),
+            ],
+        )
+    }
+}
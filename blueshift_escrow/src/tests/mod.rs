@@ -2,4 +2,7 @@
 // 测试模块入口
 // =============================================================================
 
-pub mod test;
\ No newline at end of file
+pub mod test;
+pub mod state;
+pub mod fixtures;
+pub mod escrow;
\ No newline at end of file
@@ -0,0 +1,135 @@
+// =============================================================================
+// Escrow 状态计算测试
+// =============================================================================
+// release_for、is_expired、can_take 等都是纯计算，不需要 Mollusk 环境
+
+use pinocchio::Address;
+use pinocchio::error::ProgramError;
+
+use crate::{Escrow, EscrowError};
+
+// 存入 deposit 个代币 A，期望获得 receive 个代币 B
+fn escrow(deposit: u64, receive: u64) -> Escrow {
+    Escrow {
+        seed: 0,
+        maker: Address::new_from_array([1; 32]),
+        mint_a: Address::new_from_array([2; 32]),
+        mint_b: Address::new_from_array([3; 32]),
+        receive,
+        deposit,
+        remaining: receive,
+        expiry: 0,
+        taker: Address::new_from_array([0; 32]),
+        bump: [255],
+    }
+}
+
+// 依次成交 amounts，返回每次释放的代币 A 数量
+fn fill(escrow: &mut Escrow, amounts: &[u64]) -> Vec<u64> {
+    amounts
+        .iter()
+        .map(|&amount| {
+            let release = escrow.release_for(amount).unwrap();
+            escrow.set_remaining(escrow.remaining - amount);
+            release
+        })
+        .collect()
+}
+
+#[test]
+fn test_release_is_pro_rata() {
+    let mut escrow = escrow(1_000, 500);
+
+    assert_eq!(fill(&mut escrow, &[100, 150, 250]), vec![200, 300, 500]);
+    assert_eq!(escrow.remaining, 0);
+}
+
+#[test]
+fn test_partially_filled() {
+    let mut escrow = escrow(1_000, 500);
+    assert!(!escrow.is_partially_filled());
+
+    fill(&mut escrow, &[100]);
+    assert!(escrow.is_partially_filled());
+}
+
+#[test]
+fn test_rounding_does_not_accumulate() {
+    // 每个代币 B 对应 10 / 3 个代币 A，按累计成交量计算后总量恰好等于存入数量
+    let mut escrow = escrow(10, 3);
+
+    let released = fill(&mut escrow, &[1, 1, 1]);
+    assert_eq!(released, vec![3, 3, 4]);
+    assert_eq!(released.iter().sum::<u64>(), 10);
+}
+
+#[test]
+fn test_release_handles_large_amounts() {
+    let mut escrow = escrow(u64::MAX, u64::MAX / 2);
+
+    assert_eq!(escrow.release_for(u64::MAX / 4).unwrap(), u64::MAX / 2 - 1);
+    assert_eq!(fill(&mut escrow, &[u64::MAX / 2]), vec![u64::MAX]);
+}
+
+#[test]
+fn test_invalid_take_amount() {
+    let invalid: ProgramError = EscrowError::InvalidTakeAmount.into();
+    let escrow = escrow(1, 1_000);
+
+    // 为 0 或超过剩余数量
+    assert_eq!(escrow.release_for(0), Err(invalid.clone()));
+    assert_eq!(escrow.release_for(1_001), Err(invalid.clone()));
+    // 数量太小，释放的代币 A 为 0
+    assert_eq!(escrow.release_for(999), Err(invalid));
+    assert_eq!(escrow.release_for(1_000), Ok(1));
+}
+
+#[test]
+fn test_expiry() {
+    let mut escrow = escrow(1_000, 500);
+
+    // 0 表示永不过期
+    assert!(!escrow.is_expired(i64::MAX));
+
+    // 到达过期时间即过期
+    escrow.set_expiry(1_700_000_000);
+    assert!(!escrow.is_expired(1_699_999_999));
+    assert!(escrow.is_expired(1_700_000_000));
+}
+
+#[test]
+fn test_taker_restriction() {
+    let mut escrow = escrow(1_000, 500);
+    let taker = Address::new_from_array([7; 32]);
+    let other = Address::new_from_array([8; 32]);
+
+    // 没有指定接受者时任何人都可以接受
+    assert!(escrow.can_take(&taker));
+    assert!(escrow.can_take(&other));
+
+    escrow.set_taker(taker);
+    assert!(escrow.can_take(&taker));
+    assert!(!escrow.can_take(&other));
+}
+
+#[test]
+fn test_load_seeds_supports_legacy_layout() {
+    // 旧版布局：seed + maker + mint_a + mint_b + receive + bump
+    let mut legacy = vec![0u8; Escrow::LEGACY_LEN];
+    legacy[0..8].copy_from_slice(&42u64.to_le_bytes());
+    legacy[Escrow::LEGACY_LEN - 1] = 254;
+    assert_eq!(Escrow::LEGACY_LEN, 113);
+    assert_eq!(Escrow::load_seeds(&legacy).unwrap(), (42, [254]));
+
+    // 当前布局
+    let mut current = vec![0u8; Escrow::LEN];
+    current[0..8].copy_from_slice(&7u64.to_le_bytes());
+    current[Escrow::LEN - 1] = 253;
+    assert_eq!(Escrow::load_seeds(&current).unwrap(), (7, [253]));
+
+    // 其他长度
+    assert_eq!(
+        Escrow::load_seeds(&[0u8; 100]).unwrap_err(),
+        ProgramError::InvalidAccountData
+    );
+}
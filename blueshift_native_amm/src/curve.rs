@@ -0,0 +1,476 @@
+// =============================================================================
+// 恒定乘积曲线 (x * y = k)
+// =============================================================================
+// 只依赖 core，所有中间结果使用 checked u128 计算
+// CurveError 到 ProgramError 的转换在 errors 模块中
+//
+// 舍入方向始终对池子有利：
+// - 存款：用户需要转入的数量向上取整
+// - 取款：用户可以取出的数量向下取整
+// - 交换：精确输入时，扣除手续费后的输入向下取整，输出向下取整
+//         精确输出时，需要的输入向上取整
+//
+// 由此保证：
+// - 交换后 k 不会减少
+// - 存款 / 取款后每份 LP 对应的储备量不会减少
+
+// 手续费基点的分母：10_000 bps = 100%
+pub const FEE_DENOMINATOR: u16 = 10_000;
+
+// 首次存款时永久锁定的 LP 数量
+// 池子的 LP 供应量因此永远不会回到 0，抬高每份 LP 的价格需要捐赠的数量随之放大，
+// 使份额通胀攻击无利可图
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CurveError {
+    // 计算溢出或结果超出 u64
+    Overflow,
+    // 池子储备或 LP 供应量为 0
+    ZeroLiquidity,
+    // 输入数量为 0，或计算结果为 0
+    ZeroAmount,
+    // 手续费 >= 100%
+    InvalidFee,
+    // 取出的 LP 超过总供应量，想要的输出不少于池子储备，或首次存款不足最低流动性
+    InsufficientLiquidity,
+    // StableSwap 放大系数超出范围，或调整不合法
+    InvalidAmp,
+    // StableSwap 牛顿迭代没有收敛
+    NotConverged,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SwapResult {
+    // 进入金库的数量（包含手续费）
+    pub amount_in: u64,
+    // 从金库转出的数量
+    pub amount_out: u64,
+    // 留在池子里的手续费
+    pub fee: u64,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LiquidityAmounts {
+    pub x: u64,
+    pub y: u64,
+}
+
+// 单边存入的结果
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ZapInResult {
+    // 用户实际需要转入的数量（不超过给定的 amount_in）
+    pub amount_in: u64,
+    // 其中在池子内部换成另一种代币的部分
+    pub swap_amount: u64,
+    // 铸造给用户的 LP 数量
+    pub lp: u64,
+    // 内部交换手续费中归协议的部分（输入代币），留在金库中但不计入储备
+    pub protocol_fee: u64,
+}
+
+// 单边取出的结果
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ZapOutResult {
+    // 用户最终收到的数量
+    pub amount_out: u64,
+    // 按比例取出后，在池子内部换掉的另一种代币数量
+    pub swap_amount: u64,
+    // 内部交换手续费中归协议的部分（换掉的代币），留在金库中但不计入储备
+    pub protocol_fee: u64,
+}
+
+#[inline(always)]
+fn to_u64(value: u128) -> Result<u64, CurveError> {
+    u64::try_from(value).map_err(|_| CurveError::Overflow)
+}
+
+#[inline(always)]
+pub(crate) fn mul_div_floor(a: u64, b: u64, c: u64) -> Result<u64, CurveError> {
+    if c == 0 {
+        return Err(CurveError::ZeroLiquidity);
+    }
+    let product = (a as u128).checked_mul(b as u128).ok_or(CurveError::Overflow)?;
+    to_u64(product / c as u128)
+}
+
+#[inline(always)]
+pub(crate) fn mul_div_ceil(a: u64, b: u64, c: u64) -> Result<u64, CurveError> {
+    if c == 0 {
+        return Err(CurveError::ZeroLiquidity);
+    }
+    let product = (a as u128).checked_mul(b as u128).ok_or(CurveError::Overflow)?;
+    to_u64(product.div_ceil(c as u128))
+}
+
+// 池子使用的交换曲线
+// 存款和取款始终按储备比例进行，与曲线无关，只有交换需要区分
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Curve {
+    // x * y = k
+    ConstantProduct,
+    // Curve 风格的 StableSwap，amp 为当前的放大系数，见 crate::stable
+    StableSwap { amp: u64 },
+}
+
+impl Curve {
+    pub fn swap_exact_in(
+        &self,
+        reserve_in: u64,
+        reserve_out: u64,
+        amount_in: u64,
+        fee: u16,
+    ) -> Result<SwapResult, CurveError> {
+        match *self {
+            Curve::ConstantProduct => swap_exact_in(reserve_in, reserve_out, amount_in, fee),
+            Curve::StableSwap { amp } => {
+                crate::stable::swap_exact_in(amp, reserve_in, reserve_out, amount_in, fee)
+            }
+        }
+    }
+
+    pub fn swap_exact_out(
+        &self,
+        reserve_in: u64,
+        reserve_out: u64,
+        amount_out: u64,
+        fee: u16,
+    ) -> Result<SwapResult, CurveError> {
+        match *self {
+            Curve::ConstantProduct => swap_exact_out(reserve_in, reserve_out, amount_out, fee),
+            Curve::StableSwap { amp } => {
+                crate::stable::swap_exact_out(amp, reserve_in, reserve_out, amount_out, fee)
+            }
+        }
+    }
+}
+
+// 按精确输入计算交换结果
+// fee 以基点表示，从输入中扣除后留在池子里
+pub fn swap_exact_in(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    fee: u16,
+) -> Result<SwapResult, CurveError> {
+    if fee >= FEE_DENOMINATOR {
+        return Err(CurveError::InvalidFee);
+    }
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(CurveError::ZeroLiquidity);
+    }
+    if amount_in == 0 {
+        return Err(CurveError::ZeroAmount);
+    }
+
+    // 扣除手续费后的有效输入，向下取整
+    let amount_in_after_fee = mul_div_floor(amount_in, (FEE_DENOMINATOR - fee) as u64, FEE_DENOMINATOR as u64)?;
+
+    // out = reserve_out * in_after_fee / (reserve_in + in_after_fee)，向下取整
+    let denominator = (reserve_in as u128)
+        .checked_add(amount_in_after_fee as u128)
+        .ok_or(CurveError::Overflow)?;
+    let numerator = (reserve_out as u128)
+        .checked_mul(amount_in_after_fee as u128)
+        .ok_or(CurveError::Overflow)?;
+    let amount_out = to_u64(numerator / denominator)?;
+
+    if amount_out == 0 {
+        return Err(CurveError::ZeroAmount);
+    }
+
+    // 交换后的金库余额必须仍能用 u64 表示
+    reserve_in.checked_add(amount_in).ok_or(CurveError::Overflow)?;
+
+    Ok(SwapResult {
+        amount_in,
+        amount_out,
+        fee: amount_in - amount_in_after_fee,
+    })
+}
+
+// 按精确输出计算交换结果
+// 返回的 amount_in 是金库需要收到的数量（包含手续费），向上取整
+pub fn swap_exact_out(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+    fee: u16,
+) -> Result<SwapResult, CurveError> {
+    if fee >= FEE_DENOMINATOR {
+        return Err(CurveError::InvalidFee);
+    }
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(CurveError::ZeroLiquidity);
+    }
+    if amount_out == 0 {
+        return Err(CurveError::ZeroAmount);
+    }
+    if amount_out >= reserve_out {
+        return Err(CurveError::InsufficientLiquidity);
+    }
+
+    // 扣除手续费后需要的有效输入：in_after_fee = reserve_in * out / (reserve_out - out)，向上取整
+    let amount_in_after_fee = mul_div_ceil(reserve_in, amount_out, reserve_out - amount_out)?;
+
+    // 加回手续费：in = in_after_fee * 10_000 / (10_000 - fee)，向上取整
+    let amount_in = mul_div_ceil(
+        amount_in_after_fee,
+        FEE_DENOMINATOR as u64,
+        (FEE_DENOMINATOR - fee) as u64,
+    )?;
+
+    // 交换后的金库余额必须仍能用 u64 表示
+    reserve_in.checked_add(amount_in).ok_or(CurveError::Overflow)?;
+
+    Ok(SwapResult {
+        amount_in,
+        amount_out,
+        fee: amount_in - amount_in_after_fee,
+    })
+}
+
+// 铸造 lp 份 LP 需要存入的 (x, y)，向上取整
+pub fn deposit_amounts(
+    reserve_x: u64,
+    reserve_y: u64,
+    supply: u64,
+    lp: u64,
+) -> Result<LiquidityAmounts, CurveError> {
+    if reserve_x == 0 || reserve_y == 0 || supply == 0 {
+        return Err(CurveError::ZeroLiquidity);
+    }
+    if lp == 0 {
+        return Err(CurveError::ZeroAmount);
+    }
+
+    let x = mul_div_ceil(reserve_x, lp, supply)?;
+    let y = mul_div_ceil(reserve_y, lp, supply)?;
+
+    // 存款后的金库余额和 LP 供应量必须仍能用 u64 表示
+    reserve_x.checked_add(x).ok_or(CurveError::Overflow)?;
+    reserve_y.checked_add(y).ok_or(CurveError::Overflow)?;
+    supply.checked_add(lp).ok_or(CurveError::Overflow)?;
+
+    Ok(LiquidityAmounts { x, y })
+}
+
+// 首次存款：存入 (x, y) 共铸造 sqrt(x * y) 份 LP，向下取整
+// 其中 MINIMUM_LIQUIDITY 份永久锁定，返回铸造给用户的部分
+// sqrt(x * y) 不超过 MINIMUM_LIQUIDITY 时拒绝存款
+pub fn initial_liquidity(x: u64, y: u64) -> Result<u64, CurveError> {
+    if x == 0 || y == 0 {
+        return Err(CurveError::ZeroAmount);
+    }
+
+    // x, y < 2^64，乘积不会超出 u128，平方根不会超出 u64
+    let liquidity = sqrt_floor(x as u128 * y as u128);
+    if liquidity <= MINIMUM_LIQUIDITY {
+        return Err(CurveError::InsufficientLiquidity);
+    }
+
+    Ok(liquidity - MINIMUM_LIQUIDITY)
+}
+
+// 整数平方根，向下取整（牛顿迭代）
+fn sqrt_floor(value: u128) -> u64 {
+    if value < 2 {
+        return value as u64;
+    }
+
+    // 初始值 2^ceil(bits / 2) 不小于平方根，之后单调递减到结果
+    let mut x = 1u128 << (128 - value.leading_zeros()).div_ceil(2);
+    loop {
+        let next = (x + value / x) / 2;
+        if next >= x {
+            return x as u64;
+        }
+        x = next;
+    }
+}
+
+// 销毁 lp 份 LP 可以取出的 (x, y)，向下取整
+// 取出全部 LP 时直接返回全部储备，不留"尘埃"
+pub fn withdraw_amounts(
+    reserve_x: u64,
+    reserve_y: u64,
+    supply: u64,
+    lp: u64,
+) -> Result<LiquidityAmounts, CurveError> {
+    if supply == 0 {
+        return Err(CurveError::ZeroLiquidity);
+    }
+    if lp == 0 {
+        return Err(CurveError::ZeroAmount);
+    }
+    if lp > supply {
+        return Err(CurveError::InsufficientLiquidity);
+    }
+    if lp == supply {
+        return Ok(LiquidityAmounts { x: reserve_x, y: reserve_y });
+    }
+
+    Ok(LiquidityAmounts {
+        x: mul_div_floor(reserve_x, lp, supply)?,
+        y: mul_div_floor(reserve_y, lp, supply)?,
+    })
+}
+
+// 交换手续费中归协议所有的部分：fee_amount * protocol_fee / 10_000，向下取整
+// 剩余部分留在池子里归 LP 所有
+pub fn protocol_fee(fee_amount: u64, protocol_fee: u16) -> Result<u64, CurveError> {
+    if protocol_fee > FEE_DENOMINATOR {
+        return Err(CurveError::InvalidFee);
+    }
+    mul_div_floor(fee_amount, protocol_fee as u64, FEE_DENOMINATOR as u64)
+}
+
+// 闪电贷手续费：amount * fee / 10_000，向上取整，与交换使用相同的费率
+// 向上取整保证借出任意非 0 数量都不能免费，协议费份额与交换相同，用 protocol_fee 计算
+pub fn flash_loan_fee(amount: u64, fee: u16) -> Result<u64, CurveError> {
+    if fee >= FEE_DENOMINATOR {
+        return Err(CurveError::InvalidFee);
+    }
+    mul_div_ceil(amount, fee as u64, FEE_DENOMINATOR as u64)
+}
+
+// 单边存入：用 amount_in 个输入代币铸造尽可能多的 LP
+//
+// 先在池子内部把 swap_amount 换成另一种代币（收取交换手续费），
+// 再把剩余部分和换得的代币按交换后的比例存入
+// swap_amount 取满足 "剩余输入足够与换得的代币配对" 的最大值，用二分查找求得，
+// 与 swap_exact_in 使用完全相同的取整，避免闭式解的平方根在 u128 中溢出
+pub fn zap_in(
+    reserve_in: u64,
+    reserve_out: u64,
+    supply: u64,
+    amount_in: u64,
+    fee: u16,
+) -> Result<ZapInResult, CurveError> {
+    zap_in_with(Curve::ConstantProduct, reserve_in, reserve_out, supply, amount_in, fee, 0)
+}
+
+// 与 zap_in 相同，内部交换使用指定的曲线，并与 Swap 一样按 protocol_fee_bps 收取协议费
+// 两种曲线的输出都随输入单调增加，二分查找同样适用
+pub fn zap_in_with(
+    curve: Curve,
+    reserve_in: u64,
+    reserve_out: u64,
+    supply: u64,
+    amount_in: u64,
+    fee: u16,
+    protocol_fee_bps: u16,
+) -> Result<ZapInResult, CurveError> {
+    if reserve_in == 0 || reserve_out == 0 || supply == 0 {
+        return Err(CurveError::ZeroLiquidity);
+    }
+    if amount_in == 0 {
+        return Err(CurveError::ZeroAmount);
+    }
+
+    // 交换 s 后：(amount_in - s) / (reserve_in + s) >= out / (reserve_out - out)
+    // s 越大左边越小、右边越大，因此可以二分
+    let swap_out = |s: u64| -> Result<u64, CurveError> {
+        match curve.swap_exact_in(reserve_in, reserve_out, s, fee) {
+            Ok(result) => Ok(result.amount_out),
+            Err(CurveError::ZeroAmount) => Ok(0),
+            Err(error) => Err(error),
+        }
+    };
+    let pairable = |s: u64, out: u64| -> bool {
+        let remaining = (amount_in - s) as u128 * (reserve_out - out) as u128;
+        let needed = out as u128 * (reserve_in as u128 + s as u128);
+        remaining >= needed
+    };
+
+    let (mut low, mut high) = (0u64, amount_in);
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        if pairable(mid, swap_out(mid)?) {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+
+    let swap_amount = low;
+    let swap = curve.swap_exact_in(reserve_in, reserve_out, swap_amount, fee)?;
+    let out = swap.amount_out;
+    let protocol = protocol_fee(swap.fee, protocol_fee_bps)?;
+
+    // 交换后的储备，协议费不属于 LP
+    let new_reserve_in = reserve_in
+        .checked_add(swap_amount)
+        .and_then(|reserve| reserve.checked_sub(protocol))
+        .ok_or(CurveError::Overflow)?;
+    let new_reserve_out = reserve_out - out;
+
+    // 两侧能铸造的 LP 取较小值，向下取整
+    let lp = mul_div_floor(amount_in - swap_amount, supply, new_reserve_in)?
+        .min(mul_div_floor(out, supply, new_reserve_out)?);
+
+    // 按交换后的储备计算实际需要存入的数量（向上取整，不会超过可用数量）
+    // 换得的代币中未被使用的零头留在池子里
+    let deposit = deposit_amounts(new_reserve_in, new_reserve_out, supply, lp)?;
+
+    Ok(ZapInResult {
+        amount_in: swap_amount.checked_add(deposit.x).ok_or(CurveError::Overflow)?,
+        swap_amount,
+        lp,
+        protocol_fee: protocol,
+    })
+}
+
+// 单边取出：销毁 lp 份 LP，只收到 reserve_keep 对应的代币
+//
+// 先按比例取出两种代币，再在池子内部把 reserve_swap 一侧取出的部分换成 reserve_keep 一侧
+// 取出全部 LP 后池子为空，无法再交换，因此不允许
+pub fn zap_out(
+    reserve_keep: u64,
+    reserve_swap: u64,
+    supply: u64,
+    lp: u64,
+    fee: u16,
+) -> Result<ZapOutResult, CurveError> {
+    zap_out_with(Curve::ConstantProduct, reserve_keep, reserve_swap, supply, lp, fee, 0)
+}
+
+// 与 zap_out 相同，内部交换使用指定的曲线，并与 Swap 一样按 protocol_fee_bps 收取协议费
+pub fn zap_out_with(
+    curve: Curve,
+    reserve_keep: u64,
+    reserve_swap: u64,
+    supply: u64,
+    lp: u64,
+    fee: u16,
+    protocol_fee_bps: u16,
+) -> Result<ZapOutResult, CurveError> {
+    if lp >= supply {
+        return Err(CurveError::InsufficientLiquidity);
+    }
+
+    let withdraw = withdraw_amounts(reserve_keep, reserve_swap, supply, lp)?;
+
+    // 取出的零头太小、换不出任何代币时，直接留在池子里
+    let (swapped, protocol) = match curve.swap_exact_in(
+        reserve_swap - withdraw.y,
+        reserve_keep - withdraw.x,
+        withdraw.y,
+        fee,
+    ) {
+        Ok(result) => (result.amount_out, protocol_fee(result.fee, protocol_fee_bps)?),
+        Err(CurveError::ZeroAmount) => (0, 0),
+        Err(error) => return Err(error),
+    };
+
+    let amount_out = withdraw.x.checked_add(swapped).ok_or(CurveError::Overflow)?;
+    if amount_out == 0 {
+        return Err(CurveError::ZeroAmount);
+    }
+
+    Ok(ZapOutResult {
+        amount_out,
+        swap_amount: withdraw.y,
+        protocol_fee: protocol,
+    })
+}
@@ -1,104 +1,187 @@
 // =============================================================================
 // 错误模块 - 自定义错误类型定义
 // =============================================================================
-// 本模块定义了托管程序可能返回的所有自定义错误
+// 本模块定义了 AMM 程序可能返回的所有自定义错误
 // 这些错误会作为 ProgramError::Custom() 返回给客户端
 
 use pinocchio::error::ProgramError;
 use core::fmt;
+use crate::curve::CurveError;
 
 // =============================================================================
-// EscrowError 自定义错误枚举
+// AmmError 自定义错误枚举
 // =============================================================================
 // 定义程序中所有可能的错误情况
 //
 // 每个错误都有一个明确的数值（从 0 开始递增），这个数值会被编码到
 // ProgramError::Custom(error as u32) 中返回给客户端
 //
-// 客户端可以通过这个错误码来识别具体的错误类型，并进行相应的处理
+// 客户端可以通过这个错误码来识别具体的错误类型，例如向用户说明交换失败是因为
+// 交易过期、滑点超出还是池子被禁用
 //
-// derive 属性说明：
-// - Clone: 允许错误被复制
-// - Debug: 允许使用 {:?} 格式化输出（用于调试）
-// - Eq: 允许错误之间进行相等比较
-// - PartialEq: 允许错误进行部分相等比较
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub enum EscrowError {
-    /// 账户余额低于租金豁免阈值
-    /// Solana 要求账户必须持有足够的 lamports 以免被删除
-    /// 如果余额不足，账户可能被垃圾回收
-    NotRentExempt = 0,
-
+// 错误码是对外的接口，新增错误只能追加在末尾，不能修改已有的数值
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AmmError {
     /// 账户未签名
-    /// 某些操作需要特定的账户签名（如创建者、接受者）
-    /// 如果应该签名的账户没有签名，返回此错误
-    NotSigner = 1,
+    NotSigner = 0,
 
     /// 非法的账户所有者
-    /// Solana 账户由特定程序拥有（通过 owner 字段指定）
-    /// 如果账户的所有者不是预期的程序，返回此错误
-    /// 例如：Token Account 的 owner 应该是 Token Program
-    InvalidOwner = 2,
+    /// 例如：Token Account 的 owner 不是 Token Program
+    InvalidOwner = 1,
 
     /// 非法的账户数据
     /// 账户数据的长度或格式不符合预期
-    /// 例如：期望的账户数据长度与实际不匹配
-    InvalidAccountData = 3,
+    InvalidAccountData = 2,
 
     /// 非法的地址
-    /// 提供的地址不符合预期要求
-    /// 例如：PDA 派生失败、地址不匹配等
-    InvalidAddress = 4,
+    /// 例如：PDA 派生失败、ATA 地址不匹配等
+    InvalidAddress = 3,
+
+    /// 交易已过期：当前时间晚于指令中的 expiration
+    Expired = 4,
+
+    /// 滑点超出：实际数量比用户指定的最小输出 / 最大输入更差
+    SlippageExceeded = 5,
+
+    /// 池子尚未初始化
+    PoolNotInitialized = 6,
+
+    /// 池子已被禁用，暂停所有操作
+    PoolDisabled = 7,
+
+    /// 池子处于仅提现状态，不允许存款和交换
+    PoolWithdrawOnly = 8,
+
+    /// 曲线计算失败：溢出或牛顿迭代没有收敛
+    CurveError = 9,
+
+    /// 池子储备或 LP 供应量为 0
+    ZeroLiquidity = 10,
+
+    /// 输入数量为 0，或计算出的数量为 0
+    ZeroAmount = 11,
+
+    /// 池子流动性不足：想要的输出不少于储备，取出的 LP 超过总供应量，或首次存款不足最低流动性
+    InsufficientLiquidity = 12,
+
+    /// 非法的手续费：交换手续费 >= 100% 或协议费份额 > 100%
+    InvalidFee = 13,
+
+    /// StableSwap 放大系数超出范围，或调整不合法
+    InvalidAmp = 14,
+
+    /// 未知的曲线类型，或对恒定乘积池子调整放大系数
+    InvalidCurve = 15,
+
+    /// 金库不是 config PDA 在对应 mint 下的 ATA
+    InvalidVault = 16,
+
+    /// LP Mint 不是池子的 LP Mint PDA
+    InvalidLpMint = 17,
+
+    /// Mint 与池子记录的不一致，或交易对的两个 Mint 相同
+    InvalidMint = 18,
+
+    /// 代币账户的 mint 与池子记录的不一致
+    InvalidTokenAccount = 19,
+
+    /// 多跳交换的路径没有首尾相接
+    InvalidRoute = 20,
+
+    /// Config 账户的布局版本不是当前版本，需要先迁移
+    InvalidConfigVersion = 21,
+
+    /// 池子还没有设置协议费接收者
+    NoFeeRecipient = 22,
+
+    /// LP 代币元数据的 URI 过长或不是合法的 UTF-8
+    InvalidMetadataUri = 23,
+
+    /// 闪电贷结束时金库余额少于借出前的余额加手续费
+    FlashLoanNotRepaid = 24,
+
+    /// 交易对的两个 mint 没有按字节序排列，不能注册
+    NonCanonicalMints = 25,
+
+    /// 池子已经注册过
+    PoolAlreadyRegistered = 26,
+
+    /// 注册表要求每个手续费档位只有一个池子，而这个交易对在该档位已有池子
+    FeeTierTaken = 27,
 }
 
 // =============================================================================
-// From<EscrowError> for ProgramError Trait 实现
+// From<AmmError> for ProgramError Trait 实现
 // =============================================================================
-// 这个实现允许将 EscrowError 自动转换为 ProgramError
-//
-// Rust 的 From trait 提供了一种类型转换机制
-// 实现了 From<EscrowError> for ProgramError 后，可以使用：
-// - Err(EscrowError::NotSigner)? 自动转换为 Err(ProgramError::Custom(1))
-// - ProgramError::from(EscrowError::NotSigner) 显式转换
-//
-// 在程序中使用 '?' 操作符时，如果返回的是 EscrowError，
-// 会自动调用这个 from 方法转换为 ProgramError
-//
-// error as u32:
-// 将枚举转换为它的数值表示（discriminant）
-// 例如：EscrowError::NotRentExempt => 0
-//       EscrowError::NotSigner => 1
-impl From<EscrowError> for ProgramError {
-    fn from(error: EscrowError) -> Self {
+// 实现后可以使用：
+// - Err(AmmError::Expired.into()) 转换为 Err(ProgramError::Custom(4))
+// - ProgramError::from(AmmError::Expired) 显式转换
+impl From<AmmError> for ProgramError {
+    fn from(error: AmmError) -> Self {
         ProgramError::Custom(error as u32)
     }
 }
 
+// =============================================================================
+// 曲线错误转换
+// =============================================================================
+// curve / stable 模块只依赖 core，返回的 CurveError 在这里映射为 AmmError，
+// 指令中可以直接对曲线计算的结果使用 '?'
+impl From<CurveError> for AmmError {
+    fn from(error: CurveError) -> Self {
+        match error {
+            CurveError::Overflow | CurveError::NotConverged => AmmError::CurveError,
+            CurveError::ZeroLiquidity => AmmError::ZeroLiquidity,
+            CurveError::ZeroAmount => AmmError::ZeroAmount,
+            CurveError::InvalidFee => AmmError::InvalidFee,
+            CurveError::InsufficientLiquidity => AmmError::InsufficientLiquidity,
+            CurveError::InvalidAmp => AmmError::InvalidAmp,
+        }
+    }
+}
+
+impl From<CurveError> for ProgramError {
+    fn from(error: CurveError) -> Self {
+        AmmError::from(error).into()
+    }
+}
+
 // =============================================================================
 // fmt::Display Trait 实现
 // =============================================================================
-// 这个实现允许将 EscrowError 格式化为可读的字符串
-//
-// 用途：
-// - 使用 println!("{}", error) 打印错误信息
-// - 在日志中记录人类可读的错误描述
-// - 客户端显示友好的错误消息
-//
-// 返回值：
-// - fmt::Result: 实际上是 Result<(), fmt::Error>
-//   格式化成功返回 Ok(())，失败返回 Err(fmt::Error)
-//
-// 注意：
 // 这些字符串消息主要用于调试和日志
 // 客户端通常通过错误码（数值）来识别错误类型
-impl fmt::Display for EscrowError {
+impl fmt::Display for AmmError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            EscrowError::NotRentExempt => write!(f, "Lamport balance below rent-exempt threshold"),
-            EscrowError::NotSigner => write!(f, "没有签名"),
-            EscrowError::InvalidOwner => write!(f, "非法的所有者"),
-            EscrowError::InvalidAccountData => write!(f, "非法的账户数据"),
-            EscrowError::InvalidAddress => write!(f, "非法的地址"),
+            AmmError::NotSigner => write!(f, "没有签名"),
+            AmmError::InvalidOwner => write!(f, "非法的所有者"),
+            AmmError::InvalidAccountData => write!(f, "非法的账户数据"),
+            AmmError::InvalidAddress => write!(f, "非法的地址"),
+            AmmError::Expired => write!(f, "交易已过期"),
+            AmmError::SlippageExceeded => write!(f, "滑点超出限制"),
+            AmmError::PoolNotInitialized => write!(f, "池子尚未初始化"),
+            AmmError::PoolDisabled => write!(f, "池子已被禁用"),
+            AmmError::PoolWithdrawOnly => write!(f, "池子处于仅提现状态"),
+            AmmError::CurveError => write!(f, "曲线计算失败"),
+            AmmError::ZeroLiquidity => write!(f, "池子没有流动性"),
+            AmmError::ZeroAmount => write!(f, "数量为 0"),
+            AmmError::InsufficientLiquidity => write!(f, "池子流动性不足"),
+            AmmError::InvalidFee => write!(f, "非法的手续费"),
+            AmmError::InvalidAmp => write!(f, "非法的放大系数"),
+            AmmError::InvalidCurve => write!(f, "非法的曲线类型"),
+            AmmError::InvalidVault => write!(f, "非法的金库账户"),
+            AmmError::InvalidLpMint => write!(f, "非法的 LP Mint"),
+            AmmError::InvalidMint => write!(f, "非法的 Mint"),
+            AmmError::InvalidTokenAccount => write!(f, "非法的代币账户"),
+            AmmError::InvalidRoute => write!(f, "多跳路径不连续"),
+            AmmError::InvalidConfigVersion => write!(f, "Config 版本不匹配，需要先迁移"),
+            AmmError::NoFeeRecipient => write!(f, "未设置协议费接收者"),
+            AmmError::InvalidMetadataUri => write!(f, "元数据 URI 过长或不是合法的 UTF-8"),
+            AmmError::FlashLoanNotRepaid => write!(f, "闪电贷没有归还本金和手续费"),
+            AmmError::NonCanonicalMints => write!(f, "交易对的 mint 没有按规范顺序排列"),
+            AmmError::PoolAlreadyRegistered => write!(f, "池子已经注册"),
+            AmmError::FeeTierTaken => write!(f, "该手续费档位已有池子"),
         }
     }
-}
\ No newline at end of file
+}
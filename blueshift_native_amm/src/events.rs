@@ -0,0 +1,450 @@
+// =============================================================================
+// 事件
+// =============================================================================
+// 交换、存取款、初始化、注册和管理类指令执行成功后通过 sol_log_data 输出一条事件，
+// 索引器直接从交易日志中解析，不需要对比代币余额
+//
+// 日志格式："Program data: <base64 [kind, version]> <base64 事件>"
+// - kind：事件类型，见 kind 模块
+// - version：事件布局版本，新版本只在事件末尾追加字段，旧的解码器读取前缀即可
+//
+// 事件都是紧凑排列的结构体（对齐为 1，没有填充字节），地址使用 [u8; 32] 保存
+//
+// 与 layout 相同，只依赖 core，链下客户端通过同一个文件解析事件
+
+use core::mem::size_of;
+
+// 当前的事件布局版本
+pub const EVENT_VERSION: u8 = 1;
+
+// 事件类型：事件的第一个字段
+// 与指令判别器相同，已有的数值不能修改
+pub mod kind {
+    pub const INITIALIZE: u8 = 0;
+    pub const SWAP: u8 = 1;
+    pub const DEPOSIT: u8 = 2;
+    pub const WITHDRAW: u8 = 3;
+    pub const FLASH_LOAN: u8 = 4;
+    pub const STATE_CHANGED: u8 = 5;
+    pub const FEE_UPDATED: u8 = 6;
+    pub const AUTHORITY_CHANGED: u8 = 7;
+    pub const PROTOCOL_FEES_COLLECTED: u8 = 8;
+    pub const AMP_RAMP_UPDATED: u8 = 9;
+    pub const POOL_REGISTERED: u8 = 10;
+}
+
+pub trait Event: Copy {
+    const KIND: u8;
+
+    // 紧凑排列的结构体没有填充字节，可以直接按字节读取
+    #[inline(always)]
+    fn as_bytes(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(self as *const Self as *const u8, size_of::<Self>()) }
+    }
+
+    // 事件只包含整数和字节数组，任意字节都是合法的值
+    // 比当前布局更长的数据来自更新的版本，多出的字段被忽略
+    #[inline(always)]
+    fn from_bytes(data: &[u8]) -> Option<Self> {
+        if data.len() < size_of::<Self>() {
+            return None;
+        }
+        Some(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// =============================================================================
+// 池子事件
+// =============================================================================
+
+// 创建池子
+#[repr(C, packed)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct InitializeEvent {
+    pub pool: [u8; 32],
+    pub initializer: [u8; 32],
+    // 全 0 表示不可变池子
+    pub authority: [u8; 32],
+    pub mint_x: [u8; 32],
+    pub mint_y: [u8; 32],
+    pub mint_lp: [u8; 32],
+    pub seed: u64,
+    pub fee: u16,
+    pub lp_decimals: u8,
+    pub curve_type: u8,
+    pub amp: u64,
+}
+
+// 交换，Swap / SwapExactOut 和 RouteSwap 的每一跳各输出一条
+#[repr(C, packed)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SwapEvent {
+    pub pool: [u8; 32],
+    pub user: [u8; 32],
+    // 1 表示 X 换 Y，0 表示 Y 换 X
+    pub is_x: u8,
+    // 金库实际收到的数量（包含手续费，不含 Token-2022 转账手续费）
+    pub amount_in: u64,
+    // 金库转出的数量
+    pub amount_out: u64,
+    // 手续费总额（以输入代币计），其中 protocol_fee 归协议
+    pub fee: u64,
+    pub protocol_fee: u64,
+    // 交换之后的储备（不含尚未领取的协议费）
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+}
+
+// 存入流动性，Deposit 和 DepositSingle 共用
+#[repr(C, packed)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct DepositEvent {
+    pub pool: [u8; 32],
+    pub user: [u8; 32],
+    // 金库实际收到的数量，单边存入时另一侧为 0
+    pub amount_x: u64,
+    pub amount_y: u64,
+    // 其中在池子内部换成另一种代币的部分，按比例存入时为 0
+    pub swap_amount: u64,
+    // 铸造给用户的 LP，首次存款永久锁定的 MINIMUM_LIQUIDITY 不计入
+    pub lp_amount: u64,
+    // 存入之后的储备和 LP 供应量
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub lp_supply: u64,
+}
+
+// 取出流动性，Withdraw 和 WithdrawSingle 共用
+#[repr(C, packed)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct WithdrawEvent {
+    pub pool: [u8; 32],
+    pub user: [u8; 32],
+    // 销毁的 LP
+    pub lp_amount: u64,
+    // 金库转出的数量，单边取出时另一侧为 0
+    pub amount_x: u64,
+    pub amount_y: u64,
+    // 单边取出时在池子内部换掉的另一种代币数量，按比例取出时为 0
+    pub swap_amount: u64,
+    // 取出之后的储备和 LP 供应量
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub lp_supply: u64,
+}
+
+// 闪电贷
+#[repr(C, packed)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct FlashLoanEvent {
+    pub pool: [u8; 32],
+    pub borrower: [u8; 32],
+    pub amount_x: u64,
+    pub amount_y: u64,
+    // 应付的手续费，多归还的部分不计入
+    pub fee_x: u64,
+    pub fee_y: u64,
+    // 归还之后的储备
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+}
+
+// =============================================================================
+// 管理事件
+// =============================================================================
+// 记录的都是指令执行之后的值
+
+// Lock / Unlock / SetWithdrawOnly
+#[repr(C, packed)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct StateChangedEvent {
+    pub pool: [u8; 32],
+    pub authority: [u8; 32],
+    // AmmState 的 u8 值
+    pub state: u8,
+}
+
+// UpdateFee / SetProtocolFee
+#[repr(C, packed)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct FeeUpdatedEvent {
+    pub pool: [u8; 32],
+    pub authority: [u8; 32],
+    pub fee: u16,
+    pub protocol_fee: u16,
+    pub fee_recipient: [u8; 32],
+}
+
+// ProposeAuthority / AcceptAuthority
+#[repr(C, packed)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct AuthorityChangedEvent {
+    pub pool: [u8; 32],
+    pub authority: [u8; 32],
+    // 全 0 表示没有待接受的提议
+    pub pending_authority: [u8; 32],
+}
+
+// CollectProtocolFees
+#[repr(C, packed)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ProtocolFeesCollectedEvent {
+    pub pool: [u8; 32],
+    pub authority: [u8; 32],
+    pub fee_recipient: [u8; 32],
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+// RampAmp / StopRampAmp
+#[repr(C, packed)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct AmpRampUpdatedEvent {
+    pub pool: [u8; 32],
+    pub authority: [u8; 32],
+    pub amp_initial: u64,
+    pub amp_target: u64,
+    pub ramp_start: i64,
+    pub ramp_end: i64,
+}
+
+// 注册表：Initialize 和 RegisterPool
+#[repr(C, packed)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PoolRegisteredEvent {
+    pub pool: [u8; 32],
+    pub pair: [u8; 32],
+    pub mint_x: [u8; 32],
+    pub mint_y: [u8; 32],
+    pub seed: u64,
+    pub fee_tier: u16,
+    pub curve_type: u8,
+    // 池子在交易对记录中的位置
+    pub index: u32,
+}
+
+impl Event for InitializeEvent {
+    const KIND: u8 = kind::INITIALIZE;
+}
+
+impl Event for SwapEvent {
+    const KIND: u8 = kind::SWAP;
+}
+
+impl Event for DepositEvent {
+    const KIND: u8 = kind::DEPOSIT;
+}
+
+impl Event for WithdrawEvent {
+    const KIND: u8 = kind::WITHDRAW;
+}
+
+impl Event for FlashLoanEvent {
+    const KIND: u8 = kind::FLASH_LOAN;
+}
+
+impl Event for StateChangedEvent {
+    const KIND: u8 = kind::STATE_CHANGED;
+}
+
+impl Event for FeeUpdatedEvent {
+    const KIND: u8 = kind::FEE_UPDATED;
+}
+
+impl Event for AuthorityChangedEvent {
+    const KIND: u8 = kind::AUTHORITY_CHANGED;
+}
+
+impl Event for ProtocolFeesCollectedEvent {
+    const KIND: u8 = kind::PROTOCOL_FEES_COLLECTED;
+}
+
+impl Event for AmpRampUpdatedEvent {
+    const KIND: u8 = kind::AMP_RAMP_UPDATED;
+}
+
+impl Event for PoolRegisteredEvent {
+    const KIND: u8 = kind::POOL_REGISTERED;
+}
+
+// =============================================================================
+// 解码
+// =============================================================================
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AmmEvent {
+    Initialize(InitializeEvent),
+    Swap(SwapEvent),
+    Deposit(DepositEvent),
+    Withdraw(WithdrawEvent),
+    FlashLoan(FlashLoanEvent),
+    StateChanged(StateChangedEvent),
+    FeeUpdated(FeeUpdatedEvent),
+    AuthorityChanged(AuthorityChangedEvent),
+    ProtocolFeesCollected(ProtocolFeesCollectedEvent),
+    AmpRampUpdated(AmpRampUpdatedEvent),
+    PoolRegistered(PoolRegisteredEvent),
+}
+
+// 解码后的一个 "Program data:" 日志最多这么多字节，所有事件都比它短
+const MAX_EVENT_DATA_LEN: usize = 256;
+
+impl AmmEvent {
+    // 解码 [kind, version, 事件...]，未知的类型、版本 0 或数据过短时返回 None
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let [kind, version, event @ ..] = data else {
+            return None;
+        };
+        if *version == 0 {
+            return None;
+        }
+
+        Some(match *kind {
+            kind::INITIALIZE => Self::Initialize(Event::from_bytes(event)?),
+            kind::SWAP => Self::Swap(Event::from_bytes(event)?),
+            kind::DEPOSIT => Self::Deposit(Event::from_bytes(event)?),
+            kind::WITHDRAW => Self::Withdraw(Event::from_bytes(event)?),
+            kind::FLASH_LOAN => Self::FlashLoan(Event::from_bytes(event)?),
+            kind::STATE_CHANGED => Self::StateChanged(Event::from_bytes(event)?),
+            kind::FEE_UPDATED => Self::FeeUpdated(Event::from_bytes(event)?),
+            kind::AUTHORITY_CHANGED => Self::AuthorityChanged(Event::from_bytes(event)?),
+            kind::PROTOCOL_FEES_COLLECTED => Self::ProtocolFeesCollected(Event::from_bytes(event)?),
+            kind::AMP_RAMP_UPDATED => Self::AmpRampUpdated(Event::from_bytes(event)?),
+            kind::POOL_REGISTERED => Self::PoolRegistered(Event::from_bytes(event)?),
+            _ => return None,
+        })
+    }
+
+    // 解码一行 "Program data: ..." 日志，不检查输出它的程序
+    // 同一笔交易中其它程序也可能输出 "Program data:"，解析整笔交易的日志应使用 parse_logs
+    pub fn from_log(line: &str) -> Option<Self> {
+        let fields = line.strip_prefix("Program data: ")?;
+
+        // 每个字段分别编码，解码后依次拼接
+        let mut data = [0u8; MAX_EVENT_DATA_LEN];
+        let mut len = 0;
+        for field in fields.split(' ') {
+            len = base64_decode(field.as_bytes(), &mut data, len)?;
+        }
+
+        Self::decode(&data[..len])
+    }
+}
+
+// 按交易日志的顺序解析本程序输出的事件
+// program_id 是本程序地址的 base58 字符串，只解析由它直接输出的 "Program data:"，
+// CPI 到其它程序期间输出的日志被忽略
+// 失败的交易不会改变任何状态，调用者只应解析成功交易的日志
+pub fn parse_logs<'a, I>(program_id: &'a str, logs: I) -> ParseLogs<'a, I::IntoIter>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    ParseLogs {
+        program_id,
+        logs: logs.into_iter(),
+        depth: 0,
+        ours: 0,
+    }
+}
+
+pub struct ParseLogs<'a, I> {
+    program_id: &'a str,
+    logs: I,
+    // 当前的调用深度，对应 "invoke [n]" 中的 n，0 表示不在任何程序中
+    depth: u32,
+    // 第 n 位表示深度 n 上执行的是不是本程序
+    ours: u64,
+}
+
+impl<I> Iterator for ParseLogs<'_, I>
+where
+    I: Iterator,
+    I::Item: AsRef<str>,
+{
+    type Item = AmmEvent;
+
+    fn next(&mut self) -> Option<AmmEvent> {
+        for line in self.logs.by_ref() {
+            let line = line.as_ref();
+
+            if line.starts_with("Program data: ") {
+                if self.depth > 0 && self.ours & (1 << self.depth) != 0 {
+                    if let Some(event) = AmmEvent::from_log(line) {
+                        return Some(event);
+                    }
+                }
+                continue;
+            }
+
+            // "Program <id> invoke [n]" / "Program <id> success" / "Program <id> failed: ..."
+            let Some((program, status)) = line
+                .strip_prefix("Program ")
+                .and_then(|rest| rest.split_once(' '))
+            else {
+                continue;
+            };
+
+            if let Some(depth) = status
+                .strip_prefix("invoke [")
+                .and_then(|rest| rest.strip_suffix(']'))
+                .and_then(|depth| depth.parse::<u32>().ok())
+                .filter(|depth| *depth < u64::BITS)
+            {
+                self.depth = depth;
+                if program == self.program_id {
+                    self.ours |= 1 << depth;
+                } else {
+                    self.ours &= !(1 << depth);
+                }
+            } else if status == "success" || status.starts_with("failed") {
+                self.depth = self.depth.saturating_sub(1);
+            }
+        }
+
+        None
+    }
+}
+
+// 标准 base64 字符对应的 6 位值
+#[inline(always)]
+fn base64_value(c: u8) -> Option<u32> {
+    match c {
+        b'A'..=b'Z' => Some((c - b'A') as u32),
+        b'a'..=b'z' => Some((c - b'a' + 26) as u32),
+        b'0'..=b'9' => Some((c - b'0' + 52) as u32),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+// 把带填充的标准 base64 解码到 out[len..]，返回解码后的总长度
+// 格式错误或超出 out 的长度时返回 None
+fn base64_decode(input: &[u8], out: &mut [u8], mut len: usize) -> Option<usize> {
+    if !input.len().is_multiple_of(4) {
+        return None;
+    }
+
+    let chunks = input.len() / 4;
+    for (i, chunk) in input.chunks_exact(4).enumerate() {
+        // 只有最后一组可以有 1 到 2 个 '=' 填充
+        let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+        if padding > 2 || (padding > 0 && i + 1 != chunks) {
+            return None;
+        }
+
+        let mut bits = 0u32;
+        for c in &chunk[..4 - padding] {
+            bits = bits << 6 | base64_value(*c)?;
+        }
+        bits <<= 6 * padding as u32;
+
+        let bytes = 3 - padding;
+        out.get_mut(len..len + bytes)?
+            .copy_from_slice(&bits.to_be_bytes()[1..1 + bytes]);
+        len += bytes;
+    }
+
+    Some(len)
+}
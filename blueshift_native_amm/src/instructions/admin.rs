@@ -0,0 +1,122 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::account::RefMut;
+use pinocchio::error::ProgramError;
+use crate::{AmmState, Config};
+use crate::layout::discriminator;
+use crate::events::StateChangedEvent;
+use super::helpers::{emit, AccountCheck, SignerAccount};
+
+// 管理类指令共用的账户：池子的 authority（签名者）和 Config
+pub struct AdminAccounts<'info> {
+    pub authority: &'info AccountView,
+    pub config: &'info AccountView,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for AdminAccounts<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        Ok(Self {
+            authority,
+            config,
+        })
+    }
+}
+
+impl<'info> AdminAccounts<'info> {
+    // 加载可写的 Config，并校验签名者就是池子的 authority
+    // authority 为全 0 的池子是不可变的，拒绝任何修改
+    pub fn load_config(&self) -> Result<RefMut<'info, Config>, ProgramError> {
+        let config = Config::load_mut(self.config)?;
+
+        match config.has_authority() {
+            Some(authority) if authority.eq(self.authority.address()) => Ok(config),
+            Some(_) => Err(ProgramError::IncorrectAuthority),
+            None => Err(ProgramError::Immutable),
+        }
+    }
+
+    #[inline(always)]
+    fn set_state(&self, state: AmmState) -> ProgramResult {
+        let state = state as u8;
+        self.load_config()?.set_state(state)?;
+
+        emit(&StateChangedEvent {
+            pool: self.config.address().to_bytes(),
+            authority: self.authority.address().to_bytes(),
+            state,
+        });
+        Ok(())
+    }
+}
+
+// 暂停池子：禁止存款、取款和交易
+pub struct Lock<'info> {
+    pub accounts: AdminAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for Lock<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = AdminAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'info> Lock<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::LOCK;
+
+    pub fn process(&mut self) -> ProgramResult {
+        self.accounts.set_state(AmmState::Disabled)
+    }
+}
+
+// 恢复池子到正常运行状态
+pub struct Unlock<'info> {
+    pub accounts: AdminAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for Unlock<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = AdminAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'info> Unlock<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::UNLOCK;
+
+    pub fn process(&mut self) -> ProgramResult {
+        self.accounts.set_state(AmmState::Initialized)
+    }
+}
+
+// 仅允许取款：LP 可以撤出流动性，但不能再存款或交易
+pub struct SetWithdrawOnly<'info> {
+    pub accounts: AdminAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for SetWithdrawOnly<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = AdminAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'info> SetWithdrawOnly<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::SET_WITHDRAW_ONLY;
+
+    pub fn process(&mut self) -> ProgramResult {
+        self.accounts.set_state(AmmState::WithdrawOnly)
+    }
+}
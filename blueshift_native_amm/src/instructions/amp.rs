@@ -0,0 +1,127 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use crate::CurveType;
+use crate::stable;
+use super::admin::AdminAccounts;
+use crate::errors::AmmError;
+use crate::layout::discriminator;
+use crate::events::AmpRampUpdatedEvent;
+use super::helpers::emit;
+
+// =============================================================================
+// 调整 StableSwap 放大系数：从当前值线性过渡到 target_amp，在 ramp_end 时到达
+// =============================================================================
+// 突然改变放大系数会让价格跳变，给套利者留下机会，
+// 因此只允许在至少 MIN_RAMP_DURATION 秒内逐渐调整，且单次变化不超过 MAX_AMP_CHANGE 倍
+pub struct RampAmpInstructionData {
+    pub target_amp: u64,
+    pub ramp_end: i64,
+}
+
+impl<'info> TryFrom<&'info [u8]> for RampAmpInstructionData {
+    type Error = ProgramError;
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        if data.len() < size_of::<u64>() + size_of::<i64>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let target_amp = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let ramp_end = i64::from_le_bytes(data[8..16].try_into().unwrap());
+
+        Ok(Self {
+            target_amp,
+            ramp_end,
+        })
+    }
+}
+
+pub struct RampAmp<'info> {
+    pub accounts: AdminAccounts<'info>,
+    pub instruction_data: RampAmpInstructionData,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for RampAmp<'info> {
+    type Error = ProgramError;
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = AdminAccounts::try_from(accounts)?;
+        let instruction_data = RampAmpInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'info> RampAmp<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::RAMP_AMP;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let data = &self.instruction_data;
+        let now = Clock::get()?.unix_timestamp;
+
+        let mut config = self.accounts.load_config()?;
+        if config.curve_type() != CurveType::StableSwap as u8 {
+            return Err(AmmError::InvalidCurve.into());
+        }
+
+        // 从当前值开始调整，正在进行的调整会被新的调整取代
+        let current = config.current_amp(now);
+        stable::check_ramp(current, data.target_amp, now, data.ramp_end)?;
+        config.set_amp_ramp(current, data.target_amp, now, data.ramp_end);
+
+        self.accounts.emit_amp_ramp(current, data.target_amp, now, data.ramp_end);
+        Ok(())
+    }
+}
+
+// =============================================================================
+// 停止调整：放大系数固定在当前值
+// =============================================================================
+pub struct StopRampAmp<'info> {
+    pub accounts: AdminAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for StopRampAmp<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = AdminAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'info> StopRampAmp<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::STOP_RAMP_AMP;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+
+        let mut config = self.accounts.load_config()?;
+        if config.curve_type() != CurveType::StableSwap as u8 {
+            return Err(AmmError::InvalidCurve.into());
+        }
+
+        let current = config.current_amp(now);
+        config.set_amp_ramp(current, current, now, now);
+
+        self.accounts.emit_amp_ramp(current, current, now, now);
+        Ok(())
+    }
+}
+
+impl<'info> AdminAccounts<'info> {
+    #[inline(always)]
+    fn emit_amp_ramp(&self, amp_initial: u64, amp_target: u64, ramp_start: i64, ramp_end: i64) {
+        emit(&AmpRampUpdatedEvent {
+            pool: self.config.address().to_bytes(),
+            authority: self.authority.address().to_bytes(),
+            amp_initial,
+            amp_target,
+            ramp_start,
+            ramp_end,
+        });
+    }
+}
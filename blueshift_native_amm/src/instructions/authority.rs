@@ -0,0 +1,131 @@
+use pinocchio::{AccountView, Address, ProgramResult};
+use pinocchio::error::ProgramError;
+use crate::Config;
+use crate::layout::discriminator;
+use super::admin::AdminAccounts;
+use crate::events::AuthorityChangedEvent;
+use super::helpers::{emit, AccountCheck, SignerAccount};
+
+// =============================================================================
+// 两步转移 authority
+// =============================================================================
+// 1. 当前 authority 调用 ProposeAuthority 写入 pending_authority
+// 2. 新 authority 签名调用 AcceptAuthority 完成转移
+// 这样即使填错地址，也不会把池子的管理权交给无人控制的账户
+
+pub struct ProposeAuthorityInstructionData {
+    // 全 0 地址表示撤销当前的提议
+    pub new_authority: Address,
+}
+
+impl<'info> TryFrom<&'info [u8]> for ProposeAuthorityInstructionData {
+    type Error = ProgramError;
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        if data.len() < size_of::<Address>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let new_authority: [u8; 32] = data[0..32].try_into().unwrap();
+
+        Ok(Self {
+            new_authority: new_authority.into(),
+        })
+    }
+}
+
+pub struct ProposeAuthority<'info> {
+    pub accounts: AdminAccounts<'info>,
+    pub instruction_data: ProposeAuthorityInstructionData,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for ProposeAuthority<'info> {
+    type Error = ProgramError;
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = AdminAccounts::try_from(accounts)?;
+        let instruction_data = ProposeAuthorityInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'info> ProposeAuthority<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::PROPOSE_AUTHORITY;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let mut config = self.accounts.load_config()?;
+        config.set_pending_authority(self.instruction_data.new_authority);
+
+        emit(&AuthorityChangedEvent {
+            pool: self.accounts.config.address().to_bytes(),
+            authority: self.accounts.authority.address().to_bytes(),
+            pending_authority: self.instruction_data.new_authority.to_bytes(),
+        });
+        Ok(())
+    }
+}
+
+pub struct AcceptAuthorityAccounts<'info> {
+    pub new_authority: &'info AccountView,
+    pub config: &'info AccountView,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for AcceptAuthorityAccounts<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [new_authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(new_authority)?;
+
+        Ok(Self {
+            new_authority,
+            config,
+        })
+    }
+}
+
+pub struct AcceptAuthority<'info> {
+    pub accounts: AcceptAuthorityAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for AcceptAuthority<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = AcceptAuthorityAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'info> AcceptAuthority<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::ACCEPT_AUTHORITY;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+
+        //1. 不可变池子没有 authority，也就不存在转移
+        if config.has_authority().is_none() {
+            return Err(ProgramError::Immutable);
+        }
+
+        //2. 签名者必须是当前被提议的 authority
+        match config.has_pending_authority() {
+            Some(pending) if pending.eq(self.accounts.new_authority.address()) => {
+                config.set_authority(pending);
+                config.set_pending_authority(Address::default());
+
+                emit(&AuthorityChangedEvent {
+                    pool: self.accounts.config.address().to_bytes(),
+                    authority: self.accounts.new_authority.address().to_bytes(),
+                    pending_authority: [0u8; 32],
+                });
+                Ok(())
+            }
+            _ => Err(ProgramError::IncorrectAuthority),
+        }
+    }
+}
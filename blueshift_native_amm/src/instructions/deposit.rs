@@ -1,17 +1,24 @@
-use constant_product_curve::ConstantProduct;
-use pinocchio::{AccountView, ProgramResult};
-use pinocchio::cpi::{Seed, Signer};
+use pinocchio::{AccountView, Address, ProgramResult};
+use pinocchio::cpi::Signer;
 use pinocchio::error::ProgramError;
-use pinocchio::sysvars::{clock, Sysvar};
+use pinocchio::sysvars::Sysvar;
 use pinocchio::sysvars::clock::Clock;
-use pinocchio_token::instructions::{MintTo, Transfer};
-use pinocchio_token::state::{Mint, TokenAccount};
-use solana_address::Address;
+use pinocchio_token::instructions::MintTo;
+use pinocchio_token::state::Mint;
 use crate::Config;
+use crate::curve;
+use super::helpers::{emit, AccountCheck, AssociatedTokenAccount, AssociatedTokenAccountInit, MintLpAccount, PoolSide, SignerAccount, TokenProgram, UserTokenAccount};
+use super::token_interface::{token_account_amount, transfer_in, TransferFee};
+use crate::errors::AmmError;
+use crate::events::DepositEvent;
+pub use crate::layout::DepositInstructionData;
+use crate::layout::{discriminator, LOCKED_LP_SEED};
 
 pub struct DepositAccounts<'info>{
     pub user: &'info AccountView,
     pub mint_lp: &'info AccountView,
+    pub mint_x:&'info AccountView,
+    pub mint_y:&'info AccountView,
     pub vault_x:&'info AccountView,
     pub vault_y:&'info AccountView,
     pub user_x_ata:&'info AccountView,
@@ -19,41 +26,116 @@ pub struct DepositAccounts<'info>{
     pub user_lp_ata:&'info AccountView,
     pub config:&'info AccountView,
     pub token_program:&'info AccountView,
+    pub token_program_x:&'info AccountView,
+    pub token_program_y:&'info AccountView,
+    // 首次存款时必须提供，用于锁定最低流动性
+    pub locked_lp: Option<LockedLpAccounts<'info>>,
 }
 impl<'info> TryFrom<&'info [AccountView]> for DepositAccounts<'info> {
     type Error = ProgramError;
     fn try_from(accounts:&'info [AccountView]) -> Result<Self, Self::Error> {
         let [user,
         mint_lp,
+        mint_x,
+        mint_y,
         vault_x,
         vault_y,
         user_x_ata,
         user_y_ata,
         user_lp_ata,
         config,
-        token_program] = accounts else{
+        token_program,
+        token_program_x,
+        token_program_y,
+        locked_lp @ ..] = accounts else{
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
+        SignerAccount::check(user)?;
+        TokenProgram::check(token_program)?;
+        {
+            let config_data = Config::load(config)?;
+            PoolSide::check(config_data.mint_x(), mint_x, vault_x, user_x_ata, config, token_program_x)?;
+            PoolSide::check(config_data.mint_y(), mint_y, vault_y, user_y_ata, config, token_program_y)?;
+        }
+        MintLpAccount::check(mint_lp, config)?;
+        UserTokenAccount::check(user_lp_ata, mint_lp.address())?;
+
+        //额外传入 [locked_lp_owner, locked_lp_ata, system_program, associated_token_program] 时校验锁定账户
+        let locked_lp = match locked_lp {
+            [] => None,
+            locked_lp => {
+                let locked_lp = LockedLpAccounts::try_from(locked_lp)?;
+                locked_lp.check_owner(config)?;
+                Some(locked_lp)
+            }
+        };
+
         Ok(Self{
             user,
             mint_lp,
+            mint_x,
+            mint_y,
             vault_x,
             vault_y,
             user_x_ata,
             user_y_ata,
             user_lp_ata,
             config,
-            token_program
+            token_program,
+            token_program_x,
+            token_program_y,
+            locked_lp,
+        })
+    }
+}
+
+// =============================================================================
+// 锁定的最低流动性
+// =============================================================================
+// 首次存款铸造的 LP 中有 MINIMUM_LIQUIDITY 份转入 [LOCKED_LP_SEED, config] PDA 的 LP ATA，
+// 程序从不使用这个 PDA 签名，这部分 LP 永远不会被销毁
+pub struct LockedLpAccounts<'info> {
+    pub owner: &'info AccountView,
+    pub account: &'info AccountView,
+    pub system_program: &'info AccountView,
+    pub associated_token_program: &'info AccountView,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for LockedLpAccounts<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [owner, account, system_program, associated_token_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if system_program.address().ne(&pinocchio_system::ID)
+            || associated_token_program.address().ne(&pinocchio_associated_token_account::ID)
+        {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        Ok(Self {
+            owner,
+            account,
+            system_program,
+            associated_token_program,
         })
     }
 }
 
-pub struct DepositInstructionData{
-    pub amount:u64,
-    pub max_x:u64,
-    pub max_y:u64,
-    pub expirations:i64
+impl<'info> LockedLpAccounts<'info> {
+    // owner 必须是这个池子的锁定 PDA，ATA 地址由创建 / 校验 ATA 时检查
+    pub fn check_owner(&self, config: &AccountView) -> Result<(), ProgramError> {
+        let (owner, _) = Address::find_program_address(
+            &[LOCKED_LP_SEED, config.address().as_ref()],
+            &crate::ID,
+        );
+        if owner.ne(self.owner.address()) {
+            return Err(AmmError::InvalidAddress.into());
+        }
+        Ok(())
+    }
 }
 
 impl<'info> TryFrom<&'info [u8]> for DepositInstructionData{
@@ -96,7 +178,7 @@ impl<'info> TryFrom<(&'info [u8],&'info [AccountView])> for Deposit<'info>{
 }
 
 impl<'info> Deposit<'info>{
-    pub const DISCRIMINATOR: &'info u8 = &1;
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::DEPOSIT;
 
     pub fn process(&mut self) -> ProgramResult{
         let accounts = &self.accounts;
@@ -105,78 +187,151 @@ impl<'info> Deposit<'info>{
         //1. 过期检查
         let clock = Clock::get()?;
         if clock.unix_timestamp > data.expirations {
-            return Err(ProgramError::InvalidArgument);
+            return Err(AmmError::Expired.into());
         }
 
-        // 2. 加载Config并验证状态
-        let config = Config::load(accounts.config)?;
-        if config.state() != 1 {
-            return Err(ProgramError::InvalidAccountData);
-        };
+        // 2. 加载Config并验证状态，存款会更新 TWAP，因此以可写方式加载
+        let mut config = Config::load_mut(accounts.config)?;
+        config.check_active()?;
 
         // 3.反序列化代币账户信息
         let mint_lp = unsafe { Mint::from_account_view_unchecked(accounts.mint_lp)? };
-        let vault_x = unsafe { TokenAccount::from_account_view_unchecked(accounts.vault_x)? };
-        let vault_y = unsafe { TokenAccount::from_account_view_unchecked(accounts.vault_y)? };
+        let supply = mint_lp.supply();
+        // 金库中尚未领取的协议费不属于 LP，不计入储备
+        let (reserve_x, reserve_y) = config.reserves(
+            token_account_amount(accounts.vault_x)?,
+            token_account_amount(accounts.vault_y)?,
+        )?;
+
+        // 储备变化之前，用变化前的储备推进 TWAP 累计价格
+        config.update_oracle(reserve_x, reserve_y, clock.unix_timestamp)?;
 
-        // 4. 计算存款金额(x,y)
-        let (x,y) = if mint_lp.supply() == 0{
-            //初始流动性:使用用户指定的 max值
-            (data.max_x, data.max_y)
+        // 4. 计算存款金额(x,y)，即用户需要转出的数量
+        // required 为后续存款时金库必须实际收到的数量，首次存款没有要求
+        let (x,y,required) = if supply == 0{
+            //初始流动性:存入用户指定的 max值，LP 数量在到账后按 sqrt(x * y) 计算
+            //同时需要锁定最低流动性的账户
+            if accounts.locked_lp.is_none() {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            }
+            (data.max_x, data.max_y, None)
         } else {
-            //后续流动性:基于比例计算
-            let amounts = ConstantProduct::xy_deposit_amounts_from_l(
-                vault_x.amount(),
-                vault_y.amount(),
-                mint_lp.supply(),
+            //后续流动性:基于比例计算金库需要收到的数量
+            let amounts = curve::deposit_amounts(
+                reserve_x,
+                reserve_y,
+                supply,
                 data.amount,
-                6,
-            ).map_err(|_| ProgramError::ArithmeticOverflow)?;
-            (amounts.x, amounts.y)
+            )?;
+
+            //带 TransferFee 扩展的 Mint 需要多转一部分，保证金库实际到账不少于曲线要求
+            let epoch = clock.epoch;
+            (
+                TransferFee::from_mint(accounts.mint_x, epoch)?.gross_amount(amounts.x)?,
+                TransferFee::from_mint(accounts.mint_y, epoch)?.gross_amount(amounts.y)?,
+                Some((amounts.x, amounts.y)),
+            )
         };
 
         // 5. 滑点保护检查
         if x > data.max_x || y > data.max_y {
-            return Err(ProgramError::InvalidArgument);
+            return Err(AmmError::SlippageExceeded.into());
         }
 
         // 6. 执行代币转移(用户 -》 金库)
-        Transfer{
-            from:accounts.user_x_ata,
-            to:accounts.vault_x,
-            authority:accounts.user,
-            amount:x,
-        }.invoke()?;
-
-        Transfer{
-            from:accounts.user_y_ata,
-            to:accounts.vault_y,
-            authority:accounts.user,
-            amount:y,
-        }.invoke()?;
-
-        //7. 签署并执行MintTo(Config PDA -》 用户)
-        let seed_binding = config.seed().to_le_bytes();
-        let mint_x = config.mint_x();
-        let mint_y = config.mint_y();
-        let bump = config.config_bump();
-
-        let config_seeds = [
-            Seed::from(b"config"),
-            Seed::from(&seed_binding),
-            Seed::from(mint_x.as_ref()),
-            Seed::from(mint_y.as_ref()),
-            Seed::from(bump),
-        ];
-        let signer = Signer::from(&config_seeds);
+        let received_x = transfer_in(
+            accounts.user_x_ata,
+            accounts.mint_x,
+            accounts.vault_x,
+            accounts.user,
+            x,
+            accounts.token_program_x,
+        )?;
+
+        let received_y = transfer_in(
+            accounts.user_y_ata,
+            accounts.mint_y,
+            accounts.vault_y,
+            accounts.user,
+            y,
+            accounts.token_program_y,
+        )?;
+
+        // 后续存款正好铸造 amount，金库实际到账不能少于曲线要求
+        // 与 SwapExactOut 相同，不依赖补足计算，按到账数量再检查一次
+        if let Some((required_x, required_y)) = required {
+            if received_x < required_x || received_y < required_y {
+                return Err(AmmError::SlippageExceeded.into());
+            }
+        }
+
+        // 7. 计算铸造给用户的 LP
+        // 首次存款按金库实际到账的数量计算 sqrt(x * y)，扣除永久锁定的部分，amount 作为可接受的最少 LP
+        // 后续存款正好铸造 amount
+        let lp = if supply == 0 {
+            let lp = curve::initial_liquidity(received_x, received_y)?;
+            if lp < data.amount {
+                return Err(AmmError::SlippageExceeded.into());
+            }
+            lp
+        } else {
+            data.amount
+        };
+
+        //8. 签署并执行MintTo(Config PDA -》 用户)
+        // Config 作为签名者参与 CPI，需要先释放可写借用
+        let config_signer = config.signer();
+        drop(config);
+
+        let config_seeds = config_signer.seeds();
+        let signer = [Signer::from(&config_seeds)];
 
         MintTo{
             mint:accounts.mint_lp,
             account:accounts.user_lp_ata,
             mint_authority: accounts.config,
-            amount:data.amount,
-        }.invoke_signed(&[signer])?;
+            amount:lp,
+        }.invoke_signed(&signer)?;
+
+        //9. 首次存款：把 MINIMUM_LIQUIDITY 铸造到锁定账户，LP 供应量从此不会回到 0
+        if supply == 0 {
+            let Some(locked_lp) = &accounts.locked_lp else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+
+            AssociatedTokenAccount::init_if_needed(
+                locked_lp.account,
+                accounts.mint_lp,
+                accounts.user,
+                locked_lp.owner,
+                locked_lp.system_program,
+                accounts.token_program,
+            )?;
+
+            MintTo{
+                mint:accounts.mint_lp,
+                account:locked_lp.account,
+                mint_authority: accounts.config,
+                amount:curve::MINIMUM_LIQUIDITY,
+            }.invoke_signed(&signer)?;
+        }
+
+        let locked = if supply == 0 { curve::MINIMUM_LIQUIDITY } else { 0 };
+        emit(&DepositEvent {
+            pool: accounts.config.address().to_bytes(),
+            user: accounts.user.address().to_bytes(),
+            amount_x: received_x,
+            amount_y: received_y,
+            swap_amount: 0,
+            lp_amount: lp,
+            reserve_x: reserve_x.checked_add(received_x).ok_or(ProgramError::ArithmeticOverflow)?,
+            reserve_y: reserve_y.checked_add(received_y).ok_or(ProgramError::ArithmeticOverflow)?,
+            lp_supply: supply
+                .checked_add(lp)
+                .and_then(|supply| supply.checked_add(locked))
+                .ok_or(ProgramError::ArithmeticOverflow)?,
+        });
 
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,244 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::cpi::{invoke_with_bounds, Signer};
+use pinocchio::error::ProgramError;
+use pinocchio::instruction::{InstructionAccount, InstructionView};
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use crate::Config;
+use crate::curve;
+use super::helpers::emit;
+use super::swap::SwapAccounts;
+use super::token_interface::{mint_decimals, token_account_amount, TransferChecked};
+use crate::errors::AmmError;
+use crate::events::FlashLoanEvent;
+use crate::layout::discriminator;
+
+// =============================================================================
+// 闪电贷："借出 amount_x 个 X 和 amount_y 个 Y，在同一条指令内归还本金和手续费"
+// =============================================================================
+// 账户：[borrower, mint_x, mint_y, borrower_x_ata, borrower_y_ata, vault_x, vault_y, config,
+//        token_program_x, token_program_y, receiver_program, receiver 的账户...]
+// 前 10 个账户与 Swap 相同
+//
+// 1. 从金库转出借款到 borrower 的 ATA
+// 2. 以 receiver 的账户和指令数据 CPI 到 receiver_program，由它完成套利并归还
+// 3. 检查两个金库的余额不少于借出前的余额加手续费，否则整条指令回滚
+//
+// Config 只为金库转出签名，不会把签名传给 receiver
+// 运行时不允许 receiver 再 CPI 回本程序（只允许直接自调用），
+// 因此借款期间无法在储备不完整的池子上交换、存款或取款
+pub struct FlashLoanAccounts<'info> {
+    pub pool: SwapAccounts<'info>,
+    pub receiver_program: &'info AccountView,
+    pub receiver_accounts: &'info [AccountView],
+}
+
+impl<'info> FlashLoanAccounts<'info> {
+    // 传给 receiver 的账户数量上限
+    pub const MAX_RECEIVER_ACCOUNTS: usize = 32;
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for FlashLoanAccounts<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [borrower, rest @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        if rest.len() < SwapAccounts::POOL_ACCOUNTS_LEN {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let (pool, [receiver_program, receiver_accounts @ ..]) = rest.split_at(SwapAccounts::POOL_ACCOUNTS_LEN) else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // borrower 签名，其 ATA 与金库属于这个池子
+        let pool = SwapAccounts::from_pool(borrower, pool)?;
+
+        // 直接自调用会重入本程序，receiver 不能是本程序
+        if receiver_program.address().eq(&crate::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if receiver_accounts.len() > Self::MAX_RECEIVER_ACCOUNTS {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(Self {
+            pool,
+            receiver_program,
+            receiver_accounts,
+        })
+    }
+}
+
+// 数据布局：[amount_x: u64][amount_y: u64][receiver 的指令数据...]
+pub struct FlashLoanInstructionData<'info> {
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub receiver_data: &'info [u8],
+}
+
+impl<'info> TryFrom<&'info [u8]> for FlashLoanInstructionData<'info> {
+    type Error = ProgramError;
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        const HEADER_LEN: usize = 16;
+        if data.len() < HEADER_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let amount_x = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let amount_y = u64::from_le_bytes(data[8..16].try_into().unwrap());
+
+        Ok(Self {
+            amount_x,
+            amount_y,
+            receiver_data: &data[HEADER_LEN..],
+        })
+    }
+}
+
+pub struct FlashLoan<'info> {
+    pub accounts: FlashLoanAccounts<'info>,
+    pub instruction_data: FlashLoanInstructionData<'info>,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for FlashLoan<'info> {
+    type Error = ProgramError;
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = FlashLoanAccounts::try_from(accounts)?;
+        let instruction_data = FlashLoanInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'info> FlashLoan<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::FLASH_LOAN;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let pool = &self.accounts.pool;
+        let data = &self.instruction_data;
+
+        if data.amount_x == 0 && data.amount_y == 0 {
+            return Err(AmmError::ZeroAmount.into());
+        }
+
+        //1. 加载配置，与交换相同，只有正常状态的池子可以借出
+        let clock = Clock::get()?;
+        let mut config = Config::load_mut(pool.config)?;
+        config.check_active()?;
+
+        //2. 借出前的金库余额，归还后的余额以此为基准
+        let balance_x = token_account_amount(pool.vault_x)?;
+        let balance_y = token_account_amount(pool.vault_y)?;
+
+        // 只能借出属于 LP 的储备，尚未领取的协议费不外借
+        let (reserve_x, reserve_y) = config.reserves(balance_x, balance_y)?;
+        if data.amount_x > reserve_x || data.amount_y > reserve_y {
+            return Err(AmmError::InsufficientLiquidity.into());
+        }
+
+        // 储备即将暂时变化，先用借出前的储备推进 TWAP 累计价格
+        config.update_oracle(reserve_x, reserve_y, clock.unix_timestamp)?;
+
+        //3. 手续费按交换费率向上取整，其中协议的部分单独记账
+        let fee = u16::from_le_bytes(*config.fee());
+        let fee_x = curve::flash_loan_fee(data.amount_x, fee)?;
+        let fee_y = curve::flash_loan_fee(data.amount_y, fee)?;
+        let protocol_fee_x = curve::protocol_fee(fee_x, config.protocol_fee())?;
+        let protocol_fee_y = curve::protocol_fee(fee_y, config.protocol_fee())?;
+
+        let repay_x = balance_x.checked_add(fee_x).ok_or(ProgramError::ArithmeticOverflow)?;
+        let repay_y = balance_y.checked_add(fee_y).ok_or(ProgramError::ArithmeticOverflow)?;
+
+        //4. 准备签名种子(用于从金库转出)
+        // Config 作为签名者参与 CPI，并且可能出现在 receiver 的账户中，转出前需要释放可写借用
+        let config_signer = config.signer();
+        drop(config);
+
+        let config_seeds = config_signer.seeds();
+        let signer = [Signer::from(&config_seeds)];
+
+        //5. 从金库转出借款
+        for (amount, vault, mint, borrower_ata, token_program) in [
+            (data.amount_x, pool.vault_x, pool.mint_x, pool.user_x_ata, pool.token_program_x),
+            (data.amount_y, pool.vault_y, pool.mint_y, pool.user_y_ata, pool.token_program_y),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+
+            TransferChecked {
+                from: vault,
+                mint,
+                to: borrower_ata,
+                authority: pool.config,
+                amount,
+                decimals: mint_decimals(mint)?,
+                token_program: token_program.address(),
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        //6. CPI 到 receiver，账户的签名和可写权限与本指令中的相同
+        self.accounts.invoke_receiver(data.receiver_data)?;
+
+        //7. 检查归还：金库余额不少于借出前的余额加手续费
+        // 多归还的部分与手续费一样留在池子里归 LP
+        let balance_x = token_account_amount(pool.vault_x)?;
+        let balance_y = token_account_amount(pool.vault_y)?;
+        if balance_x < repay_x || balance_y < repay_y {
+            return Err(AmmError::FlashLoanNotRepaid.into());
+        }
+
+        //8. 记录协议费
+        let mut config = Config::load_mut(pool.config)?;
+        if protocol_fee_x > 0 {
+            config.accrue_protocol_fee(true, protocol_fee_x)?;
+        }
+        if protocol_fee_y > 0 {
+            config.accrue_protocol_fee(false, protocol_fee_y)?;
+        }
+
+        let (reserve_x, reserve_y) = config.reserves(balance_x, balance_y)?;
+        emit(&FlashLoanEvent {
+            pool: pool.config.address().to_bytes(),
+            borrower: pool.user.address().to_bytes(),
+            amount_x: data.amount_x,
+            amount_y: data.amount_y,
+            fee_x,
+            fee_y,
+            reserve_x,
+            reserve_y,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> FlashLoanAccounts<'info> {
+    // 以 receiver_accounts 和 data 调用 receiver_program，不附加任何 PDA 签名
+    fn invoke_receiver(&self, data: &[u8]) -> ProgramResult {
+        const MAX: usize = FlashLoanAccounts::MAX_RECEIVER_ACCOUNTS;
+        let len = self.receiver_accounts.len();
+
+        // 固定长度的数组，只使用前 len 个元素，其余用 receiver_program 填充
+        let account_views: [&AccountView; MAX] = core::array::from_fn(|i| {
+            self.receiver_accounts.get(i).unwrap_or(self.receiver_program)
+        });
+        let instruction_accounts: [InstructionAccount; MAX] = core::array::from_fn(|i| {
+            let account = account_views[i];
+            InstructionAccount::new(account.address(), account.is_writable(), account.is_signer())
+        });
+
+        let instruction = InstructionView {
+            program_id: self.receiver_program.address(),
+            accounts: &instruction_accounts[..len],
+            data,
+        };
+
+        invoke_with_bounds::<MAX>(&instruction, &account_views[..len])
+    }
+}
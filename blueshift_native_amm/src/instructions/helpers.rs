@@ -3,9 +3,13 @@ use pinocchio::cpi::{Seed, Signer};
 use pinocchio::error::ProgramError;
 use pinocchio::sysvars::rent::Rent;
 use pinocchio::sysvars::Sysvar;
-use pinocchio_associated_token_account::instructions::Create;
-use pinocchio_system::instructions::CreateAccount;
-use crate::errors::EscrowError;
+use pinocchio_associated_token_account::instructions::{Create, CreateIdempotent};
+use pinocchio_system::instructions::{Allocate, Assign, CreateAccount, Transfer};
+use pinocchio_token::state::TokenAccount;
+use crate::Config;
+use crate::errors::AmmError;
+use crate::events::{Event, EVENT_VERSION};
+use crate::layout::MINT_LP_SEED;
 
 pub trait AccountCheck {
     // 验证账户是否符合要求
@@ -53,26 +57,12 @@ impl AccountCheck for SignerAccount {
         // is_signer() 检查账户是否在交易的签名者列表中
         // 对应 Anchor 的 Signer 类型自动进行的验证
         if !account.is_signer() {
-            return Err(EscrowError::NotSigner.into());
+            return Err(AmmError::NotSigner.into());
         }
         Ok(())
     }
 }
 
-pub struct SystemAccount;
-
-impl AccountCheck for SystemAccount {
-    fn check(account: &AccountView) -> Result<(), ProgramError> {
-        // owned_by() 检查账户的 owner 是否为指定程序
-        // System Program 的 ID 是固定的
-        if !account.owned_by(&pinocchio_system::ID) {
-            return Err(EscrowError::InvalidOwner.into());
-        }
-
-        Ok(())
-    }
-}
-
 // =============================================================================
 // Token-2022 Program 常量
 // =============================================================================
@@ -104,11 +94,11 @@ impl AccountCheck for MintInterface {
         if !account.owned_by(&TOKEN_2022_PROGRAM_ID) {
             // 如果不是 Token-2022，检查是否是旧版 Token Program
             if !account.owned_by(&pinocchio_token::ID) {
-                return Err(EscrowError::InvalidOwner.into());
+                return Err(AmmError::InvalidOwner.into());
             } else {
                 // 旧版 Token Program 的 Mint 账户长度验证
                 if account.data_len().ne(&pinocchio_token::state::Mint::LEN) {
-                    return Err(EscrowError::InvalidAccountData.into());
+                    return Err(AmmError::InvalidAccountData.into());
                 }
             }
         } else {
@@ -120,11 +110,11 @@ impl AccountCheck for MintInterface {
             if data.len().ne(&pinocchio_token::state::Mint::LEN) {
                 // 如果长度小于判别器偏移量，数据无效
                 if data.len().le(&TOKEN_2022_ACCOUNT_DISCRIMINATOR_OFFSET) {
-                    return Err(EscrowError::InvalidAccountData.into());
+                    return Err(AmmError::InvalidAccountData.into());
                 }
                 // 检查判别器是否为 Mint 类型（0x01）
                 if data[TOKEN_2022_ACCOUNT_DISCRIMINATOR_OFFSET].ne(&TOKEN_2022_MINT_DISCRIMINATOR) {
-                    return Err(EscrowError::InvalidAccountData.into());
+                    return Err(AmmError::InvalidAccountData.into());
                 }
             }
         }
@@ -140,11 +130,11 @@ impl AccountCheck for TokenAccountInterface {
         if !account.owned_by(&TOKEN_2022_PROGRAM_ID) {
             // 如果不是 Token-2022，检查是否是旧版 Token Program
             if !account.owned_by(&pinocchio_token::ID) {
-                return Err(EscrowError::InvalidOwner.into());
+                return Err(AmmError::InvalidOwner.into());
             } else {
                 // 旧版 Token Account 长度验证
                 if account.data_len().ne(&pinocchio_token::state::TokenAccount::LEN) {
-                    return Err(EscrowError::InvalidAccountData.into());
+                    return Err(AmmError::InvalidAccountData.into());
                 }
             }
         } else {
@@ -154,13 +144,13 @@ impl AccountCheck for TokenAccountInterface {
             if data.len().ne(&pinocchio_token::state::TokenAccount::LEN) {
                 // 检查长度是否足够包含判别器
                 if data.len().le(&TOKEN_2022_ACCOUNT_DISCRIMINATOR_OFFSET) {
-                    return Err(EscrowError::InvalidAccountData.into());
+                    return Err(AmmError::InvalidAccountData.into());
                 }
                 // 检查判别器是否为 Token Account 类型（0x02）
                 if data[TOKEN_2022_ACCOUNT_DISCRIMINATOR_OFFSET]
                     .ne(&TOKEN_2022_TOKEN_ACCOUNT_DISCRIMINATOR)
                 {
-                    return Err(EscrowError::InvalidAccountData.into());
+                    return Err(AmmError::InvalidAccountData.into());
                 }
             }
         }
@@ -177,6 +167,45 @@ impl AssociatedTokenAccountCheck for AssociatedTokenAccount {
         authority: &AccountView,
         mint: &AccountView,
         token_program: &AccountView,
+    ) -> Result<(), ProgramError> {
+        Self::check_address(
+            account,
+            authority.address(),
+            mint.address(),
+            token_program.address(),
+        )
+    }
+}
+
+impl AssociatedTokenAccount {
+    // 创建 ATA，账户已经存在时不报错（CreateIdempotent）
+    // 用于地址可以被提前算出的 ATA，避免别人抢先创建后本指令永远失败
+    // ATA Program 会验证已存在账户的 owner，调用者仍需自行验证其余字段
+    pub fn init_idempotent(
+        account: &AccountView,
+        mint: &AccountView,
+        payer: &AccountView,
+        owner: &AccountView,
+        system_program: &AccountView,
+        token_program: &AccountView,
+    ) -> ProgramResult {
+        CreateIdempotent {
+            funding_account: payer,
+            account,
+            wallet: owner,
+            mint,
+            system_program,
+            token_program,
+        }.invoke()
+    }
+
+    // 按地址验证 ATA
+    // 用于 mint 或 owner 只以地址形式存在（例如保存在 Config 中）的场景
+    pub fn check_address(
+        account: &AccountView,
+        authority: &Address,
+        mint: &Address,
+        token_program: &Address,
     ) -> Result<(), ProgramError> {
         // 先验证账户是否是有效的 Token Account
         TokenAccountInterface::check(account)?;
@@ -185,9 +214,9 @@ impl AssociatedTokenAccountCheck for AssociatedTokenAccount {
         // ATA 的派生种子：[authority, token_program, mint]
         let (pda, _bump) = Address::find_program_address(
             &[
-                authority.address().as_ref(),    // 所有者地址
-                token_program.address().as_ref(),  // Token Program 地址
-                mint.address().as_ref(),           // Mint 地址
+                authority.as_ref(),        // 所有者地址
+                token_program.as_ref(),    // Token Program 地址
+                mint.as_ref(),             // Mint 地址
             ],
             &pinocchio_associated_token_account::ID,  // ATA Program ID
         );
@@ -198,7 +227,7 @@ impl AssociatedTokenAccountCheck for AssociatedTokenAccount {
         // 验证计算出的 PDA 地址是否与传入的账户地址匹配
         // 这确保传入的账户确实是正确的 ATA
         if pda_address.ne(account.address()) {
-            return Err(EscrowError::InvalidAddress.into());
+            return Err(AmmError::InvalidAddress.into());
         }
 
         Ok(())
@@ -256,6 +285,151 @@ impl AssociatedTokenAccountInit for AssociatedTokenAccount {
     }
 }
 
+// =============================================================================
+// Token Program 验证
+// =============================================================================
+// 只接受旧版 Token Program 或 Token-2022，防止调用者传入伪造的程序
+pub struct TokenProgramInterface;
+
+impl AccountCheck for TokenProgramInterface {
+    fn check(account: &AccountView) -> Result<(), ProgramError> {
+        if account.address().ne(&pinocchio_token::ID) && account.address().ne(&TOKEN_2022_PROGRAM_ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        Ok(())
+    }
+}
+
+// LP mint 始终由旧版 Token Program 创建，MintTo / Burn 也只 CPI 到旧版程序
+pub struct TokenProgram;
+
+impl AccountCheck for TokenProgram {
+    fn check(account: &AccountView) -> Result<(), ProgramError> {
+        if account.address().ne(&pinocchio_token::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        Ok(())
+    }
+}
+
+// =============================================================================
+// 池子相关账户验证
+// =============================================================================
+
+// 金库：必须是 config PDA 在对应 mint 下的 ATA
+pub struct VaultAccount;
+
+impl VaultAccount {
+    pub fn check(
+        vault: &AccountView,
+        config: &AccountView,
+        mint: &Address,
+        token_program: &AccountView,
+    ) -> Result<(), ProgramError> {
+        AssociatedTokenAccount::check_address(vault, config.address(), mint, token_program.address())
+            .map_err(|_| AmmError::InvalidVault.into())
+    }
+
+    // Initialize 创建金库之后的检查
+    // Config PDA 的地址可以提前算出，任何人都可以抢先创建它的 ATA，
+    // 因此已存在的金库除了地址之外，还要验证由该侧的 token program 拥有，且 mint 和 owner 字段正确
+    pub fn check_created(
+        vault: &AccountView,
+        config: &AccountView,
+        mint: &Address,
+        token_program: &AccountView,
+    ) -> Result<(), ProgramError> {
+        Self::check(vault, config, mint, token_program)?;
+
+        if !vault.owned_by(token_program.address()) {
+            return Err(AmmError::InvalidVault.into());
+        }
+
+        let data = vault.try_borrow()?;
+        let token_account = unsafe { TokenAccount::from_bytes_unchecked(&data) };
+        if token_account.mint().ne(mint) || token_account.owner().ne(config.address()) {
+            return Err(AmmError::InvalidVault.into());
+        }
+
+        Ok(())
+    }
+}
+
+// LP Mint：必须是种子为 [b"mint_lp", config] 的 PDA
+// 使用 Config 中保存的 lp_bump，避免每次都调用 find_program_address
+pub struct MintLpAccount;
+
+impl MintLpAccount {
+    pub fn check(mint_lp: &AccountView, config: &AccountView) -> Result<(), ProgramError> {
+        MintInterface::check(mint_lp).map_err(|_| AmmError::InvalidLpMint)?;
+
+        let lp_bump = *Config::load(config)?.lp_bump();
+        let pda = Address::create_program_address(
+            &[MINT_LP_SEED, config.address().as_ref(), &lp_bump],
+            &crate::ID,
+        )?;
+
+        if pda.ne(mint_lp.address()) {
+            return Err(AmmError::InvalidLpMint.into());
+        }
+
+        Ok(())
+    }
+}
+
+// 用户代币账户：必须是有效的 Token Account，且 mint 与池子记录的一致
+pub struct UserTokenAccount;
+
+impl UserTokenAccount {
+    pub fn check(account: &AccountView, mint: &Address) -> Result<(), ProgramError> {
+        TokenAccountInterface::check(account)?;
+
+        let data = account.try_borrow()?;
+        let token_account = unsafe { TokenAccount::from_bytes_unchecked(&data) };
+
+        if token_account.mint().ne(mint) {
+            return Err(AmmError::InvalidTokenAccount.into());
+        }
+
+        Ok(())
+    }
+}
+
+// 验证交易对的一侧（X 或 Y）：
+// - mint 地址与 Config 中记录的一致，且由该侧的 token program 拥有
+// - 金库是 config PDA 在该 mint 下的 ATA
+// - 用户代币账户的 mint 正确
+// 每一侧可以分别使用旧版 Token Program 或 Token-2022
+pub struct PoolSide;
+
+impl PoolSide {
+    pub fn check(
+        expected_mint: &Address,
+        mint: &AccountView,
+        vault: &AccountView,
+        user_ata: &AccountView,
+        config: &AccountView,
+        token_program: &AccountView,
+    ) -> Result<(), ProgramError> {
+        TokenProgramInterface::check(token_program)?;
+
+        if mint.address().ne(expected_mint) {
+            return Err(AmmError::InvalidMint.into());
+        }
+        MintInterface::check(mint)?;
+        if !mint.owned_by(token_program.address()) {
+            return Err(AmmError::InvalidOwner.into());
+        }
+
+        VaultAccount::check(vault, config, expected_mint, token_program)?;
+        UserTokenAccount::check(user_ata, expected_mint)?;
+
+        Ok(())
+    }
+}
+
 pub struct ProgramAccount;
 
 impl AccountCheck for ProgramAccount {
@@ -263,12 +437,12 @@ impl AccountCheck for ProgramAccount {
         // 验证账户由本程序拥有
         // 对应 Anchor 的 Account<T> 自动进行的 owner 检查
         if !account.owned_by(&crate::ID) {
-            return Err(EscrowError::InvalidOwner.into());
+            return Err(AmmError::InvalidOwner.into());
         }
 
         // 验证账户数据长度是否匹配 Escrow 结构体
         if account.data_len().ne(&crate::state::Config::LEN) {
-            return Err(EscrowError::InvalidAccountData.into());
+            return Err(AmmError::InvalidAccountData.into());
         }
 
         Ok(())
@@ -300,6 +474,29 @@ impl ProgramAccountInit for ProgramAccount {
         // 对应 Anchor 的 bump 自动处理
         let signer = [Signer::from(seeds)];
 
+        // PDA 的地址可以提前算出，任何人都可以先向它转入 lamports，
+        // 此时 CreateAccount 会失败，改为补足租金后分配空间并转移所有权
+        // 已经有数据或不归 System Program 所有的账户，Allocate / Assign 仍然会失败
+        let current_lamports = account.lamports();
+        if current_lamports > 0 {
+            if lamports > current_lamports {
+                Transfer {
+                    from: payer,
+                    to: account,
+                    lamports: lamports - current_lamports,
+                }.invoke()?;
+            }
+            Allocate {
+                account,
+                space: space as u64,
+            }.invoke_signed(&signer)?;
+            Assign {
+                account,
+                owner: &crate::ID,
+            }.invoke_signed(&signer)?;
+            return Ok(());
+        }
+
         // 创建账户并设置为本程序拥有
         // invoke_signed 使用 PDA 签名
         CreateAccount {
@@ -315,28 +512,32 @@ impl ProgramAccountInit for ProgramAccount {
     }
 }
 
-pub trait AccountClose {
-    fn close(account: &AccountView, destination: &AccountView) -> ProgramResult;
+// =============================================================================
+// 指令数据中的布尔值
+// =============================================================================
+// 只接受 0 和 1，其他值直接按 bool 读取是未定义行为
+#[inline(always)]
+pub fn read_flag(byte: u8) -> Result<bool, ProgramError> {
+    match byte {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
 }
 
-impl AccountClose for ProgramAccount {
-    fn close(account: &AccountView, destination: &AccountView) -> ProgramResult {
-        {
-            // 将账户数据的第一个字节设置为 0xff
-            // 这是 Solana 的惯例，表示账户已关闭
-            let mut data = account.try_borrow_mut()?;
-            data[0] = 0xff;
-        }
-
-        // 将账户的 lamports 转给目标账户
-        // 对应 Anchor 的 close = destination 约束
-        destination.set_lamports(destination.lamports()+account.lamports());
-
-        // 将账户大小缩减到 1 字节（只剩下 0xff 标记）
-        account.resize(1)?;
-
-        // 关闭账户
-        // 此时账户的 lamports 已被转移，数据被清零
-        account.close()
+// =============================================================================
+// 事件输出
+// =============================================================================
+// 事件作为 sol_log_data 的两个字段输出：[kind, version] 和事件本身，格式见 crate::events
+// 不在链上运行（例如单元测试）时没有日志系统调用，不输出任何内容
+#[inline(always)]
+pub fn emit<E: Event>(event: &E) {
+    let fields: [&[u8]; 2] = [&[E::KIND, EVENT_VERSION], event.as_bytes()];
+
+    #[cfg(target_os = "solana")]
+    unsafe {
+        pinocchio::syscalls::sol_log_data(fields.as_ptr() as *const u8, fields.len() as u64);
     }
+    #[cfg(not(target_os = "solana"))]
+    core::hint::black_box(fields);
 }
@@ -1,5 +1,5 @@
 use std::mem::MaybeUninit;
-use pinocchio::{AccountView, ProgramResult};
+use pinocchio::{AccountView, Address, ProgramResult};
 use pinocchio::cpi::{Seed, Signer};
 use pinocchio::error::ProgramError;
 use pinocchio::sysvars::rent::Rent;
@@ -7,61 +7,136 @@ use pinocchio::sysvars::Sysvar;
 use pinocchio_system::instructions::CreateAccount;
 use pinocchio_token::instructions::InitializeMint2;
 use pinocchio_token::state::Mint;
-use crate::Config;
+use crate::{Config, ConfigSigner};
+use crate::errors::AmmError;
+use crate::events::InitializeEvent;
+pub use crate::layout::InitializeInstructionData;
+use crate::layout::{discriminator, is_canonical, CONFIG_SEED, MINT_LP_SEED};
+use super::lp_metadata::{CreateMetadataAccountV3, LpMetadataAccounts, MintMetadataAccounts};
+use super::registry::RegisterPoolAccounts;
+use super::helpers::{emit, AccountCheck, AssociatedTokenAccount, MintInterface, SignerAccount, TokenProgram, TokenProgramInterface, VaultAccount};
 
 pub struct InitializeAccounts<'info>{
     pub initializer: &'info AccountView,
     pub mint_lp: &'info AccountView,
+    pub mint_x: &'info AccountView,
+    pub mint_y: &'info AccountView,
+    pub vault_x: &'info AccountView,
+    pub vault_y: &'info AccountView,
     pub config: &'info AccountView,
     pub token_program: &'info AccountView,
+    pub token_program_x: &'info AccountView,
+    pub token_program_y: &'info AccountView,
     pub system_program: &'info AccountView,
+    pub associated_token_program: &'info AccountView,
+    // 注册表和这个交易对的记录，创建池子时同时注册
+    pub registry: &'info AccountView,
+    pub pair_record: &'info AccountView,
+    // 可选：LP Mint 的 Metaplex Metadata 账户和 Metaplex 程序，以及两个 Mint 的 Metadata
+    // 不传入时之后可以由池子的 authority 通过 CreateLpMetadata 创建
+    pub lp_metadata: Option<(LpMetadataAccounts<'info>, MintMetadataAccounts<'info>)>,
 }
 
 impl<'info> TryFrom<&'info [AccountView]> for InitializeAccounts<'info>{
     type Error = ProgramError;
     fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error>{
-        let [initializer, mint_lp, config, token_program, system_program] = accounts else {
+        let [
+            initializer,
+            mint_lp,
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            config,
+            token_program,
+            token_program_x,
+            token_program_y,
+            system_program,
+            associated_token_program,
+            registry,
+            pair_record,
+            lp_metadata @ ..
+        ] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
+        SignerAccount::check(initializer)?;
+        //LP mint 由旧版 Token Program 创建，两侧可以分别使用旧版 Token Program 或 Token-2022
+        TokenProgram::check(token_program)?;
+        TokenProgramInterface::check(token_program_x)?;
+        TokenProgramInterface::check(token_program_y)?;
+
+        if system_program.address().ne(&pinocchio_system::ID)
+            || associated_token_program.address().ne(&pinocchio_associated_token_account::ID)
+        {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        //两个 mint 必须是由各自 token program 拥有的真实 Mint，且不能相同
+        MintInterface::check(mint_x)?;
+        MintInterface::check(mint_y)?;
+        if !mint_x.owned_by(token_program_x.address()) || !mint_y.owned_by(token_program_y.address()) {
+            return Err(AmmError::InvalidOwner.into());
+        }
+        if mint_x.address().eq(mint_y.address()) {
+            return Err(AmmError::InvalidMint.into());
+        }
+        //两个 mint 必须按规范顺序排列，同一个交易对的所有池子都记录在同一个 PairRecord 中
+        if !is_canonical(mint_x.address(), mint_y.address()) {
+            return Err(AmmError::NonCanonicalMints.into());
+        }
+
+        //额外传入 [metadata, metadata_program, mint_x_metadata, mint_y_metadata] 时同时为 LP mint 创建元数据
+        let lp_metadata = match lp_metadata {
+            [] => None,
+            [metadata @ .., mint_x_metadata, mint_y_metadata] => Some((
+                LpMetadataAccounts::try_from(metadata)?,
+                MintMetadataAccounts {
+                    mint_x_metadata,
+                    mint_y_metadata,
+                },
+            )),
+            _ => return Err(ProgramError::NotEnoughAccountKeys),
+        };
+
         Ok(Self{
             initializer,
             mint_lp,
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
             config,
             token_program,
+            token_program_x,
+            token_program_y,
             system_program,
+            associated_token_program,
+            registry,
+            pair_record,
+            lp_metadata,
         })
 
     }
 }
 
-#[repr(C,packed)]
-pub struct InitializeInstructionData {
-    pub seed: u64,
-    pub fee: u16,
-    pub mint_x: [u8; 32],
-    pub mint_y: [u8; 32],
-    pub config_bump: [u8;1],
-    pub lp_bump: [u8;1],
-    pub authority: [u8;32]
-}
 impl TryFrom<&[u8]> for InitializeInstructionData {
     type Error = ProgramError;
     fn try_from(data: &[u8]) -> Result<Self, Self::Error>{
-        const INITIALIZE_DATA_LEN_WITH_AUTHORITY: usize = size_of::<InitializeInstructionData>();
-        const INITIALIZE_DATA_LEN: usize =
-            INITIALIZE_DATA_LEN_WITH_AUTHORITY - size_of::<[u8;32]>();
+        const INITIALIZE_DATA_LEN_WITH_CURVE: usize = InitializeInstructionData::LEN;
+        const INITIALIZE_DATA_LEN_WITH_AUTHORITY: usize = InitializeInstructionData::LEN_WITH_AUTHORITY;
+        const INITIALIZE_DATA_LEN: usize = InitializeInstructionData::LEN_WITHOUT_AUTHORITY;
 
         match data.len() {
-            INITIALIZE_DATA_LEN_WITH_AUTHORITY => {
+            INITIALIZE_DATA_LEN_WITH_CURVE => {
                 Ok(unsafe{ (data.as_ptr() as *const Self).read_unaligned() })
             }
-            INITIALIZE_DATA_LEN => {
-                let mut raw: MaybeUninit<[u8;INITIALIZE_DATA_LEN_WITH_AUTHORITY]> = MaybeUninit::uninit();
+            len @ (INITIALIZE_DATA_LEN | INITIALIZE_DATA_LEN_WITH_AUTHORITY) => {
+                let mut raw: MaybeUninit<[u8;INITIALIZE_DATA_LEN_WITH_CURVE]> = MaybeUninit::uninit();
                 let raw_ptr = raw.as_mut_ptr() as *mut u8;
                 unsafe {
-                    core::ptr::copy_nonoverlapping(data.as_ptr(), raw_ptr, INITIALIZE_DATA_LEN);
-                    core::ptr::write_bytes(raw_ptr.add(INITIALIZE_DATA_LEN),0,32);
+                    core::ptr::copy_nonoverlapping(data.as_ptr(), raw_ptr, len);
+                    core::ptr::write_bytes(raw_ptr.add(len),0,INITIALIZE_DATA_LEN_WITH_CURVE - len);
                     Ok((raw.as_ptr() as *const Self).read_unaligned())
                 }
             }
@@ -92,53 +167,81 @@ impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for Initialize<'info> {
 }
 
 impl<'info> Initialize<'info> {
-    pub const DISCRIMINATOR: &'info u8 = &0;
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::INITIALIZE;
 
     pub fn process(&self) -> ProgramResult{
 
-        //使用CreateAccount 和种子创建Config账户
         let instruction_data = &self.initialize_instruction_data;
         let accounts = &self.initialize_accounts;
         let rent = Rent::get()?;
 
+        //1. 链上派生 Config 和 LP mint 的 PDA，不信任客户端提供的 bump
+        let seed_binding = instruction_data.seed.to_le_bytes();
+        let (config_address, config_bump) = Address::find_program_address(
+            &[
+                CONFIG_SEED,
+                &seed_binding,
+                accounts.mint_x.address().as_ref(),
+                accounts.mint_y.address().as_ref(),
+            ],
+            &crate::ID,
+        );
+        if config_address.ne(accounts.config.address()) {
+            return Err(AmmError::InvalidAddress.into());
+        }
+
+        let (mint_lp_address, lp_bump) = Address::find_program_address(
+            &[MINT_LP_SEED, accounts.config.address().as_ref()],
+            &crate::ID,
+        );
+        if mint_lp_address.ne(accounts.mint_lp.address()) {
+            return Err(AmmError::InvalidLpMint.into());
+        }
+
+        //2. 使用CreateAccount 和种子创建Config账户
         let config_lamports = rent.try_minimum_balance(Config::LEN)?;
-        let seed_binding = self.initialize_instruction_data.seed.to_le_bytes();
-        let config_seeds = [
-            Seed::from(b"config"),
-            Seed::from(&seed_binding),
-            Seed::from(&instruction_data.mint_x),
-            Seed::from(&instruction_data.mint_y),
-            Seed::from(&instruction_data.config_bump),
-        ];
+        let config_signer = ConfigSigner::new(
+            instruction_data.seed,
+            accounts.mint_x.address(),
+            accounts.mint_y.address(),
+            [config_bump],
+        );
+        let config_seeds = config_signer.seeds();
 
-        let config_signer = Signer::from(&config_seeds);
         CreateAccount{
             from:accounts.initializer,
             to:accounts.config,
             lamports:config_lamports,
             space:Config::LEN as u64,
             owner:&crate::ID,
-        }.invoke_signed(&[config_signer])?;
+        }.invoke_signed(&[Signer::from(&config_seeds)])?;
 
-        //初始化Config数据
-
-        let config_account = unsafe { Config::load_mut_unchecked(accounts.config)? };
-        config_account.set_inner(
-            instruction_data.seed,
-            instruction_data.authority.into(), // 将 [u8;32] 转为 Pubkey
-            instruction_data.mint_x.into(),
-            instruction_data.mint_y.into(),
-            instruction_data.fee,
-            instruction_data.config_bump,
-        )?;
+        //3. 初始化Config数据
+        // 代码块结束时释放可变借用，之后的 CPI 需要读取 Config 账户
+        let amp = {
+            let mut config_account = Config::load_mut_unchecked(accounts.config)?;
+            config_account.set_inner(
+                instruction_data.seed,
+                instruction_data.authority.into(), // 将 [u8;32] 转为 Pubkey
+                *accounts.mint_x.address(),
+                *accounts.mint_y.address(),
+                instruction_data.fee,
+                [config_bump],
+            )?;
+            config_account.set_lp_bump([lp_bump]);
+            config_account.set_curve(instruction_data.curve_type, instruction_data.amp)?;
+            // 恒定乘积池子忽略指令中的 amp，事件中记录实际保存的值
+            config_account.amp_initial()
+        }; // ← config_account 在这里自动释放
 
+        //4. 创建Mint LP账户
+        let lp_bump_binding = [lp_bump];
         let mint_lp_seeds = [
-            Seed::from(b"mint_lp"),
+            Seed::from(MINT_LP_SEED),
             Seed::from(accounts.config.address().as_array()),
-            Seed::from(&instruction_data.lp_bump),
+            Seed::from(&lp_bump_binding),
         ];
 
-        //创建Mint LP账户
         let mint_space = size_of::<Mint>();
         let mint_lamports = rent.try_minimum_balance(mint_space)?;
 
@@ -150,14 +253,78 @@ impl<'info> Initialize<'info> {
             owner:&pinocchio_token::ID,
         }.invoke_signed(&[Signer::from(&mint_lp_seeds)])?; //Mint的所有者是Token Program
 
-        //初始化Mint LP
+        //5. 初始化Mint LP
         InitializeMint2{
             mint:accounts.mint_lp,
-            decimals:6,
+            decimals:instruction_data.lp_decimals,
             mint_authority:accounts.config.address(),
             freeze_authority:None,
         }.invoke()?;
 
+        //6. 创建 LP 元数据：名称和符号由两个 Mint 的符号决定，Config PDA 同时是 mint authority 和 update authority
+        if let Some((lp_metadata, mint_metadata)) = &accounts.lp_metadata {
+            CreateMetadataAccountV3 {
+                metadata: lp_metadata.metadata,
+                mint: accounts.mint_lp,
+                authority: accounts.config,
+                payer: accounts.initializer,
+                system_program: accounts.system_program,
+                metadata_program: lp_metadata.metadata_program,
+                name: mint_metadata.lp_token_name(accounts.mint_x.address(), accounts.mint_y.address())?,
+            }.invoke_signed(&[Signer::from(&config_seeds)])?;
+        }
+
+        //7. 创建两个金库：Config PDA 在 mint_x / mint_y 下的 ATA
+        // 金库可能已经被别人抢先创建，已存在时沿用并验证
+        AssociatedTokenAccount::init_idempotent(
+            accounts.vault_x,
+            accounts.mint_x,
+            accounts.initializer,
+            accounts.config,
+            accounts.system_program,
+            accounts.token_program_x,
+        )?;
+        VaultAccount::check_created(accounts.vault_x, accounts.config, accounts.mint_x.address(), accounts.token_program_x)?;
+
+        AssociatedTokenAccount::init_idempotent(
+            accounts.vault_y,
+            accounts.mint_y,
+            accounts.initializer,
+            accounts.config,
+            accounts.system_program,
+            accounts.token_program_y,
+        )?;
+        VaultAccount::check_created(accounts.vault_y, accounts.config, accounts.mint_y.address(), accounts.token_program_y)?;
+
+        emit(&InitializeEvent {
+            pool: accounts.config.address().to_bytes(),
+            initializer: accounts.initializer.address().to_bytes(),
+            authority: instruction_data.authority,
+            mint_x: accounts.mint_x.address().to_bytes(),
+            mint_y: accounts.mint_y.address().to_bytes(),
+            mint_lp: accounts.mint_lp.address().to_bytes(),
+            seed: instruction_data.seed,
+            fee: instruction_data.fee,
+            lp_decimals: instruction_data.lp_decimals,
+            curve_type: instruction_data.curve_type,
+            amp,
+        });
+
+        //8. 注册池子：每个池子都在交易对记录中，注册表要求时同一个手续费档位只能有一个池子
+        RegisterPoolAccounts {
+            payer: accounts.initializer,
+            registry: accounts.registry,
+            config: accounts.config,
+            pair_record: accounts.pair_record,
+            system_program: accounts.system_program,
+        }.register(
+            accounts.mint_x.address(),
+            accounts.mint_y.address(),
+            instruction_data.seed,
+            instruction_data.fee,
+            instruction_data.curve_type,
+        )?;
+
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,351 @@
+use pinocchio::{AccountView, Address, ProgramResult};
+use pinocchio::account::Ref;
+use pinocchio::cpi::{invoke_signed, Signer};
+use pinocchio::error::ProgramError;
+use pinocchio::instruction::{InstructionAccount, InstructionView};
+use super::admin::AdminAccounts;
+use super::helpers::{AccountCheck, MintLpAccount, SignerAccount};
+use crate::errors::AmmError;
+use crate::layout::discriminator;
+use crate::metadata::{
+    create_metadata_data, read_name_and_symbol, update_metadata_data, LpTokenName, MAX_INSTRUCTION_DATA_LEN,
+    MAX_URI_LEN, METADATA_PROGRAM_ID, METADATA_SEED,
+};
+
+// =============================================================================
+// LP 代币元数据账户
+// =============================================================================
+// Metadata 账户的地址由 Metaplex 程序自己校验，这里只需要保证 CPI 的目标是 Metaplex：
+// Config PDA 会作为签名者参与 CPI，不能把签名交给任意程序
+pub struct LpMetadataAccounts<'info> {
+    pub metadata: &'info AccountView,
+    pub metadata_program: &'info AccountView,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for LpMetadataAccounts<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [metadata, metadata_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if metadata_program.address().ne(&METADATA_PROGRAM_ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        Ok(Self {
+            metadata,
+            metadata_program,
+        })
+    }
+}
+
+// 交易对两个 Mint 的 Metaplex Metadata，用于确定 LP 代币的名称和符号
+// 地址必须是对应 Mint 的 Metadata PDA；账户不存在时这一侧使用 Mint 地址的 base58 前缀
+pub struct MintMetadataAccounts<'info> {
+    pub mint_x_metadata: &'info AccountView,
+    pub mint_y_metadata: &'info AccountView,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for MintMetadataAccounts<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [mint_x_metadata, mint_y_metadata] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            mint_x_metadata,
+            mint_y_metadata,
+        })
+    }
+}
+
+impl<'info> MintMetadataAccounts<'info> {
+    // 由两个 Mint 的符号生成 LP 代币的名称和符号
+    pub fn lp_token_name(&self, mint_x: &Address, mint_y: &Address) -> Result<LpTokenName, ProgramError> {
+        let data_x = Self::load(self.mint_x_metadata, mint_x)?;
+        let data_y = Self::load(self.mint_y_metadata, mint_y)?;
+        let symbol_x = data_x.as_deref().and_then(read_name_and_symbol).map(|(_, symbol)| symbol);
+        let symbol_y = data_y.as_deref().and_then(read_name_and_symbol).map(|(_, symbol)| symbol);
+
+        Ok(LpTokenName::new(mint_x, symbol_x, mint_y, symbol_y))
+    }
+
+    // 验证 Metadata 的地址，Mint 没有元数据时返回 None
+    fn load<'a>(metadata: &'a AccountView, mint: &Address) -> Result<Option<Ref<'a, [u8]>>, ProgramError> {
+        let (address, _) = Address::find_program_address(
+            &[METADATA_SEED, METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+            &METADATA_PROGRAM_ID,
+        );
+        if address.ne(metadata.address()) {
+            return Err(AmmError::InvalidAddress.into());
+        }
+        if !metadata.owned_by(&METADATA_PROGRAM_ID) {
+            return Ok(None);
+        }
+        Ok(Some(metadata.try_borrow()?))
+    }
+}
+
+// =============================================================================
+// Metaplex CPI
+// =============================================================================
+
+// 为 LP Mint 创建 Metadata，mint authority 和 update authority 都是 Config PDA
+pub struct CreateMetadataAccountV3<'a> {
+    pub metadata: &'a AccountView,
+    pub mint: &'a AccountView,
+    pub authority: &'a AccountView,
+    pub payer: &'a AccountView,
+    pub system_program: &'a AccountView,
+    pub metadata_program: &'a AccountView,
+    pub name: LpTokenName,
+}
+
+impl CreateMetadataAccountV3<'_> {
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable(self.metadata.address()),
+            InstructionAccount::readonly(self.mint.address()),
+            InstructionAccount::readonly_signer(self.authority.address()),
+            InstructionAccount::writable_signer(self.payer.address()),
+            InstructionAccount::readonly_signer(self.authority.address()),
+            InstructionAccount::readonly(self.system_program.address()),
+        ];
+
+        // 创建时 URI 为空，之后由池子的 authority 通过 UpdateLpMetadata 设置
+        let mut instruction_data = [0u8; MAX_INSTRUCTION_DATA_LEN];
+        let len = create_metadata_data(&self.name, "", &mut instruction_data)
+            .ok_or(ProgramError::InvalidInstructionData)?;
+
+        let instruction = InstructionView {
+            program_id: self.metadata_program.address(),
+            accounts: &instruction_accounts,
+            data: &instruction_data[..len],
+        };
+
+        invoke_signed(
+            &instruction,
+            &[
+                self.metadata,
+                self.mint,
+                self.authority,
+                self.payer,
+                self.authority,
+                self.system_program,
+            ],
+            signers,
+        )
+    }
+}
+
+// 更新 Metadata 的 URI，名称和符号保持不变
+pub struct UpdateMetadataAccountV2<'a> {
+    pub metadata: &'a AccountView,
+    pub update_authority: &'a AccountView,
+    pub metadata_program: &'a AccountView,
+    pub name: LpTokenName,
+    pub uri: &'a str,
+}
+
+impl UpdateMetadataAccountV2<'_> {
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable(self.metadata.address()),
+            InstructionAccount::readonly_signer(self.update_authority.address()),
+        ];
+
+        let mut instruction_data = [0u8; MAX_INSTRUCTION_DATA_LEN];
+        let len = update_metadata_data(&self.name, self.uri, &mut instruction_data)
+            .ok_or(AmmError::InvalidMetadataUri)?;
+
+        let instruction = InstructionView {
+            program_id: self.metadata_program.address(),
+            accounts: &instruction_accounts,
+            data: &instruction_data[..len],
+        };
+
+        invoke_signed(&instruction, &[self.metadata, self.update_authority], signers)
+    }
+}
+
+// =============================================================================
+// 更新 LP 代币元数据的 URI（仅限池子的 authority）
+// =============================================================================
+// 指令数据就是 UTF-8 编码的 URI，不超过 MAX_URI_LEN 字节
+pub struct UpdateLpMetadataInstructionData<'info> {
+    pub uri: &'info str,
+}
+
+impl<'info> TryFrom<&'info [u8]> for UpdateLpMetadataInstructionData<'info> {
+    type Error = ProgramError;
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        if data.len() > MAX_URI_LEN {
+            return Err(AmmError::InvalidMetadataUri.into());
+        }
+
+        let uri = core::str::from_utf8(data).map_err(|_| AmmError::InvalidMetadataUri)?;
+
+        Ok(Self { uri })
+    }
+}
+
+pub struct UpdateLpMetadataAccounts<'info> {
+    pub admin: AdminAccounts<'info>,
+    pub lp_metadata: LpMetadataAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for UpdateLpMetadataAccounts<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        // [authority, config, metadata, metadata_program]
+        if accounts.len() < 4 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let (admin, lp_metadata) = accounts.split_at(2);
+
+        Ok(Self {
+            admin: AdminAccounts::try_from(admin)?,
+            lp_metadata: LpMetadataAccounts::try_from(lp_metadata)?,
+        })
+    }
+}
+
+pub struct UpdateLpMetadata<'info> {
+    pub accounts: UpdateLpMetadataAccounts<'info>,
+    pub instruction_data: UpdateLpMetadataInstructionData<'info>,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for UpdateLpMetadata<'info> {
+    type Error = ProgramError;
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = UpdateLpMetadataAccounts::try_from(accounts)?;
+        let instruction_data = UpdateLpMetadataInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'info> UpdateLpMetadata<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::UPDATE_LP_METADATA;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        //1. 校验签名者是池子的 authority，不可变的池子拒绝修改
+        let config = accounts.admin.load_config()?;
+
+        //2. Config 作为 update authority 签名，需要先释放可写借用
+        let config_signer = config.signer();
+        drop(config);
+
+        //3. Metaplex 用新的 DataV2 整体替换，名称和符号沿用 Metadata 中已有的值
+        let name = {
+            let metadata = accounts.lp_metadata.metadata;
+            if !metadata.owned_by(&METADATA_PROGRAM_ID) {
+                return Err(ProgramError::UninitializedAccount);
+            }
+            LpTokenName::from_metadata(&metadata.try_borrow()?).ok_or(ProgramError::InvalidAccountData)?
+        };
+
+        let config_seeds = config_signer.seeds();
+
+        UpdateMetadataAccountV2 {
+            metadata: accounts.lp_metadata.metadata,
+            update_authority: accounts.admin.config,
+            metadata_program: accounts.lp_metadata.metadata_program,
+            name,
+            uri: self.instruction_data.uri,
+        }
+        .invoke_signed(&[Signer::from(&config_seeds)])
+    }
+}
+
+// =============================================================================
+// 为已有的池子创建 LP 代币元数据（仅限池子的 authority）
+// =============================================================================
+// 创建池子时没有传入元数据账户的池子，之后由 authority 补充创建
+// 名称和符号与 Initialize 相同，由两个 Mint 的符号决定；URI 为空，之后通过 UpdateLpMetadata 设置
+pub struct CreateLpMetadataAccounts<'info> {
+    // 池子的 authority，同时支付 Metadata 账户的租金
+    pub admin: AdminAccounts<'info>,
+    pub mint_lp: &'info AccountView,
+    pub lp_metadata: LpMetadataAccounts<'info>,
+    pub system_program: &'info AccountView,
+    pub mint_metadata: MintMetadataAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for CreateLpMetadataAccounts<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        // [authority, config, mint_lp, metadata, metadata_program, system_program, mint_x_metadata, mint_y_metadata]
+        let [authority, config, mint_lp, lp_metadata @ .., system_program, mint_x_metadata, mint_y_metadata] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+        MintLpAccount::check(mint_lp, config)?;
+        if system_program.address().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        Ok(Self {
+            admin: AdminAccounts { authority, config },
+            mint_lp,
+            lp_metadata: LpMetadataAccounts::try_from(lp_metadata)?,
+            system_program,
+            mint_metadata: MintMetadataAccounts {
+                mint_x_metadata,
+                mint_y_metadata,
+            },
+        })
+    }
+}
+
+pub struct CreateLpMetadata<'info> {
+    pub accounts: CreateLpMetadataAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for CreateLpMetadata<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = CreateLpMetadataAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'info> CreateLpMetadata<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::CREATE_LP_METADATA;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        //1. 校验签名者是池子的 authority，不可变的池子拒绝修改
+        let config = accounts.admin.load_config()?;
+        let mint_x = *config.mint_x();
+        let mint_y = *config.mint_y();
+
+        //2. Config 作为 mint authority 和 update authority 签名，需要先释放可写借用
+        let config_signer = config.signer();
+        drop(config);
+
+        let config_seeds = config_signer.seeds();
+
+        //3. Metadata 已存在时 Metaplex 会拒绝创建
+        CreateMetadataAccountV3 {
+            metadata: accounts.lp_metadata.metadata,
+            mint: accounts.mint_lp,
+            authority: accounts.admin.config,
+            payer: accounts.admin.authority,
+            system_program: accounts.system_program,
+            metadata_program: accounts.lp_metadata.metadata_program,
+            name: accounts.mint_metadata.lp_token_name(&mint_x, &mint_y)?,
+        }
+        .invoke_signed(&[Signer::from(&config_seeds)])
+    }
+}
@@ -0,0 +1,98 @@
+use pinocchio::{AccountView, Address, ProgramResult};
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::rent::Rent;
+use pinocchio::sysvars::Sysvar;
+use pinocchio_system::instructions::Transfer;
+use crate::Config;
+use crate::layout::{discriminator, MINT_LP_SEED};
+use super::helpers::{AccountCheck, SignerAccount};
+
+// =============================================================================
+// 将旧版本的 Config 账户迁移到当前布局
+// =============================================================================
+// 新字段都追加在旧布局之后，迁移只需要扩容账户并把新字段设置为默认值，
+// 不会改变池子的任何参数，因此任何人都可以支付租金执行
+pub struct MigrateConfigAccounts<'info> {
+    pub payer: &'info AccountView,
+    pub config: &'info AccountView,
+    pub system_program: &'info AccountView,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for MigrateConfigAccounts<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [payer, config, system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(payer)?;
+        if system_program.address().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if !config.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        // 已经是当前版本的账户不需要迁移
+        if Config::stored_version(config)? == Config::VERSION {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // 版本 0 没有版本字节，长度相同的其他账户（如交易对记录）不能被当作 Config 迁移
+        Config::check_address(config)?;
+
+        Ok(Self {
+            payer,
+            config,
+            system_program,
+        })
+    }
+}
+
+pub struct MigrateConfig<'info> {
+    pub accounts: MigrateConfigAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for MigrateConfig<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = MigrateConfigAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'info> MigrateConfig<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::MIGRATE_CONFIG;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+        let version = Config::stored_version(accounts.config)?;
+
+        //1. 补足扩容后需要的租金
+        let rent_exemption = Rent::get()?.try_minimum_balance(Config::LEN)?;
+        if rent_exemption > accounts.config.lamports() {
+            Transfer {
+                from: accounts.payer,
+                to: accounts.config,
+                lamports: rent_exemption - accounts.config.lamports(),
+            }.invoke()?;
+        }
+
+        //2. 扩容到当前布局的长度
+        accounts.config.resize(Config::LEN)?;
+
+        //3. 新字段设置为默认值并写入版本号
+        let mut config = Config::load_mut_unchecked(accounts.config)?;
+        config.init_fields_since(version);
+
+        //4. 版本 0 的账户没有保存 lp_bump，按 [MINT_LP_SEED, config] 重新派生
+        if version < 1 {
+            let (_, lp_bump) = Address::find_program_address(
+                &[MINT_LP_SEED, accounts.config.address().as_ref()],
+                &crate::ID,
+            );
+            config.set_lp_bump([lp_bump]);
+        }
+
+        Ok(())
+    }
+}
@@ -3,8 +3,31 @@ mod initialize;
 mod swap;
 mod withdraw;
 mod helpers;
+mod admin;
+mod update_fee;
+mod authority;
+mod token_interface;
+mod zap;
+mod protocol_fee;
+mod migrate;
+mod route;
+mod amp;
+mod lp_metadata;
+mod flash_loan;
+mod registry;
 
 pub use deposit::*;
 pub use swap::*;
 pub use withdraw::*;
-pub use initialize::*;
\ No newline at end of file
+pub use initialize::*;
+pub use admin::*;
+pub use update_fee::*;
+pub use authority::*;
+pub use zap::*;
+pub use protocol_fee::*;
+pub use migrate::*;
+pub use route::*;
+pub use amp::*;
+pub use lp_metadata::*;
+pub use flash_loan::*;
+pub use registry::*;
@@ -0,0 +1,199 @@
+use pinocchio::{AccountView, Address, ProgramResult};
+use pinocchio::cpi::Signer;
+use pinocchio::error::ProgramError;
+use crate::Config;
+use crate::errors::AmmError;
+use crate::events::{FeeUpdatedEvent, ProtocolFeesCollectedEvent};
+use crate::layout::discriminator;
+use super::admin::AdminAccounts;
+use super::helpers::{emit, AccountCheck, AssociatedTokenAccount, PoolSide, SignerAccount};
+use super::token_interface::{mint_decimals, TransferChecked};
+
+// =============================================================================
+// 设置协议费比例和接收者
+// =============================================================================
+pub struct SetProtocolFeeInstructionData {
+    // 协议费占交换手续费的比例（bps）
+    pub protocol_fee: u16,
+    // 协议费接收者，全 0 表示不设置接收者
+    pub fee_recipient: Address,
+}
+
+impl<'info> TryFrom<&'info [u8]> for SetProtocolFeeInstructionData {
+    type Error = ProgramError;
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        if data.len() < size_of::<u16>() + size_of::<Address>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let protocol_fee = u16::from_le_bytes(data[0..2].try_into().unwrap());
+        let fee_recipient = Address::new_from_array(data[2..34].try_into().unwrap());
+
+        Ok(Self {
+            protocol_fee,
+            fee_recipient,
+        })
+    }
+}
+
+pub struct SetProtocolFee<'info> {
+    pub accounts: AdminAccounts<'info>,
+    pub instruction_data: SetProtocolFeeInstructionData,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for SetProtocolFee<'info> {
+    type Error = ProgramError;
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = AdminAccounts::try_from(accounts)?;
+        let instruction_data = SetProtocolFeeInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'info> SetProtocolFee<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::SET_PROTOCOL_FEE;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let data = &self.instruction_data;
+
+        // 收取协议费必须有接收者，否则累计的协议费无法领取
+        if data.protocol_fee > 0 && data.fee_recipient == Address::default() {
+            return Err(AmmError::NoFeeRecipient.into());
+        }
+
+        let mut config = self.accounts.load_config()?;
+        // set_protocol_fee 会拒绝 > 10_000 bps 的比例
+        config.set_protocol_fee(data.protocol_fee)?;
+        config.set_fee_recipient(data.fee_recipient);
+
+        emit(&FeeUpdatedEvent {
+            pool: self.accounts.config.address().to_bytes(),
+            authority: self.accounts.authority.address().to_bytes(),
+            fee: u16::from_le_bytes(*config.fee()),
+            protocol_fee: data.protocol_fee,
+            fee_recipient: data.fee_recipient.to_bytes(),
+        });
+        Ok(())
+    }
+}
+
+// =============================================================================
+// 领取协议费：authority 把累计的协议费从金库转给接收者
+// =============================================================================
+pub struct CollectProtocolFeesAccounts<'info> {
+    pub admin: AdminAccounts<'info>,
+    pub mint_x: &'info AccountView,
+    pub mint_y: &'info AccountView,
+    pub vault_x: &'info AccountView,
+    pub vault_y: &'info AccountView,
+    pub recipient_x_ata: &'info AccountView,
+    pub recipient_y_ata: &'info AccountView,
+    pub token_program_x: &'info AccountView,
+    pub token_program_y: &'info AccountView,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for CollectProtocolFeesAccounts<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config, mint_x, mint_y, vault_x, vault_y, recipient_x_ata, recipient_y_ata, token_program_x, token_program_y] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+        {
+            let config_data = Config::load(config)?;
+            PoolSide::check(config_data.mint_x(), mint_x, vault_x, recipient_x_ata, config, token_program_x)?;
+            PoolSide::check(config_data.mint_y(), mint_y, vault_y, recipient_y_ata, config, token_program_y)?;
+
+            // 协议费只能转入接收者的 ATA
+            let fee_recipient = config_data
+                .has_fee_recipient()
+                .ok_or(AmmError::NoFeeRecipient)?;
+            AssociatedTokenAccount::check_address(recipient_x_ata, &fee_recipient, mint_x.address(), token_program_x.address())?;
+            AssociatedTokenAccount::check_address(recipient_y_ata, &fee_recipient, mint_y.address(), token_program_y.address())?;
+        }
+
+        Ok(Self {
+            admin: AdminAccounts { authority, config },
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            recipient_x_ata,
+            recipient_y_ata,
+            token_program_x,
+            token_program_y,
+        })
+    }
+}
+
+pub struct CollectProtocolFees<'info> {
+    pub accounts: CollectProtocolFeesAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for CollectProtocolFees<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = CollectProtocolFeesAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'info> CollectProtocolFees<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::COLLECT_PROTOCOL_FEES;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        //1. 校验 authority，取出累计的协议费并清零
+        let mut config = accounts.admin.load_config()?;
+        let (fees_x, fees_y) = config.take_protocol_fees();
+        let fee_recipient = config.fee_recipient().to_bytes();
+
+        //2. 准备签名种子，Config 作为签名者参与 CPI，转出前需要释放可写借用
+        let config_signer = config.signer();
+        drop(config);
+
+        let config_seeds = config_signer.seeds();
+        let signer = Signer::from(&config_seeds);
+
+        //3. 从金库转给接收者
+        if fees_x > 0 {
+            TransferChecked {
+                from: accounts.vault_x,
+                mint: accounts.mint_x,
+                to: accounts.recipient_x_ata,
+                authority: accounts.admin.config,
+                amount: fees_x,
+                decimals: mint_decimals(accounts.mint_x)?,
+                token_program: accounts.token_program_x.address(),
+            }.invoke_signed(core::slice::from_ref(&signer))?;
+        }
+
+        if fees_y > 0 {
+            TransferChecked {
+                from: accounts.vault_y,
+                mint: accounts.mint_y,
+                to: accounts.recipient_y_ata,
+                authority: accounts.admin.config,
+                amount: fees_y,
+                decimals: mint_decimals(accounts.mint_y)?,
+                token_program: accounts.token_program_y.address(),
+            }.invoke_signed(core::slice::from_ref(&signer))?;
+        }
+
+        emit(&ProtocolFeesCollectedEvent {
+            pool: accounts.admin.config.address().to_bytes(),
+            authority: accounts.admin.authority.address().to_bytes(),
+            fee_recipient,
+            amount_x: fees_x,
+            amount_y: fees_y,
+        });
+        Ok(())
+    }
+}
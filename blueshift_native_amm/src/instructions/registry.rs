@@ -0,0 +1,438 @@
+use pinocchio::{AccountView, Address, ProgramResult};
+use pinocchio::cpi::Seed;
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::rent::Rent;
+use pinocchio::sysvars::Sysvar;
+use pinocchio_system::instructions::Transfer;
+use crate::{Config, PairRecord, Registry};
+use crate::errors::AmmError;
+use crate::events::PoolRegisteredEvent;
+use crate::layout::{discriminator, is_canonical, BPF_LOADER_UPGRADEABLE_ID, PAIR_SEED, REGISTRY_SEED};
+pub use crate::layout::InitializeRegistryInstructionData;
+use super::helpers::{emit, AccountCheck, ProgramAccount, ProgramAccountInit, SignerAccount};
+
+// =============================================================================
+// 创建注册表
+// =============================================================================
+// 注册表全局只有一个，只有程序的升级权限可以创建，由它指定 authority
+// 部署程序后、创建第一个池子之前调用
+pub struct InitializeRegistryAccounts<'info> {
+    // 程序的升级权限，同时支付租金
+    pub payer: &'info AccountView,
+    pub registry: &'info AccountView,
+    // 本程序的 ProgramData，保存升级权限
+    pub program_data: &'info AccountView,
+    pub system_program: &'info AccountView,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for InitializeRegistryAccounts<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [payer, registry, program_data, system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(payer)?;
+        if system_program.address().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        // 否则任何人都可以抢先创建注册表并永久控制注册规则
+        check_upgrade_authority(program_data, payer)?;
+
+        Ok(Self {
+            payer,
+            registry,
+            program_data,
+            system_program,
+        })
+    }
+}
+
+// ProgramData 账户是 bincode 编码的 UpgradeableLoaderState::ProgramData：
+// [tag: u32 = 3][slot: u64][upgrade_authority: Option<Address>][程序代码]
+const PROGRAM_DATA_TAG: u32 = 3;
+const PROGRAM_DATA_AUTHORITY_OFFSET: usize = size_of::<u32>() + size_of::<u64>();
+
+// 验证 authority 是本程序当前的升级权限
+// 已经放弃升级权限的程序没有人能通过检查
+fn check_upgrade_authority(program_data: &AccountView, authority: &AccountView) -> ProgramResult {
+    let (program_data_address, _) =
+        Address::find_program_address(&[crate::ID.as_ref()], &BPF_LOADER_UPGRADEABLE_ID);
+    if program_data_address.ne(program_data.address()) {
+        return Err(AmmError::InvalidAddress.into());
+    }
+    if !program_data.owned_by(&BPF_LOADER_UPGRADEABLE_ID) {
+        return Err(AmmError::InvalidOwner.into());
+    }
+
+    let data = program_data.try_borrow()?;
+    let authority_end = PROGRAM_DATA_AUTHORITY_OFFSET + 1 + size_of::<Address>();
+    if data.len() < authority_end
+        || u32::from_le_bytes(data[0..4].try_into().unwrap()) != PROGRAM_DATA_TAG
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if data[PROGRAM_DATA_AUTHORITY_OFFSET] != 1
+        || data[PROGRAM_DATA_AUTHORITY_OFFSET + 1..authority_end].ne(authority.address().as_ref())
+    {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+    Ok(())
+}
+
+impl TryFrom<&[u8]> for InitializeRegistryInstructionData {
+    type Error = ProgramError;
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            authority: data[0..32].try_into().unwrap(),
+            one_pool_per_fee_tier: data[32] != 0,
+        })
+    }
+}
+
+pub struct InitializeRegistry<'info> {
+    pub accounts: InitializeRegistryAccounts<'info>,
+    pub instruction_data: InitializeRegistryInstructionData,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for InitializeRegistry<'info> {
+    type Error = ProgramError;
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = InitializeRegistryAccounts::try_from(accounts)?;
+        let instruction_data = InitializeRegistryInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'info> InitializeRegistry<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::INITIALIZE_REGISTRY;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+        let data = self.instruction_data;
+
+        //1. 链上派生注册表的 PDA，账户已存在时 CreateAccount 会失败
+        let (registry_address, bump) = Address::find_program_address(&[REGISTRY_SEED], &crate::ID);
+        if registry_address.ne(accounts.registry.address()) {
+            return Err(AmmError::InvalidAddress.into());
+        }
+
+        let bump_binding = [bump];
+        let seeds = [Seed::from(REGISTRY_SEED), Seed::from(&bump_binding)];
+        ProgramAccount::init::<Registry>(accounts.payer, accounts.registry, &seeds, Registry::LEN)?;
+
+        //2. 初始化注册表
+        let mut registry = Registry::load_mut(accounts.registry)?;
+        registry.set_inner(data.authority.into(), data.one_pool_per_fee_tier, bump_binding);
+
+        Ok(())
+    }
+}
+
+// =============================================================================
+// 修改注册规则：是否限制每个手续费档位只有一个池子
+// =============================================================================
+// 只影响之后的注册，已经注册的池子不受影响
+pub struct UpdateRegistryAccounts<'info> {
+    pub authority: &'info AccountView,
+    pub registry: &'info AccountView,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for UpdateRegistryAccounts<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, registry] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        Ok(Self {
+            authority,
+            registry,
+        })
+    }
+}
+
+pub struct UpdateRegistryInstructionData {
+    pub one_pool_per_fee_tier: bool,
+}
+
+impl<'info> TryFrom<&'info [u8]> for UpdateRegistryInstructionData {
+    type Error = ProgramError;
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        let [one_pool_per_fee_tier, ..] = data else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        Ok(Self {
+            one_pool_per_fee_tier: *one_pool_per_fee_tier != 0,
+        })
+    }
+}
+
+pub struct UpdateRegistry<'info> {
+    pub accounts: UpdateRegistryAccounts<'info>,
+    pub instruction_data: UpdateRegistryInstructionData,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for UpdateRegistry<'info> {
+    type Error = ProgramError;
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = UpdateRegistryAccounts::try_from(accounts)?;
+        let instruction_data = UpdateRegistryInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'info> UpdateRegistry<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::UPDATE_REGISTRY;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let mut registry = Registry::load_mut(self.accounts.registry)?;
+        if registry.authority().ne(self.accounts.authority.address()) {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+
+        registry.set_one_pool_per_fee_tier(self.instruction_data.one_pool_per_fee_tier);
+
+        Ok(())
+    }
+}
+
+// =============================================================================
+// 注册池子
+// =============================================================================
+// Initialize 创建池子时通过 RegisterPoolAccounts::register 自动注册，
+// RegisterPool 用于把注册表创建之前的池子加入它的交易对记录，任何人都可以支付租金调用
+// 池子的参数直接从 Config 中读取，注册不会改变池子本身
+//
+// 1. 交易对记录按规范顺序保存两个 mint；注册表创建之前的池子可能不是规范顺序，注册时交换
+// 2. 交易对的第一个池子创建 PairRecord，之后每个池子把 PairRecord 扩容一个 PoolEntry
+// 3. 同一个池子只能注册一次；注册表要求时，同一个手续费档位只能有一个池子
+pub struct RegisterPoolAccounts<'info> {
+    pub payer: &'info AccountView,
+    pub registry: &'info AccountView,
+    pub config: &'info AccountView,
+    pub pair_record: &'info AccountView,
+    pub system_program: &'info AccountView,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for RegisterPoolAccounts<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [payer, registry, config, pair_record, system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(payer)?;
+        if system_program.address().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        Ok(Self {
+            payer,
+            registry,
+            config,
+            pair_record,
+            system_program,
+        })
+    }
+}
+
+impl<'info> RegisterPoolAccounts<'info> {
+    // 把 config 以给定的参数加入 [mint_x, mint_y] 的交易对记录，两个 mint 的顺序不限
+    pub fn register(
+        &self,
+        mint_x: &Address,
+        mint_y: &Address,
+        seed: u64,
+        fee_tier: u16,
+        curve_type: u8,
+    ) -> ProgramResult {
+        let mut registry = Registry::load_mut(self.registry)?;
+
+        let (mint_x, mint_y) = if is_canonical(mint_x, mint_y) {
+            (mint_x, mint_y)
+        } else {
+            (mint_y, mint_x)
+        };
+
+        //1. 交易对的第一个池子：创建只有头部的 PairRecord
+        // 按所有者判断是否已经创建，别人预先转入的 lamports 不影响创建
+        let new_pair = !self.pair_record.owned_by(&crate::ID);
+        if new_pair {
+            let (pair_address, bump) = Address::find_program_address(
+                &[PAIR_SEED, mint_x.as_ref(), mint_y.as_ref()],
+                &crate::ID,
+            );
+            if pair_address.ne(self.pair_record.address()) {
+                return Err(AmmError::InvalidAddress.into());
+            }
+
+            let bump_binding = [bump];
+            let seeds = [
+                Seed::from(PAIR_SEED),
+                Seed::from(mint_x.as_ref()),
+                Seed::from(mint_y.as_ref()),
+                Seed::from(&bump_binding),
+            ];
+            ProgramAccount::init::<PairRecord>(self.payer, self.pair_record, &seeds, PairRecord::LEN)?;
+
+            let mut data = self.pair_record.try_borrow_mut()?;
+            let (pair, _) = unsafe { PairRecord::from_bytes_unchecked_mut(&mut data) };
+            pair.set_inner(*mint_x, *mint_y, bump_binding);
+        }
+
+        //2. 检查重复注册和手续费档位
+        let pools = {
+            let data = PairRecord::load_mut(self.pair_record)?;
+            let (pair, pools) = unsafe { PairRecord::from_bytes_unchecked(&data) };
+            if pair.mint_x().ne(mint_x) || pair.mint_y().ne(mint_y) {
+                return Err(AmmError::InvalidAddress.into());
+            }
+
+            for pool in pools {
+                if pool.config().eq(self.config.address()) {
+                    return Err(AmmError::PoolAlreadyRegistered.into());
+                }
+                if registry.one_pool_per_fee_tier() && pool.fee_tier() == fee_tier {
+                    return Err(AmmError::FeeTierTaken.into());
+                }
+            }
+            pools.len()
+        };
+
+        //3. 扩容一个 PoolEntry，补足租金后写入池子参数
+        let len = PairRecord::len_with(pools + 1);
+        let rent_exemption = Rent::get()?.try_minimum_balance(len)?;
+        if rent_exemption > self.pair_record.lamports() {
+            Transfer {
+                from: self.payer,
+                to: self.pair_record,
+                lamports: rent_exemption - self.pair_record.lamports(),
+            }.invoke()?;
+        }
+        self.pair_record.resize(len)?;
+
+        {
+            let mut data = PairRecord::load_mut(self.pair_record)?;
+            let (_, entries) = unsafe { PairRecord::from_bytes_unchecked_mut(&mut data) };
+            entries[pools].set_inner(*self.config.address(), seed, fee_tier, curve_type);
+        }
+        registry.add_pool(new_pair)?;
+
+        emit(&PoolRegisteredEvent {
+            pool: self.config.address().to_bytes(),
+            pair: self.pair_record.address().to_bytes(),
+            mint_x: mint_x.to_bytes(),
+            mint_y: mint_y.to_bytes(),
+            seed,
+            fee_tier,
+            curve_type,
+            index: pools as u32,
+        });
+
+        Ok(())
+    }
+}
+
+pub struct RegisterPool<'info> {
+    pub accounts: RegisterPoolAccounts<'info>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for RegisterPool<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = RegisterPoolAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'info> RegisterPool<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::REGISTER_POOL;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 读取池子参数，只有程序创建的当前版本 Config 能通过 load
+        let config = Config::load(self.accounts.config)?;
+        let mint_x = *config.mint_x();
+        let mint_y = *config.mint_y();
+        let seed = config.seed();
+        let fee_tier = u16::from_le_bytes(*config.fee());
+        let curve_type = config.curve_type();
+        drop(config);
+
+        self.accounts.register(&mint_x, &mint_y, seed, fee_tier, curve_type)
+    }
+}
+
+// =============================================================================
+// 同步手续费档位
+// =============================================================================
+// UpdateFee 修改池子的手续费后调用，使 PoolEntry 中的 fee_tier 与 Config 保持一致
+//
+// 池子没有注册时（注册表创建之前的池子，或交易对记录还不存在）不需要同步，
+// 但交易对记录的地址仍然必须是这个交易对的 PDA，防止传入其他账户跳过同步
+pub fn sync_fee_tier(
+    registry: &AccountView,
+    pair_record: &AccountView,
+    config: &AccountView,
+    mint_x: &Address,
+    mint_y: &Address,
+    fee_tier: u16,
+) -> ProgramResult {
+    let (mint_x, mint_y) = if is_canonical(mint_x, mint_y) {
+        (mint_x, mint_y)
+    } else {
+        (mint_y, mint_x)
+    };
+
+    //1. 交易对记录还不存在（可能已经被转入 lamports）：验证地址后直接返回
+    if !pair_record.owned_by(&crate::ID) {
+        let (pair_address, _) = Address::find_program_address(
+            &[PAIR_SEED, mint_x.as_ref(), mint_y.as_ref()],
+            &crate::ID,
+        );
+        if pair_address.ne(pair_record.address()) {
+            return Err(AmmError::InvalidAddress.into());
+        }
+        return Ok(());
+    }
+
+    //2. 在交易对记录中找到这个池子，检查新的档位后更新
+    let mut data = PairRecord::load_mut(pair_record)?;
+    let (pair, entries) = unsafe { PairRecord::from_bytes_unchecked_mut(&mut data) };
+    if pair.mint_x().ne(mint_x) || pair.mint_y().ne(mint_y) {
+        return Err(AmmError::InvalidAddress.into());
+    }
+
+    let Some(index) = entries.iter().position(|entry| entry.config().eq(config.address())) else {
+        return Ok(());
+    };
+
+    let registry = Registry::load(registry)?;
+    if registry.one_pool_per_fee_tier()
+        && entries
+            .iter()
+            .enumerate()
+            .any(|(i, entry)| i != index && entry.fee_tier() == fee_tier)
+    {
+        return Err(AmmError::FeeTierTaken.into());
+    }
+    entries[index].set_fee_tier(fee_tier);
+
+    Ok(())
+}
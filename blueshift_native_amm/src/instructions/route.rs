@@ -0,0 +1,148 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::error::ProgramError;
+use super::helpers::{AccountCheck, SignerAccount};
+use super::swap::{SwapAccounts, SwapMode};
+use super::token_interface::token_account_amount;
+use crate::errors::AmmError;
+use crate::layout::discriminator;
+
+// =============================================================================
+// 多跳交换："X -> Y -> Z，转入 amount_in 个 X，最终至少收到 min_out 个 Z"
+// =============================================================================
+// 账户：[user, 池子 1 的账户组, 池子 2 的账户组, ...]
+// 每个账户组与 Swap 去掉 user 后的顺序相同：
+// [mint_x, mint_y, user_x_ata, user_y_ata, vault_x, vault_y, config, token_program_x, token_program_y]
+//
+// 每一跳都按 Swap 的逻辑执行，上一跳实际到账的数量作为下一跳的输入，
+// 中间跳不做滑点检查，只检查最终收到的数量，整个路径在同一条指令中原子执行
+pub struct RouteSwapAccounts<'info> {
+    pub user: &'info AccountView,
+    pub pools: &'info [AccountView],
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for RouteSwapAccounts<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [user, pools @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(user)?;
+        if pools.is_empty() || pools.len() % SwapAccounts::POOL_ACCOUNTS_LEN != 0 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        Ok(Self { user, pools })
+    }
+}
+
+// 数据布局：[amount_in: u64][min_out: u64][expirations: i64][hops: u8][is_x: u8; hops]
+// is_x 与 Swap 相同，表示该跳是否以池子的 X 作为输入
+pub struct RouteSwapInstructionData<'info> {
+    pub amount_in: u64,
+    pub min_out: u64,
+    pub expirations: i64,
+    pub path: &'info [u8],
+}
+
+impl<'info> TryFrom<&'info [u8]> for RouteSwapInstructionData<'info> {
+    type Error = ProgramError;
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        const HEADER_LEN: usize = 25;
+        if data.len() < HEADER_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let amount_in = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let min_out = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let expirations = i64::from_le_bytes(data[16..24].try_into().unwrap());
+        let hops = data[24] as usize;
+
+        let path = data
+            .get(HEADER_LEN..HEADER_LEN + hops)
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        if hops == 0 || path.iter().any(|is_x| *is_x > 1) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            amount_in,
+            min_out,
+            expirations,
+            path,
+        })
+    }
+}
+
+pub struct RouteSwap<'info> {
+    pub accounts: RouteSwapAccounts<'info>,
+    pub instruction_data: RouteSwapInstructionData<'info>,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for RouteSwap<'info> {
+    type Error = ProgramError;
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = RouteSwapAccounts::try_from(accounts)?;
+        let instruction_data = RouteSwapInstructionData::try_from(data)?;
+
+        // 每一跳对应一个池子账户组
+        if accounts.pools.len() != instruction_data.path.len() * SwapAccounts::POOL_ACCOUNTS_LEN {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'info> RouteSwap<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::ROUTE_SWAP;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let data = &self.instruction_data;
+        let pools = self.accounts.pools.chunks_exact(SwapAccounts::POOL_ACCOUNTS_LEN);
+
+        let mut amount = data.amount_in;
+        let mut previous_out: Option<&AccountView> = None;
+
+        for (pool, is_x) in pools.zip(data.path.iter().map(|is_x| *is_x == 1)) {
+            //1. 逐跳验证账户，与 Swap 相同
+            let hop = SwapAccounts::from_pool(self.accounts.user, pool)?;
+
+            //2. 上一跳收到代币的账户必须是这一跳转出代币的账户，保证路径首尾相接
+            let user_in = hop.user_in(is_x);
+            if let Some(previous_out) = previous_out {
+                if previous_out.address().ne(user_in.address()) {
+                    return Err(AmmError::InvalidRoute.into());
+                }
+            }
+
+            //3. 执行这一跳，按用户实际到账的数量作为下一跳的输入
+            // 带 TransferFee 扩展的 Mint 到账数量会少于金库转出的数量
+            let user_out = hop.user_out(is_x);
+            let before = token_account_amount(user_out)?;
+            hop.execute(
+                is_x,
+                data.expirations,
+                SwapMode::ExactIn {
+                    amount_in: amount,
+                    min_out: 0,
+                },
+            )?;
+            amount = token_account_amount(user_out)?
+                .checked_sub(before)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+
+            previous_out = Some(user_out);
+        }
+
+        //4. 只检查最终收到的数量
+        if amount < data.min_out {
+            return Err(AmmError::SlippageExceeded.into());
+        }
+
+        Ok(())
+    }
+}
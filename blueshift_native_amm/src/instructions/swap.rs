@@ -1,35 +1,59 @@
-use constant_product_curve::{ConstantProduct, LiquidityPair};
 use pinocchio::{AccountView, ProgramResult};
-use pinocchio::cpi::{Seed, Signer};
+use pinocchio::cpi::Signer;
 use pinocchio::error::ProgramError;
 use pinocchio::sysvars::clock::Clock;
 use pinocchio::sysvars::Sysvar;
-use pinocchio_token::instructions::Transfer;
-use pinocchio_token::state::TokenAccount;
 use crate::Config;
+use crate::curve;
+use super::helpers::{emit, read_flag, AccountCheck, PoolSide, SignerAccount};
+use super::token_interface::{mint_decimals, token_account_amount, transfer_in, TransferChecked, TransferFee};
+use crate::errors::AmmError;
+use crate::events::SwapEvent;
+pub use crate::layout::{SwapExactOutInstructionData, SwapInstructionData};
+use crate::layout::discriminator;
 
 pub struct SwapAccounts<'info> {
     pub user: &'info AccountView,
+    pub mint_x: &'info AccountView,
+    pub mint_y: &'info AccountView,
     pub user_x_ata: &'info AccountView,
     pub user_y_ata: &'info AccountView,
     pub vault_x: &'info AccountView,
     pub vault_y: &'info AccountView,
     pub config: &'info AccountView,
-    pub token_program: &'info AccountView,
+    pub token_program_x: &'info AccountView,
+    pub token_program_y: &'info AccountView,
 }
 impl<'info> TryFrom<&'info [AccountView]> for SwapAccounts<'info> {
     type Error = ProgramError;
     fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [user, pool @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Self::from_pool(user, pool)
+    }
+}
+
+impl<'info> SwapAccounts<'info> {
+    // 一个池子需要的账户数量（不含 user）
+    pub const POOL_ACCOUNTS_LEN: usize = 9;
+
+    // 由签名者和一个池子的账户组构造，RouteSwap 的每一跳共用同一个 user
+    pub fn from_pool(user: &'info AccountView, pool: &'info [AccountView]) -> Result<Self, ProgramError> {
 
         // let mut iter = accounts.iter();
         // Ok(Self {
         //     user: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+        //     mint_x: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+        //     mint_y: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
         //     user_x_ata: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
         //     user_y_ata: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
         //     vault_x: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
         //     vault_y: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
         //     config: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
-        //     token_program: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+        //     token_program_x: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+        //     token_program_y: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
         // })
         // ═══════════════════════════════════════════════════════════════════════
         // 数组切片模式解析账户（推荐写法）
@@ -41,37 +65,45 @@ impl<'info> TryFrom<&'info [AccountView]> for SwapAccounts<'info> {
         // 4. 可读性强: 一眼看出账户结构，代码简洁清晰
         // 5. 易于维护: 添加/删除字段时，编译器会提醒更新模式匹配
         // ═══════════════════════════════════════════════════════════════════════
-        let [user, user_x_ata, user_y_ata, vault_x, vault_y, config, token_program] = accounts else {
+        let [mint_x, mint_y, user_x_ata, user_y_ata, vault_x, vault_y, config, token_program_x, token_program_y] = pool else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
+        SignerAccount::check(user)?;
+        {
+            let config_data = Config::load(config)?;
+            PoolSide::check(config_data.mint_x(), mint_x, vault_x, user_x_ata, config, token_program_x)?;
+            PoolSide::check(config_data.mint_y(), mint_y, vault_y, user_y_ata, config, token_program_y)?;
+        }
+
         Ok(Self {
             user,
+            mint_x,
+            mint_y,
             user_x_ata,
             user_y_ata,
             vault_x,
             vault_y,
             config,
-            token_program
+            token_program_x,
+            token_program_y,
         })
     }
 }
 
-#[repr(C,packed)]
-#[derive(Copy, Clone)]
-pub struct SwapInstructionData {
-    pub is_x: bool,
-    pub amount: u64,
-    pub min: u64,
-    pub expirations: i64,
-}
-impl<'info> TryFrom<&'info [u8]> for SwapInstructionData {
+// 逐字段读取：is_x 不是 0 或 1 时返回 InvalidInstructionData
+impl TryFrom<&[u8]> for SwapInstructionData {
     type Error = ProgramError;
     fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
         if data.len() < size_of::<Self>() {
             return Err(ProgramError::InvalidInstructionData);
         }
-        Ok(unsafe { *(data.as_ptr() as *const Self) })
+        Ok(Self {
+            is_x: read_flag(data[0])?,
+            amount: u64::from_le_bytes(data[1..9].try_into().unwrap()),
+            min: u64::from_le_bytes(data[9..17].try_into().unwrap()),
+            expirations: i64::from_le_bytes(data[17..25].try_into().unwrap()),
+        })
     }
 }
 
@@ -92,97 +124,245 @@ impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for Swap<'info> {
     }
 }
 impl<'info> Swap<'info> {
-    pub const DISCRIMINATOR: &'info u8 = &3;
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::SWAP;
     pub fn process(&mut self) -> ProgramResult {
-        let accounts = &mut self.accounts;
-        let data = &self.instruction_data;
+        let data = self.instruction_data;
+
+        self.accounts.execute(
+            data.is_x,
+            data.expirations,
+            SwapMode::ExactIn {
+                amount_in: data.amount,
+                min_out: data.min,
+            },
+        )?;
+
+        Ok(())
+    }
+}
+
+// =============================================================================
+// 精确输出交换："我要正好 amount_out 个 Y，最多花 max_in 个 X"
+// =============================================================================
+impl TryFrom<&[u8]> for SwapExactOutInstructionData {
+    type Error = ProgramError;
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(Self {
+            is_x: read_flag(data[0])?,
+            amount_out: u64::from_le_bytes(data[1..9].try_into().unwrap()),
+            max_in: u64::from_le_bytes(data[9..17].try_into().unwrap()),
+            expirations: i64::from_le_bytes(data[17..25].try_into().unwrap()),
+        })
+    }
+}
+
+pub struct SwapExactOut<'info> {
+    pub accounts: SwapAccounts<'info>,
+    pub instruction_data: SwapExactOutInstructionData,
+}
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for SwapExactOut<'info> {
+    type Error = ProgramError;
+    fn try_from((data,accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = SwapAccounts::try_from(accounts)?;
+        let instruction_data = SwapExactOutInstructionData::try_from(data)?;
+
+        Ok(Self{
+            accounts,
+            instruction_data,
+        })
+    }
+}
+impl<'info> SwapExactOut<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::SWAP_EXACT_OUT;
+    pub fn process(&mut self) -> ProgramResult {
+        let data = self.instruction_data;
+
+        self.accounts.execute(
+            data.is_x,
+            data.expirations,
+            SwapMode::ExactOut {
+                amount_out: data.amount_out,
+                max_in: data.max_in,
+            },
+        )?;
+
+        Ok(())
+    }
+}
+
+// 交换数量的指定方式及其滑点限制
+// 用户"收到"的数量是扣除输出代币 TransferFee 之后实际到账的数量
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SwapMode {
+    // 精确输入：转入 amount_in，至少收到 min_out
+    ExactIn { amount_in: u64, min_out: u64 },
+    // 精确输出：收到 amount_out，最多转入 max_in
+    ExactOut { amount_out: u64, max_in: u64 },
+}
+
+impl<'info> SwapAccounts<'info> {
+    // 两种交换模式共用：过期、池子状态和手续费检查，以及金库签名转出
+    // 返回从金库转出的数量
+    pub fn execute(&self, is_x: bool, expirations: i64, mode: SwapMode) -> Result<u64, ProgramError> {
+        let accounts = self;
 
         //1. 验证过期时间
-        let colck = Clock::get()?;
-        if colck.unix_timestamp > data.expirations {
-            return Err(ProgramError::InvalidArgument);
+        let clock = Clock::get()?;
+        if clock.unix_timestamp > expirations {
+            return Err(AmmError::Expired.into());
         };
 
-        // 2. 加载配置和状态
-        let config = Config::load(accounts.config)?;
-        if config.state() != 1 {
-            //必须是Initialized
-            return Err(ProgramError::InvalidAccountData);
-        };
+        // 2. 加载配置和状态，交换会累计协议费并更新 TWAP，因此以可写方式加载
+        let mut config = Config::load_mut(accounts.config)?;
+        //必须是Initialized
+        config.check_active()?;
+
+        // 3.获取金库当前余额（转入之前的储备量）
+        // 金库中尚未领取的协议费不属于 LP，不计入储备
+        let (reserve_x, reserve_y) = config.reserves(
+            token_account_amount(accounts.vault_x)?,
+            token_account_amount(accounts.vault_y)?,
+        )?;
 
-        // 3.获取金库当前余额并计算交换
-        let vault_x = unsafe{ TokenAccount::from_account_view_unchecked(accounts.vault_x)?};
-        let vault_y = unsafe{ TokenAccount::from_account_view_unchecked(accounts.vault_y)?};
+        // 储备变化之前，用变化前的储备推进 TWAP 累计价格
+        config.update_oracle(reserve_x, reserve_y, clock.unix_timestamp)?;
 
         // 将 fee 从 [u8; 2]（小端序）转换为 u16
         let fee = u16::from_le_bytes(*config.fee());
+        // 池子的曲线，StableSwap 的放大系数可能正在调整
+        let pool_curve = config.curve(clock.unix_timestamp)?;
 
-        let mut curve = ConstantProduct::init(
-            vault_x.amount(),
-            vault_y.amount(),
-            vault_x.amount(),
-            fee,
-            None
-        ).map_err(|_| ProgramError::ArithmeticOverflow)?;
-
-        let pair = if data.is_x{
-            LiquidityPair::X
-        }else {
-            LiquidityPair::Y
+        // 4. 按方向确定转入侧和转出侧
+        let (
+            (mint_in, user_in, vault_in, token_program_in, reserve_in),
+            (mint_out, user_out, vault_out, token_program_out, reserve_out),
+        ) = if is_x {
+            //X - Y
+            (
+                (accounts.mint_x, accounts.user_x_ata, accounts.vault_x, accounts.token_program_x, reserve_x),
+                (accounts.mint_y, accounts.user_y_ata, accounts.vault_y, accounts.token_program_y, reserve_y),
+            )
+        } else {
+            //Y - X
+            (
+                (accounts.mint_y, accounts.user_y_ata, accounts.vault_y, accounts.token_program_y, reserve_y),
+                (accounts.mint_x, accounts.user_x_ata, accounts.vault_x, accounts.token_program_x, reserve_x),
+            )
         };
-        let swap_result = curve
-            .swap(pair,data.amount,data.min)
-            .map_err(|_| ProgramError::InvalidArgument)?;
-
-        //4. 准备签名种子(用于从金库转出)
-        let seed_binding = config.seed().to_le_bytes();
-        let min_x_key = config.mint_x();
-        let min_y_key = config.mint_y();
-        let bump = config.config_bump();
-
-        let config_seeds = [
-            Seed::from(b"config"),
-            Seed::from(&seed_binding),
-            Seed::from(min_x_key.as_ref()),
-            Seed::from(min_y_key.as_ref()),
-            Seed::from(bump),
-        ];
-        let signer = Signer::from(&config_seeds);
 
-        // 5. 执行原子转账
-        if data.is_x{
+        // 5. 转入并按金库实际收到的数量计算曲线
+        // 带 TransferFee 扩展的 Mint 到账数量会少于转出数量，转入和转出两侧都要考虑
+        let transfer_fee_out = TransferFee::from_mint(mint_out, clock.epoch)?;
+        let (received, amount_out, swap_fee) = match mode {
+            SwapMode::ExactIn { amount_in, min_out } => {
+                let received = transfer_in(
+                    user_in,
+                    mint_in,
+                    vault_in,
+                    accounts.user,
+                    amount_in,
+                    token_program_in,
+                )?;
 
-            //X - Y
-            Transfer{
-                from:accounts.user_x_ata,
-                to:accounts.vault_x,
-                authority: accounts.user,
-                amount:swap_result.deposit,
-            }.invoke()?;
-
-            Transfer{
-                from:accounts.vault_y,
-                to:accounts.user_y_ata,
-                authority:accounts.config,
-                amount: swap_result.withdraw,
-            }.invoke_signed(&[signer])?;
-        }else {
-            //Y - X
-            Transfer{
-                from:accounts.user_y_ata,
-                to:accounts.vault_y,
-                authority: accounts.user,
-                amount:swap_result.deposit,
-            }.invoke()?;
-
-            Transfer{
-                from:accounts.vault_x,
-                to:accounts.user_x_ata,
-                authority:accounts.config,
-                amount: swap_result.withdraw,
-            }.invoke_signed(&[signer])?;
+                let swap_result = pool_curve.swap_exact_in(reserve_in, reserve_out, received, fee)?;
+
+                // 滑点保护检查：按用户实际到账的数量
+                let net_out = swap_result.amount_out - transfer_fee_out.fee(swap_result.amount_out);
+                if net_out < min_out {
+                    return Err(AmmError::SlippageExceeded.into());
+                }
+                (received, swap_result.amount_out, swap_result.fee)
+            }
+            SwapMode::ExactOut { amount_out, max_in } => {
+                // 金库需要多转出输出代币的转账手续费，保证用户实际到账 amount_out
+                let amount_out = transfer_fee_out.gross_amount(amount_out)?;
+                // 金库需要收到的数量（向上取整），再加上转账手续费得到用户需要转出的数量
+                let swap_result = pool_curve.swap_exact_out(reserve_in, reserve_out, amount_out, fee)?;
+                let amount_in = TransferFee::from_mint(mint_in, clock.epoch)?
+                    .gross_amount(swap_result.amount_in)?;
+
+                // 滑点保护检查
+                if amount_in > max_in {
+                    return Err(AmmError::SlippageExceeded.into());
+                }
+
+                let received = transfer_in(
+                    user_in,
+                    mint_in,
+                    vault_in,
+                    accounts.user,
+                    amount_in,
+                    token_program_in,
+                )?;
+                if received < swap_result.amount_in {
+                    return Err(AmmError::SlippageExceeded.into());
+                }
+                (received, amount_out, swap_result.fee)
+            }
+        };
+
+        // 6. 手续费以输入代币收取，其中协议的部分单独记账
+        let protocol_fee = curve::protocol_fee(swap_fee, config.protocol_fee())?;
+        if protocol_fee > 0 {
+            config.accrue_protocol_fee(is_x, protocol_fee)?;
         }
 
-        Ok(())
+        // 交换之后的储备：输入侧增加到账数量并扣除协议费，输出侧减少转出数量
+        let reserve_in = reserve_in
+            .checked_add(received)
+            .and_then(|reserve| reserve.checked_sub(protocol_fee))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let reserve_out = reserve_out
+            .checked_sub(amount_out)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let (reserve_x, reserve_y) = if is_x { (reserve_in, reserve_out) } else { (reserve_out, reserve_in) };
+
+        //7. 准备签名种子(用于从金库转出)
+        // Config 作为签名者参与 CPI，转出前需要释放可写借用
+        let config_signer = config.signer();
+        drop(config);
+
+        let config_seeds = config_signer.seeds();
+        let signer = Signer::from(&config_seeds);
+
+        // 8. 从金库转出
+        TransferChecked{
+            from:vault_out,
+            mint:mint_out,
+            to:user_out,
+            authority:accounts.config,
+            amount: amount_out,
+            decimals: mint_decimals(mint_out)?,
+            token_program: token_program_out.address(),
+        }.invoke_signed(&[signer])?;
+
+        emit(&SwapEvent {
+            pool: accounts.config.address().to_bytes(),
+            user: accounts.user.address().to_bytes(),
+            is_x: is_x as u8,
+            amount_in: received,
+            amount_out,
+            fee: swap_fee,
+            protocol_fee,
+            reserve_x,
+            reserve_y,
+        });
+
+        Ok(amount_out)
     }
-}
\ No newline at end of file
+
+    // 交换中用户收到代币的账户
+    #[inline(always)]
+    pub fn user_out(&self, is_x: bool) -> &'info AccountView {
+        if is_x { self.user_y_ata } else { self.user_x_ata }
+    }
+
+    // 交换中用户转出代币的账户
+    #[inline(always)]
+    pub fn user_in(&self, is_x: bool) -> &'info AccountView {
+        if is_x { self.user_x_ata } else { self.user_y_ata }
+    }
+}
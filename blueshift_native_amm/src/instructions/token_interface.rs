@@ -0,0 +1,217 @@
+use pinocchio::{AccountView, Address, ProgramResult};
+use pinocchio::cpi::{invoke_signed, Signer};
+use pinocchio::error::ProgramError;
+use pinocchio::instruction::{InstructionAccount, InstructionView};
+use super::helpers::TOKEN_2022_PROGRAM_ID;
+
+// =============================================================================
+// 同时兼容旧版 Token Program 和 Token-2022 的代币操作
+// =============================================================================
+// pinocchio_token 的指令只会 CPI 到旧版 Token Program，
+// 这里手动构造 TransferChecked，由调用者指定每一侧实际使用的 token program
+
+// TransferChecked 指令判别器（旧版与 Token-2022 相同）
+const TRANSFER_CHECKED_DISCRIMINATOR: u8 = 12;
+
+// Mint 账户中 decimals 字段的偏移量：mint_authority(36) + supply(8)
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+// Token Account 中 amount 字段的偏移量：mint(32) + owner(32)
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+
+// Token-2022 扩展从账户类型字节之后开始，按 [type: u16][length: u16][value] 排列
+const TOKEN_2022_ACCOUNT_TYPE_OFFSET: usize = 165;
+const TOKEN_2022_EXTENSIONS_OFFSET: usize = TOKEN_2022_ACCOUNT_TYPE_OFFSET + 1;
+
+// TransferFeeConfig 扩展类型及其内部布局
+// [transfer_fee_config_authority: 32][withdraw_withheld_authority: 32][withheld_amount: 8]
+// [older_transfer_fee: 18][newer_transfer_fee: 18]
+const TRANSFER_FEE_CONFIG_EXTENSION: u16 = 1;
+const TRANSFER_FEE_CONFIG_LEN: usize = 108;
+const OLDER_TRANSFER_FEE_OFFSET: usize = 72;
+const NEWER_TRANSFER_FEE_OFFSET: usize = 90;
+
+const MAX_FEE_BASIS_POINTS: u16 = 10_000;
+
+pub struct TransferChecked<'a> {
+    pub from: &'a AccountView,
+    pub mint: &'a AccountView,
+    pub to: &'a AccountView,
+    pub authority: &'a AccountView,
+    pub amount: u64,
+    pub decimals: u8,
+    pub token_program: &'a Address,
+}
+
+impl TransferChecked<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable(self.from.address()),
+            InstructionAccount::readonly(self.mint.address()),
+            InstructionAccount::writable(self.to.address()),
+            InstructionAccount::readonly_signer(self.authority.address()),
+        ];
+
+        // [discriminator: u8][amount: u64][decimals: u8]
+        let mut instruction_data = [0u8; 10];
+        instruction_data[0] = TRANSFER_CHECKED_DISCRIMINATOR;
+        instruction_data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        instruction_data[9] = self.decimals;
+
+        let instruction = InstructionView {
+            program_id: self.token_program,
+            accounts: &instruction_accounts,
+            data: &instruction_data,
+        };
+
+        invoke_signed(
+            &instruction,
+            &[self.from, self.mint, self.to, self.authority],
+            signers,
+        )
+    }
+}
+
+// 读取 Mint 的 decimals，旧版和 Token-2022 的基础布局相同
+#[inline(always)]
+pub fn mint_decimals(mint: &AccountView) -> Result<u8, ProgramError> {
+    let data = mint.try_borrow()?;
+    data.get(MINT_DECIMALS_OFFSET)
+        .copied()
+        .ok_or(ProgramError::InvalidAccountData)
+}
+
+// 读取 Token Account 的余额，旧版和 Token-2022 的基础布局相同
+#[inline(always)]
+pub fn token_account_amount(account: &AccountView) -> Result<u64, ProgramError> {
+    let data = account.try_borrow()?;
+    let amount = data
+        .get(TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    Ok(u64::from_le_bytes(amount.try_into().unwrap()))
+}
+
+// 某个 epoch 生效的转账手续费
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TransferFee {
+    pub maximum_fee: u64,
+    pub basis_points: u16,
+}
+
+impl TransferFee {
+    // 从 Mint 中读取当前 epoch 生效的转账手续费
+    // 旧版 Token Program 或没有 TransferFeeConfig 扩展的 Mint 返回零手续费
+    pub fn from_mint(mint: &AccountView, epoch: u64) -> Result<Self, ProgramError> {
+        if !mint.owned_by(&TOKEN_2022_PROGRAM_ID) {
+            return Ok(Self::default());
+        }
+
+        let data = mint.try_borrow()?;
+        let mut offset = TOKEN_2022_EXTENSIONS_OFFSET;
+
+        // 逐个遍历 TLV 扩展，直到找到 TransferFeeConfig
+        while offset + 4 <= data.len() {
+            let extension_type = u16::from_le_bytes([data[offset], data[offset + 1]]);
+            let length = u16::from_le_bytes([data[offset + 2], data[offset + 3]]) as usize;
+            let value_start = offset + 4;
+            let value_end = value_start + length;
+
+            if value_end > data.len() {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            if extension_type == TRANSFER_FEE_CONFIG_EXTENSION {
+                if length != TRANSFER_FEE_CONFIG_LEN {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                let value = &data[value_start..value_end];
+
+                // newer_transfer_fee 从其 epoch 开始生效，之前仍使用 older_transfer_fee
+                let newer_epoch = u64::from_le_bytes(
+                    value[NEWER_TRANSFER_FEE_OFFSET..NEWER_TRANSFER_FEE_OFFSET + 8].try_into().unwrap(),
+                );
+                let fee_offset = if epoch >= newer_epoch {
+                    NEWER_TRANSFER_FEE_OFFSET
+                } else {
+                    OLDER_TRANSFER_FEE_OFFSET
+                };
+
+                return Ok(Self {
+                    maximum_fee: u64::from_le_bytes(
+                        value[fee_offset + 8..fee_offset + 16].try_into().unwrap(),
+                    ),
+                    basis_points: u16::from_le_bytes(
+                        value[fee_offset + 16..fee_offset + 18].try_into().unwrap(),
+                    ),
+                });
+            }
+
+            offset = value_end;
+        }
+
+        Ok(Self::default())
+    }
+
+    // 转出 amount 时被扣除的手续费（向上取整，不超过 maximum_fee）
+    pub fn fee(&self, amount: u64) -> u64 {
+        if self.basis_points == 0 || amount == 0 {
+            return 0;
+        }
+        let fee = (amount as u128 * self.basis_points as u128)
+            .div_ceil(MAX_FEE_BASIS_POINTS as u128);
+        fee.min(self.maximum_fee as u128) as u64
+    }
+
+    // 为了让接收方至少收到 net，发送方需要转出的数量
+    pub fn gross_amount(&self, net: u64) -> Result<u64, ProgramError> {
+        if self.basis_points == 0 || net == 0 {
+            return Ok(net);
+        }
+        if self.basis_points >= MAX_FEE_BASIS_POINTS {
+            return net
+                .checked_add(self.maximum_fee)
+                .ok_or(ProgramError::ArithmeticOverflow);
+        }
+
+        let gross = (net as u128 * MAX_FEE_BASIS_POINTS as u128)
+            .div_ceil((MAX_FEE_BASIS_POINTS - self.basis_points) as u128);
+
+        if gross - net as u128 > self.maximum_fee as u128 {
+            net.checked_add(self.maximum_fee)
+                .ok_or(ProgramError::ArithmeticOverflow)
+        } else {
+            u64::try_from(gross).map_err(|_| ProgramError::ArithmeticOverflow)
+        }
+    }
+}
+
+// 用户把代币转入金库，返回金库实际收到的数量
+// 对带 TransferFee 扩展的 Mint，实际到账数量小于转出的 amount
+pub fn transfer_in(
+    from: &AccountView,
+    mint: &AccountView,
+    vault: &AccountView,
+    authority: &AccountView,
+    amount: u64,
+    token_program: &AccountView,
+) -> Result<u64, ProgramError> {
+    let before = token_account_amount(vault)?;
+
+    TransferChecked {
+        from,
+        mint,
+        to: vault,
+        authority,
+        amount,
+        decimals: mint_decimals(mint)?,
+        token_program: token_program.address(),
+    }.invoke()?;
+
+    let after = token_account_amount(vault)?;
+    after.checked_sub(before).ok_or(ProgramError::ArithmeticOverflow)
+}
@@ -0,0 +1,106 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::error::ProgramError;
+use super::admin::AdminAccounts;
+use super::helpers::{emit, AccountCheck, SignerAccount};
+use super::registry::sync_fee_tier;
+use crate::events::FeeUpdatedEvent;
+use crate::layout::discriminator;
+
+// 除了 authority 和 Config，还可以额外传入注册表和这个交易对的记录：
+// 已注册的池子修改手续费时同步更新 PoolEntry 中的手续费档位
+// 两个账户放在最后，只传入 [authority, config] 的调用者仍然可以修改手续费，但不会同步
+pub struct UpdateFeeAccounts<'info> {
+    pub admin: AdminAccounts<'info>,
+    // (registry, pair_record)
+    pub pair: Option<(&'info AccountView, &'info AccountView)>,
+}
+
+impl<'info> TryFrom<&'info [AccountView]> for UpdateFeeAccounts<'info> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config, pair @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        let pair = match pair {
+            [] => None,
+            [registry, pair_record] => Some((registry, pair_record)),
+            _ => return Err(ProgramError::NotEnoughAccountKeys),
+        };
+
+        Ok(Self {
+            admin: AdminAccounts { authority, config },
+            pair,
+        })
+    }
+}
+
+pub struct UpdateFeeInstructionData {
+    pub fee: u16,
+}
+
+impl<'info> TryFrom<&'info [u8]> for UpdateFeeInstructionData {
+    type Error = ProgramError;
+    fn try_from(data: &'info [u8]) -> Result<Self, Self::Error> {
+        if data.len() < size_of::<u16>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let fee = u16::from_le_bytes(data[0..2].try_into().unwrap());
+
+        Ok(Self { fee })
+    }
+}
+
+pub struct UpdateFee<'info> {
+    pub accounts: UpdateFeeAccounts<'info>,
+    pub instruction_data: UpdateFeeInstructionData,
+}
+
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for UpdateFee<'info> {
+    type Error = ProgramError;
+    fn try_from((data, accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = UpdateFeeAccounts::try_from(accounts)?;
+        let instruction_data = UpdateFeeInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'info> UpdateFee<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::UPDATE_FEE;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        // set_fee 会拒绝 >= 10_000 bps 的手续费
+        let mut config = accounts.admin.load_config()?;
+        config.set_fee(self.instruction_data.fee)?;
+
+        // 注册表要求每个档位只有一个池子时，新的手续费不能与同一交易对的其他池子相同
+        if let Some((registry, pair_record)) = accounts.pair {
+            sync_fee_tier(
+                registry,
+                pair_record,
+                accounts.admin.config,
+                config.mint_x(),
+                config.mint_y(),
+                self.instruction_data.fee,
+            )?;
+        }
+
+        emit(&FeeUpdatedEvent {
+            pool: accounts.admin.config.address().to_bytes(),
+            authority: accounts.admin.authority.address().to_bytes(),
+            fee: self.instruction_data.fee,
+            protocol_fee: config.protocol_fee(),
+            fee_recipient: config.fee_recipient().to_bytes(),
+        });
+        Ok(())
+    }
+}
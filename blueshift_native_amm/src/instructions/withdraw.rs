@@ -1,16 +1,24 @@
-use constant_product_curve::ConstantProduct;
 use pinocchio::{AccountView, ProgramResult};
-use pinocchio::cpi::{Seed, Signer};
+use pinocchio::cpi::Signer;
 use pinocchio::error::ProgramError;
 use pinocchio::sysvars::clock::Clock;
 use pinocchio::sysvars::Sysvar;
-use pinocchio_token::instructions::{Burn, Transfer};
-use pinocchio_token::state::{Mint, TokenAccount};
+use pinocchio_token::instructions::Burn;
+use pinocchio_token::state::Mint;
 use crate::Config;
+use crate::curve;
+use super::helpers::{emit, AccountCheck, MintLpAccount, PoolSide, SignerAccount, TokenProgram, UserTokenAccount};
+use super::token_interface::{mint_decimals, token_account_amount, TransferChecked};
+use crate::errors::AmmError;
+use crate::events::WithdrawEvent;
+pub use crate::layout::WithdrawInstructionData;
+use crate::layout::discriminator;
 
 pub struct WithdrawAccounts<'info> {
     pub user: &'info AccountView,
     pub mint_lp: &'info AccountView,
+    pub mint_x: &'info AccountView,
+    pub mint_y: &'info AccountView,
     pub vault_x: &'info AccountView,
     pub vault_y: &'info AccountView,
     pub user_x_ata: &'info AccountView,
@@ -18,40 +26,45 @@ pub struct WithdrawAccounts<'info> {
     pub user_lp_ata: &'info AccountView,
     pub config: &'info AccountView,
     pub token_program: &'info AccountView,
+    pub token_program_x: &'info AccountView,
+    pub token_program_y: &'info AccountView,
 }
 
 impl<'info> TryFrom<&'info [AccountView]> for WithdrawAccounts<'info> {
     type Error = ProgramError;
     fn try_from(accounts: &'info [AccountView]) -> Result<Self, Self::Error> {
-        let [user,mint_lp,vault_x,vault_y,user_x_ata,user_y_ata,user_lp_ata,config,token_program] = accounts else{
+        let [user,mint_lp,mint_x,mint_y,vault_x,vault_y,user_x_ata,user_y_ata,user_lp_ata,config,token_program,token_program_x,token_program_y] = accounts else{
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
+        SignerAccount::check(user)?;
+        TokenProgram::check(token_program)?;
+        {
+            let config_data = Config::load(config)?;
+            PoolSide::check(config_data.mint_x(), mint_x, vault_x, user_x_ata, config, token_program_x)?;
+            PoolSide::check(config_data.mint_y(), mint_y, vault_y, user_y_ata, config, token_program_y)?;
+        }
+        MintLpAccount::check(mint_lp, config)?;
+        UserTokenAccount::check(user_lp_ata, mint_lp.address())?;
+
         Ok(Self{
             user,
             mint_lp,
+            mint_x,
+            mint_y,
             vault_x,
             vault_y,
             user_x_ata,
             user_y_ata,
             user_lp_ata,
             config,
-            token_program
+            token_program,
+            token_program_x,
+            token_program_y,
         })
     }
 }
 
-// 使用 C 内存布局 + 紧凑排列，确保结构体在内存中的布局与字节数组完全一致
-// 内存布局: [amount: u64][mint_x: u64][mint_y: u64][expiration: i64] = 32字节
-// 这是直接指针转换反序列化的前提条件
-#[repr(C, packed)]
-#[derive(Copy, Clone)]
-pub struct WithdrawInstructionData{
-    pub amount: u64,
-    pub mint_x: u64,
-    pub mint_y: u64,
-    pub expiration: i64,
-}
 impl<'info> TryFrom<&'info [u8]> for WithdrawInstructionData {
     type Error = ProgramError;
     fn try_from(data: &'info [u8]) -> Result<WithdrawInstructionData, Self::Error> {
@@ -101,7 +114,7 @@ impl<'info> TryFrom<(&'info [u8],&'info [AccountView])> for Withdraw<'info> {
 }
 
 impl<'info> Withdraw<'info> {
-    pub const DISCRIMINATOR: &'info u8 = &2;
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::WITHDRAW;
     pub fn process(&mut self) -> ProgramResult {
 
         let accounts = &self.accounts;
@@ -110,39 +123,38 @@ impl<'info> Withdraw<'info> {
         //1. 过期检查
         let clock = Clock::get()?;
         if clock.unix_timestamp > data.expiration{
-            return Err(ProgramError::InvalidArgument);
+            return Err(AmmError::Expired.into());
         }
 
         //2. 加载状态并检查 Withdraw要求非Disable
-        let config = Config::load(accounts.config)?;
-        if config.state() == 2 {
-            return Err(ProgramError::InvalidAccountData);
-        }
+        let mut config = Config::load_mut(accounts.config)?;
+        config.check_withdrawable()?;
 
         //3. 反序列化代币信息
         let mint_lp = unsafe { Mint::from_account_view_unchecked(accounts.mint_lp)? };
-        let vault_x = unsafe { TokenAccount::from_account_view_unchecked(accounts.vault_x)? };
-        let vault_y = unsafe { TokenAccount::from_account_view_unchecked(accounts.vault_y)? };
+        let supply = mint_lp.supply();
+        // 金库中尚未领取的协议费不属于 LP，不计入储备
+        let (reserve_x, reserve_y) = config.reserves(
+            token_account_amount(accounts.vault_x)?,
+            token_account_amount(accounts.vault_y)?,
+        )?;
+
+        // 储备变化之前，用变化前的储备推进 TWAP 累计价格
+        config.update_oracle(reserve_x, reserve_y, clock.unix_timestamp)?;
 
         //4. 计算应退还的 X, Y数量
-        let (x,y) = if mint_lp.supply() == data.amount{
-
-            //全额提取:直接取走所有余额,防止舍入误差留下“尘埃”
-            (vault_x.amount(),vault_y.amount())
-        }else {
-            let amounts = ConstantProduct::xy_deposit_amounts_from_l(
-                vault_x.amount(),
-                vault_y.amount(),
-                mint_lp.supply(),
-                data.amount,
-                6
-            ).map_err(|_| ProgramError::ArithmeticOverflow)?;
-            (amounts.x,amounts.y)
-        };
+        //全额提取时直接取走所有余额,防止舍入误差留下“尘埃”
+        let amounts = curve::withdraw_amounts(
+            reserve_x,
+            reserve_y,
+            supply,
+            data.amount,
+        )?;
+        let (x,y) = (amounts.x,amounts.y);
 
         // 5. 滑点检查
         if x < data.mint_x || y < data.mint_y {
-            return Err(ProgramError::InvalidArgument);
+            return Err(AmmError::SlippageExceeded.into());
         }
 
         // 6. 销毁用户的LP
@@ -154,34 +166,47 @@ impl<'info> Withdraw<'info> {
         }.invoke()?;
 
         // 7. 构造Config PDA签名从金库转账
-        let seed_binding = config.seed().to_le_bytes();
-        let mint_x_key = config.mint_x();
-        let mint_y_key = config.mint_y();
-        let bump = config.config_bump();
-
-        let config_seeds = [
-            Seed::from(b"config"),
-            Seed::from(&seed_binding),
-            Seed::from(mint_x_key.as_ref()),
-            Seed::from(mint_y_key.as_ref()),
-            Seed::from(bump),
-        ];
+        // Config 作为签名者参与 CPI，需要先释放可写借用
+        let config_signer = config.signer();
+        drop(config);
+
+        let config_seeds = config_signer.seeds();
         let signer = Signer::from(&config_seeds);
 
         // 8. 转移Token X和Y (Config PDA签名)
-        Transfer {
+        TransferChecked {
             from: accounts.vault_x,
+            mint: accounts.mint_x,
             to: accounts.user_x_ata,
             authority: accounts.config,
             amount: x,
-        }.invoke_signed(&[signer.clone()])?;
+            decimals: mint_decimals(accounts.mint_x)?,
+            token_program: accounts.token_program_x.address(),
+        }.invoke_signed(core::slice::from_ref(&signer))?;
 
-        Transfer {
+        TransferChecked {
             from: accounts.vault_y,
+            mint: accounts.mint_y,
             to: accounts.user_y_ata,
             authority: accounts.config,
             amount: y,
-        }.invoke_signed(&[signer.clone()])?;
+            decimals: mint_decimals(accounts.mint_y)?,
+            token_program: accounts.token_program_y.address(),
+        }.invoke_signed(core::slice::from_ref(&signer))?;
+
+        // withdraw_amounts 保证取出的数量不超过储备，销毁的 LP 不超过供应量
+        emit(&WithdrawEvent {
+            pool: accounts.config.address().to_bytes(),
+            user: accounts.user.address().to_bytes(),
+            lp_amount: data.amount,
+            amount_x: x,
+            amount_y: y,
+            swap_amount: 0,
+            reserve_x: reserve_x - x,
+            reserve_y: reserve_y - y,
+            lp_supply: supply - data.amount,
+        });
+
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,324 @@
+use pinocchio::{AccountView, ProgramResult};
+use pinocchio::cpi::Signer;
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::Sysvar;
+use pinocchio_token::instructions::{Burn, MintTo};
+use pinocchio_token::state::Mint;
+use crate::Config;
+use crate::curve;
+use super::deposit::DepositAccounts;
+use super::withdraw::WithdrawAccounts;
+use super::helpers::{emit, read_flag};
+use super::token_interface::{mint_decimals, token_account_amount, transfer_in, TransferChecked, TransferFee};
+use crate::errors::AmmError;
+use crate::events::{DepositEvent, WithdrawEvent};
+use crate::layout::discriminator;
+
+// =============================================================================
+// 单边存入："我只有 X，最多花 amount_in 个 X，至少拿到 min_lp 个 LP"
+// =============================================================================
+// 程序在池子内部把一部分 X 按曲线换成 Y（收取交换手续费），
+// 换得的 Y 不离开金库，与剩余的 X 一起按比例作为流动性存入
+// 内部交换与 Swap 一样收取手续费，其中协议的部分单独记账
+#[repr(C,packed)]
+#[derive(Copy, Clone)]
+pub struct DepositSingleInstructionData {
+    pub is_x: bool,
+    pub amount_in: u64,
+    pub min_lp: u64,
+    pub expirations: i64,
+}
+// 逐字段读取：is_x 不是 0 或 1 时返回 InvalidInstructionData
+impl TryFrom<&[u8]> for DepositSingleInstructionData {
+    type Error = ProgramError;
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(Self {
+            is_x: read_flag(data[0])?,
+            amount_in: u64::from_le_bytes(data[1..9].try_into().unwrap()),
+            min_lp: u64::from_le_bytes(data[9..17].try_into().unwrap()),
+            expirations: i64::from_le_bytes(data[17..25].try_into().unwrap()),
+        })
+    }
+}
+
+pub struct DepositSingle<'info> {
+    pub accounts: DepositAccounts<'info>,
+    pub instruction_data: DepositSingleInstructionData,
+}
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for DepositSingle<'info> {
+    type Error = ProgramError;
+    fn try_from((data,accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = DepositAccounts::try_from(accounts)?;
+        let instruction_data = DepositSingleInstructionData::try_from(data)?;
+
+        Ok(Self{
+            accounts,
+            instruction_data,
+        })
+    }
+}
+impl<'info> DepositSingle<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::DEPOSIT_SINGLE;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+        let data = self.instruction_data;
+
+        //1. 过期检查
+        let clock = Clock::get()?;
+        if clock.unix_timestamp > data.expirations {
+            return Err(AmmError::Expired.into());
+        }
+
+        //2. 加载Config并验证状态
+        let mut config = Config::load_mut(accounts.config)?;
+        config.check_active()?;
+
+        //3. 读取储备和 LP 供应量，空池子没有价格，不能单边存入
+        let mint_lp = unsafe { Mint::from_account_view_unchecked(accounts.mint_lp)? };
+        let supply = mint_lp.supply();
+        // 金库中尚未领取的协议费不属于 LP，不计入储备
+        let (reserve_x, reserve_y) = config.reserves(
+            token_account_amount(accounts.vault_x)?,
+            token_account_amount(accounts.vault_y)?,
+        )?;
+
+        // 储备变化之前，用变化前的储备推进 TWAP 累计价格
+        config.update_oracle(reserve_x, reserve_y, clock.unix_timestamp)?;
+        let fee = u16::from_le_bytes(*config.fee());
+
+        let (mint_in, user_in, vault_in, token_program_in, reserve_in, reserve_out) = if data.is_x {
+            (accounts.mint_x, accounts.user_x_ata, accounts.vault_x, accounts.token_program_x, reserve_x, reserve_y)
+        } else {
+            (accounts.mint_y, accounts.user_y_ata, accounts.vault_y, accounts.token_program_y, reserve_y, reserve_x)
+        };
+
+        //4. 按金库实际能收到的数量规划交换和存入
+        // 带 TransferFee 扩展的 Mint 到账数量会少于转出数量
+        let transfer_fee = TransferFee::from_mint(mint_in, clock.epoch)?;
+        let budget = data.amount_in - transfer_fee.fee(data.amount_in);
+        let zap = curve::zap_in_with(
+            config.curve(clock.unix_timestamp)?,
+            reserve_in,
+            reserve_out,
+            supply,
+            budget,
+            fee,
+            config.protocol_fee(),
+        )?;
+
+        // 5. 滑点保护检查
+        if zap.lp < data.min_lp {
+            return Err(AmmError::SlippageExceeded.into());
+        }
+
+        //6. 只转入实际需要的数量，剩余部分留在用户账户
+        let amount_in = transfer_fee.gross_amount(zap.amount_in)?;
+        let received = transfer_in(
+            user_in,
+            mint_in,
+            vault_in,
+            accounts.user,
+            amount_in,
+            token_program_in,
+        )?;
+        if received < zap.amount_in {
+            return Err(AmmError::SlippageExceeded.into());
+        }
+
+        // 内部交换的手续费以输入代币收取，其中协议的部分单独记账
+        if zap.protocol_fee > 0 {
+            config.accrue_protocol_fee(data.is_x, zap.protocol_fee)?;
+        }
+
+        //7. 签署并执行MintTo(Config PDA -》 用户)
+        // Config 作为签名者参与 CPI，需要先释放可写借用
+        let config_signer = config.signer();
+        drop(config);
+
+        let config_seeds = config_signer.seeds();
+        let signer = Signer::from(&config_seeds);
+
+        MintTo{
+            mint:accounts.mint_lp,
+            account:accounts.user_lp_ata,
+            mint_authority: accounts.config,
+            amount:zap.lp,
+        }.invoke_signed(&[signer])?;
+
+        // 换得的代币留在金库中，只有转入一侧的储备增加，并扣除协议费
+        let (amount_x, amount_y) = if data.is_x { (received, 0) } else { (0, received) };
+        let (protocol_fee_x, protocol_fee_y) = if data.is_x { (zap.protocol_fee, 0) } else { (0, zap.protocol_fee) };
+        emit(&DepositEvent {
+            pool: accounts.config.address().to_bytes(),
+            user: accounts.user.address().to_bytes(),
+            amount_x,
+            amount_y,
+            swap_amount: zap.swap_amount,
+            lp_amount: zap.lp,
+            reserve_x: reserve_x
+                .checked_add(amount_x)
+                .and_then(|reserve| reserve.checked_sub(protocol_fee_x))
+                .ok_or(ProgramError::ArithmeticOverflow)?,
+            reserve_y: reserve_y
+                .checked_add(amount_y)
+                .and_then(|reserve| reserve.checked_sub(protocol_fee_y))
+                .ok_or(ProgramError::ArithmeticOverflow)?,
+            lp_supply: supply.checked_add(zap.lp).ok_or(ProgramError::ArithmeticOverflow)?,
+        });
+
+        Ok(())
+    }
+}
+
+// =============================================================================
+// 单边取出："销毁 amount 个 LP，只要 X，至少拿到 min_out 个 X"
+// =============================================================================
+// 程序先按比例计算应退还的 X 和 Y，再在池子内部把 Y 按曲线换成 X（收取交换手续费），
+// 换掉的 Y 不离开金库，用户只收到一种代币
+#[repr(C,packed)]
+#[derive(Copy, Clone)]
+pub struct WithdrawSingleInstructionData {
+    pub is_x: bool,
+    pub amount: u64,
+    pub min_out: u64,
+    pub expirations: i64,
+}
+// 与 DepositSingle 相同，逐字段读取
+impl TryFrom<&[u8]> for WithdrawSingleInstructionData {
+    type Error = ProgramError;
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(Self {
+            is_x: read_flag(data[0])?,
+            amount: u64::from_le_bytes(data[1..9].try_into().unwrap()),
+            min_out: u64::from_le_bytes(data[9..17].try_into().unwrap()),
+            expirations: i64::from_le_bytes(data[17..25].try_into().unwrap()),
+        })
+    }
+}
+
+pub struct WithdrawSingle<'info> {
+    pub accounts: WithdrawAccounts<'info>,
+    pub instruction_data: WithdrawSingleInstructionData,
+}
+impl<'info> TryFrom<(&'info [u8], &'info [AccountView])> for WithdrawSingle<'info> {
+    type Error = ProgramError;
+    fn try_from((data,accounts): (&'info [u8], &'info [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = WithdrawAccounts::try_from(accounts)?;
+        let instruction_data = WithdrawSingleInstructionData::try_from(data)?;
+
+        Ok(Self{
+            accounts,
+            instruction_data,
+        })
+    }
+}
+impl<'info> WithdrawSingle<'info> {
+    pub const DISCRIMINATOR: &'info u8 = &discriminator::WITHDRAW_SINGLE;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+        let data = self.instruction_data;
+
+        //1. 过期检查
+        let clock = Clock::get()?;
+        if clock.unix_timestamp > data.expirations {
+            return Err(AmmError::Expired.into());
+        }
+
+        //2. 内部交换要求池子处于正常运行状态，WithdrawOnly 时只能按比例取款
+        let mut config = Config::load_mut(accounts.config)?;
+        config.check_active()?;
+
+        //3. 读取储备和 LP 供应量
+        let mint_lp = unsafe { Mint::from_account_view_unchecked(accounts.mint_lp)? };
+        let supply = mint_lp.supply();
+        // 金库中尚未领取的协议费不属于 LP，不计入储备
+        let (reserve_x, reserve_y) = config.reserves(
+            token_account_amount(accounts.vault_x)?,
+            token_account_amount(accounts.vault_y)?,
+        )?;
+
+        // 储备变化之前，用变化前的储备推进 TWAP 累计价格
+        config.update_oracle(reserve_x, reserve_y, clock.unix_timestamp)?;
+        let fee = u16::from_le_bytes(*config.fee());
+
+        let (mint_out, user_out, vault_out, token_program_out, reserve_keep, reserve_swap) = if data.is_x {
+            (accounts.mint_x, accounts.user_x_ata, accounts.vault_x, accounts.token_program_x, reserve_x, reserve_y)
+        } else {
+            (accounts.mint_y, accounts.user_y_ata, accounts.vault_y, accounts.token_program_y, reserve_y, reserve_x)
+        };
+
+        //4. 计算单边取出数量，最后一份流动性只能通过 Withdraw 按比例取出
+        let zap = curve::zap_out_with(
+            config.curve(clock.unix_timestamp)?,
+            reserve_keep,
+            reserve_swap,
+            supply,
+            data.amount,
+            fee,
+            config.protocol_fee(),
+        )?;
+
+        // 5. 滑点检查
+        if zap.amount_out < data.min_out {
+            return Err(AmmError::SlippageExceeded.into());
+        }
+
+        // 内部交换的手续费以换掉的代币收取，其中协议的部分单独记账
+        if zap.protocol_fee > 0 {
+            config.accrue_protocol_fee(!data.is_x, zap.protocol_fee)?;
+        }
+
+        // 6. 销毁用户的LP
+        Burn {
+            mint: accounts.mint_lp,
+            account: accounts.user_lp_ata,
+            authority: accounts.user,
+            amount: data.amount,
+        }.invoke()?;
+
+        // 7. 构造Config PDA签名从金库转账
+        // Config 作为签名者参与 CPI，需要先释放可写借用
+        let config_signer = config.signer();
+        drop(config);
+
+        let config_seeds = config_signer.seeds();
+        let signer = Signer::from(&config_seeds);
+
+        TransferChecked {
+            from: vault_out,
+            mint: mint_out,
+            to: user_out,
+            authority: accounts.config,
+            amount: zap.amount_out,
+            decimals: mint_decimals(mint_out)?,
+            token_program: token_program_out.address(),
+        }.invoke_signed(&[signer])?;
+
+        // 换掉的代币不离开金库，取出一侧的储备减少，换掉一侧扣除协议费
+        // zap_out_with 保证取出的数量和协议费不超过储备，销毁的 LP 少于供应量
+        let (amount_x, amount_y) = if data.is_x { (zap.amount_out, 0) } else { (0, zap.amount_out) };
+        let (protocol_fee_x, protocol_fee_y) = if data.is_x { (0, zap.protocol_fee) } else { (zap.protocol_fee, 0) };
+        emit(&WithdrawEvent {
+            pool: accounts.config.address().to_bytes(),
+            user: accounts.user.address().to_bytes(),
+            lp_amount: data.amount,
+            amount_x,
+            amount_y,
+            swap_amount: zap.swap_amount,
+            reserve_x: reserve_x - amount_x - protocol_fee_x,
+            reserve_y: reserve_y - amount_y - protocol_fee_y,
+            lp_supply: supply - data.amount,
+        });
+
+        Ok(())
+    }
+}
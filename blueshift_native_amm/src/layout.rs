@@ -0,0 +1,727 @@
+// =============================================================================
+// 账户和指令数据布局
+// =============================================================================
+// 程序和链下客户端（blueshift_native_amm_client）共用这一个文件，
+// 保证 Config 布局、PDA 种子、指令判别器和指令数据的字节格式不会不一致
+//
+// 只依赖 core 和 solana_address，不能引入 pinocchio 等链上依赖
+
+use core::mem::size_of;
+use solana_address::Address;
+
+// 22222222222222222222222222222222222222222222
+pub const ID: Address = Address::new_from_array([
+    0x0f, 0x1e, 0x6b, 0x14, 0x21, 0xc0, 0x4a, 0x07, 0x04, 0x31, 0x26, 0x5c, 0x19, 0xc5, 0xbb, 0xee,
+    0x19, 0x92, 0xba, 0xe8, 0xaf, 0xd1, 0xcd, 0x07, 0x8e, 0xf8, 0xaf, 0x70, 0x47, 0xdc, 0x11, 0xf7,
+]);
+
+// BPFLoaderUpgradeab1e11111111111111111111111
+// 程序的 ProgramData 是 [ID] 在这个程序下的 PDA，其中保存程序的升级权限
+pub const BPF_LOADER_UPGRADEABLE_ID: Address = Address::new_from_array([
+    0x02, 0xa8, 0xf6, 0x91, 0x4e, 0x88, 0xa1, 0xb0, 0xe2, 0x10, 0x15, 0x3e, 0xf7, 0x63, 0xae, 0x2b,
+    0x00, 0xc2, 0xb9, 0x3d, 0x16, 0xc1, 0x24, 0xd2, 0xc0, 0x53, 0x7a, 0x10, 0x04, 0x80, 0x00, 0x00,
+]);
+
+// =============================================================================
+// PDA 种子
+// =============================================================================
+
+// Config：[CONFIG_SEED, seed（u64 小端序）, mint_x, mint_y]
+pub const CONFIG_SEED: &[u8] = b"config";
+
+// LP Mint：[MINT_LP_SEED, config]
+pub const MINT_LP_SEED: &[u8] = b"mint_lp";
+
+// 锁定 LP 的持有者：[LOCKED_LP_SEED, config]
+// 程序从不使用这个 PDA 签名，转入它的 LP ATA 的 LP 永远无法取出
+pub const LOCKED_LP_SEED: &[u8] = b"locked_lp";
+
+// 池子注册表：[REGISTRY_SEED]，每个程序只有一个
+pub const REGISTRY_SEED: &[u8] = b"registry";
+
+// 交易对记录：[PAIR_SEED, mint_x, mint_y]，mint_x 按字节序小于 mint_y
+pub const PAIR_SEED: &[u8] = b"pair";
+
+// =============================================================================
+// 指令判别器：指令数据的第一个字节
+// =============================================================================
+pub mod discriminator {
+    pub const INITIALIZE: u8 = 0;
+    pub const DEPOSIT: u8 = 1;
+    pub const WITHDRAW: u8 = 2;
+    pub const SWAP: u8 = 3;
+    pub const LOCK: u8 = 4;
+    pub const UNLOCK: u8 = 5;
+    pub const SET_WITHDRAW_ONLY: u8 = 6;
+    pub const UPDATE_FEE: u8 = 7;
+    pub const PROPOSE_AUTHORITY: u8 = 8;
+    pub const ACCEPT_AUTHORITY: u8 = 9;
+    pub const SWAP_EXACT_OUT: u8 = 10;
+    pub const DEPOSIT_SINGLE: u8 = 11;
+    pub const WITHDRAW_SINGLE: u8 = 12;
+    pub const SET_PROTOCOL_FEE: u8 = 13;
+    pub const COLLECT_PROTOCOL_FEES: u8 = 14;
+    pub const MIGRATE_CONFIG: u8 = 15;
+    pub const ROUTE_SWAP: u8 = 16;
+    pub const RAMP_AMP: u8 = 17;
+    pub const STOP_RAMP_AMP: u8 = 18;
+    pub const UPDATE_LP_METADATA: u8 = 19;
+    pub const FLASH_LOAN: u8 = 20;
+    pub const INITIALIZE_REGISTRY: u8 = 21;
+    pub const UPDATE_REGISTRY: u8 = 22;
+    pub const REGISTER_POOL: u8 = 23;
+    pub const CREATE_LP_METADATA: u8 = 24;
+}
+
+// =============================================================================
+// Config 账户
+// =============================================================================
+// 使用 C 语言内存布局，确保结构体字段按声明顺序在内存中连续排列
+// 这对于将原始字节数组直接解释为结构体至关重要
+#[repr(C)]
+pub struct Config {
+    // AMM 状态字段，存储 AmmState 枚举的 u8 值（0-3）
+    pub(crate) state: u8,
+    // 种子值，用于 PDA（Program Derived Address）派生，存储为 8 字节小端序
+    pub(crate) seed: [u8; 8],
+    // 拥有此配置账户权限的地址（32 字节）
+    pub(crate) authority: Address,
+    // 第一个代币的 mint 账户地址（32 字节）
+    pub(crate) mint_x: Address,
+    // 第二个代币的 mint 账户地址（32 字节）
+    pub(crate) mint_y: Address,
+    // 手续费比率，存储为 2 字节数组（可能是 bps 或其他费率表示）
+    pub(crate) fee: [u8;2],
+    // 配置账户的 PDA bump seed，用于验证地址派生
+    pub(crate) config_bump: [u8;1],
+
+    // ---- 以下字段从版本 1 开始加入，版本 0 的账户需要先执行 MigrateConfig ----
+
+    // 待接受的新 authority（两步转移权限），全 0 表示没有待处理的提议
+    pub(crate) pending_authority: Address,
+    // LP mint 的 PDA bump seed，种子为 [b"mint_lp", config]
+    pub(crate) lp_bump: [u8;1],
+    // 布局版本号，版本 0 的账户没有这个字节，只能通过数据长度识别
+    pub(crate) version: u8,
+    // 协议费占交换手续费的比例（bps），0 表示手续费全部归 LP
+    pub(crate) protocol_fee: [u8;2],
+    // 协议费接收者，CollectProtocolFees 将协议费转入它在两个 mint 下的 ATA
+    pub(crate) fee_recipient: Address,
+    // 已累计但尚未领取的协议费，仍存放在金库中，但不计入池子储备
+    pub(crate) protocol_fees_x: [u8;8],
+    pub(crate) protocol_fees_y: [u8;8],
+
+    // ---- 以下字段从版本 2 开始加入 ----
+
+    // TWAP 累计价格（Q64.64 价格 × 秒），见 crate::oracle
+    pub(crate) price_x_cumulative: [u8;16],
+    pub(crate) price_y_cumulative: [u8;16],
+    // 最后一次推进累计价格的 unix 时间戳
+    pub(crate) last_update_timestamp: [u8;8],
+
+    // ---- 以下字段从版本 3 开始加入 ----
+
+    // 交换曲线类型，存储 CurveType 枚举的 u8 值
+    pub(crate) curve_type: u8,
+    // StableSwap 放大系数在 [ramp_start, ramp_end] 之间从 amp_initial 线性过渡到 amp_target
+    // 恒定乘积池子不使用这些字段
+    pub(crate) amp_initial: [u8;8],
+    pub(crate) amp_target: [u8;8],
+    pub(crate) ramp_start: [u8;8],
+    pub(crate) ramp_end: [u8;8],
+}
+
+// 使用 u8 内存布局的枚举，确保每个枚举值只占 1 字节
+// 这样可以直接与 Config 结构体中的 state: u8 字段对应
+#[repr(u8)]
+pub enum AmmState {
+    // 未初始化状态，账户刚创建但未设置
+    Uninitialized = 0u8,
+    // 已初始化状态，AMM 正常运行
+    Initialized = 1u8,
+    // 已禁用状态，暂停所有操作
+    Disabled = 2u8,
+    // 仅提现状态，允许流动性移除但不允许添加或交易
+    WithdrawOnly = 3u8,
+}
+
+// 池子的交换曲线，在 Initialize 时选择，之后不能修改
+#[repr(u8)]
+pub enum CurveType {
+    // 恒定乘积 x * y = k
+    ConstantProduct = 0u8,
+    // StableSwap，适合锚定资产
+    StableSwap = 1u8,
+}
+
+impl Config {
+    // 常量 LEN：在编译时计算 Config 结构体的字节大小
+    // 用于验证账户数据长度是否正确
+    pub const LEN: usize = size_of::<Config>();
+
+    // 当前的布局版本
+    pub const VERSION: u8 = 3;
+
+    // 版本 0 的布局长度：最初发布的程序创建的池子，没有 pending_authority、lp_bump 和 version
+    pub const LEN_V0: usize = core::mem::offset_of!(Config, pending_authority);
+
+    // 版本字节的偏移量，版本 1 及之后的布局都包含这个字节
+    pub const VERSION_OFFSET: usize = core::mem::offset_of!(Config, version);
+
+    // 版本 1 的布局长度：TWAP 字段之前的所有字段
+    pub const LEN_V1: usize = core::mem::offset_of!(Config, price_x_cumulative);
+
+    // 版本 2 的布局长度：曲线字段之前的所有字段
+    pub const LEN_V2: usize = core::mem::offset_of!(Config, curve_type);
+
+    // 根据数据长度和版本字节识别账户的布局版本，无法识别时返回 None
+    // 版本 0 没有版本字节，只能通过长度识别
+    pub fn version_of(data: &[u8]) -> Option<u8> {
+        let version = match data.len() {
+            Self::LEN_V0 => return Some(0),
+            Self::LEN_V1 => 1,
+            Self::LEN_V2 => 2,
+            Self::LEN => Self::VERSION,
+            _ => return None,
+        };
+        if data[Self::VERSION_OFFSET] != version {
+            return None;
+        }
+        Some(version)
+    }
+
+    // 按当前布局解释账户数据，长度或版本不匹配时返回 None
+    // Config 只包含字节数组和 Address，对齐为 1，可以直接转换
+    #[inline(always)]
+    pub fn from_bytes(data: &[u8]) -> Option<&Self> {
+        if data.len() != Self::LEN {
+            return None;
+        }
+        let config = unsafe { Self::from_bytes_unchecked(data) };
+        if config.version != Self::VERSION {
+            return None;
+        }
+        Some(config)
+    }
+
+    /// 核心：将不可变字节切片转换为 Config 引用（零拷贝）
+    /// 这就是"为什么这样读取数据"的关键
+    ///
+    /// # Safety
+    ///
+    /// bytes 的长度必须不小于 Config::LEN，并且在引用存在期间没有可变别名
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked( bytes: &[u8]) -> &Self {
+        // 获取字节切片的指针，将其重新解释为 Config 类型的常量指针，然后解引用
+        // &* 创建引用，避免实际解引用的开销
+        &*(bytes.as_ptr() as *const Config)
+    }
+
+    /// 将可变字节切片转换为可变 Config 引用（零拷贝）
+    /// 允许直接修改账户数据
+    ///
+    /// # Safety
+    ///
+    /// bytes 的长度必须不小于 Config::LEN
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut( bytes: &mut [u8]) -> &mut Self {
+        // 同上，但使用可变指针
+        &mut *(bytes.as_mut_ptr() as *mut Config)
+    }
+
+    // Getter：返回 AMM 状态值
+    #[inline(always)]
+    pub fn state(&self) -> u8 {
+        self.state
+    }
+
+    // Getter：返回 seed 的 u64 表示（从小端字节数组转换）
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    // Getter：返回权限管理员的地址引用
+    #[inline(always)]
+    pub fn authority(&self) -> &Address {
+        &self.authority
+    }
+
+    // Getter：返回第一个代币的 mint 地址
+    #[inline(always)]
+    pub fn mint_x(&self) -> &Address {
+        &self.mint_x
+    }
+
+    // Getter：返回第二个代币的 mint 地址
+    #[inline(always)]
+    pub fn mint_y(&self) -> &Address {
+        &self.mint_y
+    }
+
+    // Getter：返回手续费字节数组引用
+    #[inline(always)]
+    pub fn fee(&self) -> &[u8;2] {
+        &self.fee
+    }
+
+    // Getter：返回配置账户的 bump seed
+    #[inline(always)]
+    pub fn config_bump(&self) -> &[u8;1] {
+        &self.config_bump
+    }
+
+    // Getter：返回 LP mint 的 bump seed
+    #[inline(always)]
+    pub fn lp_bump(&self) -> &[u8;1] {
+        &self.lp_bump
+    }
+
+    // Getter：返回待接受的新 authority 地址
+    #[inline(always)]
+    pub fn pending_authority(&self) -> &Address {
+        &self.pending_authority
+    }
+
+    // Getter：返回布局版本号
+    #[inline(always)]
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    // Getter：返回协议费比例（交换手续费的 bps）
+    #[inline(always)]
+    pub fn protocol_fee(&self) -> u16 {
+        u16::from_le_bytes(self.protocol_fee)
+    }
+
+    // Getter：返回协议费接收者
+    #[inline(always)]
+    pub fn fee_recipient(&self) -> &Address {
+        &self.fee_recipient
+    }
+
+    // Getter：返回已累计的 X 侧协议费
+    #[inline(always)]
+    pub fn protocol_fees_x(&self) -> u64 {
+        u64::from_le_bytes(self.protocol_fees_x)
+    }
+
+    // Getter：返回已累计的 Y 侧协议费
+    #[inline(always)]
+    pub fn protocol_fees_y(&self) -> u64 {
+        u64::from_le_bytes(self.protocol_fees_y)
+    }
+
+    // Getter：返回曲线类型
+    #[inline(always)]
+    pub fn curve_type(&self) -> u8 {
+        self.curve_type
+    }
+
+    // Getter：返回放大系数调整的起点和终点
+    #[inline(always)]
+    pub fn amp_initial(&self) -> u64 {
+        u64::from_le_bytes(self.amp_initial)
+    }
+
+    #[inline(always)]
+    pub fn amp_target(&self) -> u64 {
+        u64::from_le_bytes(self.amp_target)
+    }
+
+    #[inline(always)]
+    pub fn ramp_start(&self) -> i64 {
+        i64::from_le_bytes(self.ramp_start)
+    }
+
+    #[inline(always)]
+    pub fn ramp_end(&self) -> i64 {
+        i64::from_le_bytes(self.ramp_end)
+    }
+
+    // Getter：返回 TWAP 累计价格和最后一次更新的时间戳
+    #[inline(always)]
+    pub fn price_x_cumulative(&self) -> u128 {
+        u128::from_le_bytes(self.price_x_cumulative)
+    }
+
+    #[inline(always)]
+    pub fn price_y_cumulative(&self) -> u128 {
+        u128::from_le_bytes(self.price_y_cumulative)
+    }
+
+    #[inline(always)]
+    pub fn last_update_timestamp(&self) -> i64 {
+        i64::from_le_bytes(self.last_update_timestamp)
+    }
+
+    #[inline(always)]
+    pub fn has_authority(&self) -> Option<Address> {
+        let auth = unsafe {core::ptr::addr_of!(self.authority).read_unaligned()};
+
+        if auth == Address::default(){
+            None
+        }else {
+            Some(auth)
+        }
+    }
+
+    // 返回待接受的新 authority，全 0 表示当前没有转移提议
+    #[inline(always)]
+    pub fn has_pending_authority(&self) -> Option<Address> {
+        let pending = unsafe {core::ptr::addr_of!(self.pending_authority).read_unaligned()};
+
+        if pending == Address::default(){
+            None
+        }else {
+            Some(pending)
+        }
+    }
+
+    // 返回协议费接收者，全 0 表示尚未设置
+    #[inline(always)]
+    pub fn has_fee_recipient(&self) -> Option<Address> {
+        let recipient = unsafe {core::ptr::addr_of!(self.fee_recipient).read_unaligned()};
+
+        if recipient == Address::default(){
+            None
+        }else {
+            Some(recipient)
+        }
+    }
+}
+
+// =============================================================================
+// 池子注册表
+// =============================================================================
+// Config 的地址由 seed 决定，不知道 seed 就找不到池子
+// 注册表为每个交易对保存一条 PairRecord，记录这个交易对下注册过的所有池子，
+// 路由只需要按两个 mint 派生 PairRecord 的地址，读取一个账户就能列出全部池子
+//
+// 交易对的两个 mint 必须按字节序排列（见 is_canonical），同一个交易对只有一条记录
+// Initialize 创建池子时同时注册，注册表必须在创建第一个池子之前由程序的升级权限创建
+
+// 两个 mint 是否按规范顺序排列：mint_x 按字节序小于 mint_y
+#[inline(always)]
+pub fn is_canonical(mint_x: &Address, mint_y: &Address) -> bool {
+    mint_x.as_ref() < mint_y.as_ref()
+}
+
+#[repr(C)]
+pub struct Registry {
+    // 可以修改注册规则的地址
+    pub(crate) authority: Address,
+    // 非 0 时同一个交易对的每个手续费档位只能注册一个池子
+    pub(crate) one_pool_per_fee_tier: u8,
+    pub(crate) bump: [u8;1],
+    // 已注册的交易对和池子数量
+    pub(crate) pair_count: [u8;8],
+    pub(crate) pool_count: [u8;8],
+}
+
+impl Registry {
+    pub const LEN: usize = size_of::<Registry>();
+
+    #[inline(always)]
+    pub fn from_bytes(data: &[u8]) -> Option<&Self> {
+        if data.len() != Self::LEN {
+            return None;
+        }
+        Some(unsafe { Self::from_bytes_unchecked(data) })
+    }
+
+    /// Registry 只包含字节数组和 Address，对齐为 1，可以直接转换
+    ///
+    /// # Safety
+    ///
+    /// bytes 的长度必须不小于 Registry::LEN
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const Registry)
+    }
+
+    /// # Safety
+    ///
+    /// bytes 的长度必须不小于 Registry::LEN
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut Registry)
+    }
+
+    #[inline(always)]
+    pub fn authority(&self) -> &Address {
+        &self.authority
+    }
+
+    #[inline(always)]
+    pub fn one_pool_per_fee_tier(&self) -> bool {
+        self.one_pool_per_fee_tier != 0
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> &[u8;1] {
+        &self.bump
+    }
+
+    #[inline(always)]
+    pub fn pair_count(&self) -> u64 {
+        u64::from_le_bytes(self.pair_count)
+    }
+
+    #[inline(always)]
+    pub fn pool_count(&self) -> u64 {
+        u64::from_le_bytes(self.pool_count)
+    }
+}
+
+// 交易对记录：固定的头部之后是按注册顺序排列的 PoolEntry，每注册一个池子账户扩容一个 PoolEntry
+#[repr(C)]
+pub struct PairRecord {
+    pub(crate) mint_x: Address,
+    pub(crate) mint_y: Address,
+    pub(crate) bump: [u8;1],
+}
+
+// 交易对下的一个池子，创建池子时的参数
+#[repr(C)]
+pub struct PoolEntry {
+    pub(crate) config: Address,
+    pub(crate) seed: [u8;8],
+    // 池子当前的手续费（bps），UpdateFee 修改手续费时同步更新
+    pub(crate) fee_tier: [u8;2],
+    // CurveType 的 u8 值
+    pub(crate) curve_type: u8,
+}
+
+impl PairRecord {
+    // 头部长度，即还没有池子时的账户长度
+    pub const LEN: usize = size_of::<PairRecord>();
+
+    // 包含 pools 个池子时的账户长度
+    #[inline(always)]
+    pub const fn len_with(pools: usize) -> usize {
+        Self::LEN + pools * PoolEntry::LEN
+    }
+
+    // 解析头部和其后的池子列表，长度不是 len_with(n) 时返回 None
+    #[inline(always)]
+    pub fn from_bytes(data: &[u8]) -> Option<(&Self, &[PoolEntry])> {
+        if data.len() < Self::LEN || !(data.len() - Self::LEN).is_multiple_of(PoolEntry::LEN) {
+            return None;
+        }
+        Some(unsafe { Self::from_bytes_unchecked(data) })
+    }
+
+    /// PairRecord 和 PoolEntry 的对齐都为 1，可以直接转换
+    ///
+    /// # Safety
+    ///
+    /// bytes 的长度必须不小于 PairRecord::LEN，末尾不足一个 PoolEntry 的字节会被忽略
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> (&Self, &[PoolEntry]) {
+        let pools = (bytes.len() - Self::LEN) / PoolEntry::LEN;
+        (
+            &*(bytes.as_ptr() as *const PairRecord),
+            core::slice::from_raw_parts(bytes.as_ptr().add(Self::LEN) as *const PoolEntry, pools),
+        )
+    }
+
+    /// # Safety
+    ///
+    /// bytes 的长度必须不小于 PairRecord::LEN，末尾不足一个 PoolEntry 的字节会被忽略
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> (&mut Self, &mut [PoolEntry]) {
+        let pools = (bytes.len() - Self::LEN) / PoolEntry::LEN;
+        (
+            &mut *(bytes.as_mut_ptr() as *mut PairRecord),
+            core::slice::from_raw_parts_mut(bytes.as_mut_ptr().add(Self::LEN) as *mut PoolEntry, pools),
+        )
+    }
+
+    #[inline(always)]
+    pub fn mint_x(&self) -> &Address {
+        &self.mint_x
+    }
+
+    #[inline(always)]
+    pub fn mint_y(&self) -> &Address {
+        &self.mint_y
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> &[u8;1] {
+        &self.bump
+    }
+}
+
+impl PoolEntry {
+    pub const LEN: usize = size_of::<PoolEntry>();
+
+    #[inline(always)]
+    pub fn config(&self) -> &Address {
+        &self.config
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    #[inline(always)]
+    pub fn fee_tier(&self) -> u16 {
+        u16::from_le_bytes(self.fee_tier)
+    }
+
+    #[inline(always)]
+    pub fn curve_type(&self) -> u8 {
+        self.curve_type
+    }
+}
+
+// 程序拥有的账户没有类型判别器，Config::load 只检查长度和版本字节，
+// 因此 Registry 和任意长度的 PairRecord 都不能与有版本字节的 Config 长度相同
+//
+// 版本 0 的长度由最初发布的程序决定，与只有一个池子的 PairRecord 相同，
+// 只有 MigrateConfig 接受这个长度，并且会先用 Config::check_address 验证 Config PDA
+const _: () = {
+    const fn is_pair_record_len(len: usize) -> bool {
+        len >= PairRecord::LEN && (len - PairRecord::LEN).is_multiple_of(PoolEntry::LEN)
+    }
+    assert!(Config::LEN_V0 == 108);
+    assert!(Config::LEN_V0 != Registry::LEN);
+    let config_lens = [Config::LEN_V1, Config::LEN_V2, Config::LEN];
+    let mut i = 0;
+    while i < config_lens.len() {
+        assert!(config_lens[i] != Registry::LEN && !is_pair_record_len(config_lens[i]));
+        i += 1;
+    }
+    assert!(!is_pair_record_len(Registry::LEN));
+};
+
+// =============================================================================
+// 指令数据
+// =============================================================================
+// 以下结构体都是紧凑排列（对齐为 1，没有填充字节），程序按字节解析，
+// 客户端用 as_bytes 得到完全相同的字节，不包含开头的判别器
+
+// 紧凑排列的结构体没有填充字节，可以直接按字节读取
+#[inline(always)]
+fn bytes_of<T: Copy>(value: &T) -> &[u8] {
+    unsafe { core::slice::from_raw_parts(value as *const T as *const u8, size_of::<T>()) }
+}
+
+// mint_x / mint_y 从账户中读取，config_bump / lp_bump 在链上派生，
+// 客户端只需要提供池子参数
+// 末尾的 authority 和曲线参数可以省略，省略的部分按 0 处理：
+// 没有 authority（不可变池子），使用恒定乘积曲线
+#[repr(C,packed)]
+#[derive(Copy, Clone)]
+pub struct InitializeInstructionData {
+    pub seed: u64,
+    pub fee: u16,
+    pub lp_decimals: u8,
+    pub authority: [u8;32],
+    // CurveType 的 u8 值
+    pub curve_type: u8,
+    // StableSwap 的初始放大系数，恒定乘积池子忽略
+    pub amp: u64,
+}
+
+impl InitializeInstructionData {
+    // 完整长度：包含 authority 和曲线参数
+    pub const LEN: usize = size_of::<Self>();
+    // 省略曲线参数
+    pub const LEN_WITH_AUTHORITY: usize = Self::LEN - size_of::<u8>() - size_of::<u64>();
+    // 同时省略 authority
+    pub const LEN_WITHOUT_AUTHORITY: usize = Self::LEN_WITH_AUTHORITY - size_of::<[u8;32]>();
+
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &[u8] {
+        bytes_of(self)
+    }
+}
+
+// 内存布局: [amount: u64][max_x: u64][max_y: u64][expirations: i64] = 32字节
+#[repr(C, packed)]
+#[derive(Copy, Clone)]
+pub struct DepositInstructionData{
+    pub amount:u64,
+    pub max_x:u64,
+    pub max_y:u64,
+    pub expirations:i64
+}
+
+impl DepositInstructionData {
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &[u8] {
+        bytes_of(self)
+    }
+}
+
+// 使用 C 内存布局 + 紧凑排列，确保结构体在内存中的布局与字节数组完全一致
+// 内存布局: [amount: u64][mint_x: u64][mint_y: u64][expiration: i64] = 32字节
+// 这是直接指针转换反序列化的前提条件
+#[repr(C, packed)]
+#[derive(Copy, Clone)]
+pub struct WithdrawInstructionData{
+    pub amount: u64,
+    pub mint_x: u64,
+    pub mint_y: u64,
+    pub expiration: i64,
+}
+
+impl WithdrawInstructionData {
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &[u8] {
+        bytes_of(self)
+    }
+}
+
+#[repr(C,packed)]
+#[derive(Copy, Clone)]
+pub struct SwapInstructionData {
+    pub is_x: bool,
+    pub amount: u64,
+    pub min: u64,
+    pub expirations: i64,
+}
+
+impl SwapInstructionData {
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &[u8] {
+        bytes_of(self)
+    }
+}
+
+// 精确输出交换："我要正好 amount_out 个 Y，最多花 max_in 个 X"
+#[repr(C,packed)]
+#[derive(Copy, Clone)]
+pub struct SwapExactOutInstructionData {
+    pub is_x: bool,
+    pub amount_out: u64,
+    pub max_in: u64,
+    pub expirations: i64,
+}
+
+impl SwapExactOutInstructionData {
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &[u8] {
+        bytes_of(self)
+    }
+}
+
+// 创建注册表，只有程序的升级权限可以调用，authority 之后可以修改注册规则
+#[repr(C,packed)]
+#[derive(Copy, Clone)]
+pub struct InitializeRegistryInstructionData {
+    pub authority: [u8;32],
+    pub one_pool_per_fee_tier: bool,
+}
+
+impl InitializeRegistryInstructionData {
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &[u8] {
+        bytes_of(self)
+    }
+}
@@ -9,16 +9,23 @@ entrypoint!(process_instruction);
 pub mod instructions;
 pub use instructions::*;
 
+pub mod layout;
+pub use layout::ID;
+
 pub mod state;
-mod errors;
+pub mod curve;
+pub mod oracle;
+pub mod stable;
+pub mod quote;
+pub mod metadata;
+pub mod events;
+pub mod errors;
 
-pub use state::*;
+#[cfg(test)]
+pub mod tests;
 
-// 22222222222222222222222222222222222222222222
-pub const ID: Address = Address::new_from_array([
-    0x0f, 0x1e, 0x6b, 0x14, 0x21, 0xc0, 0x4a, 0x07, 0x04, 0x31, 0x26, 0x5c, 0x19, 0xc5, 0xbb, 0xee,
-    0x19, 0x92, 0xba, 0xe8, 0xaf, 0xd1, 0xcd, 0x07, 0x8e, 0xf8, 0xaf, 0x70, 0x47, 0xdc, 0x11, 0xf7,
-]);
+pub use state::*;
+pub use errors::AmmError;
 
 fn process_instruction(
     _program_id: &Address,
@@ -31,7 +38,46 @@ fn process_instruction(
         }
         Some((Deposit::DISCRIMINATOR, data)) => Deposit::try_from((data, accounts))?.process(),
         Some((Withdraw::DISCRIMINATOR, data)) => Withdraw::try_from((data, accounts))?.process(),
+        Some((DepositSingle::DISCRIMINATOR, data)) => {
+            DepositSingle::try_from((data, accounts))?.process()
+        }
+        Some((WithdrawSingle::DISCRIMINATOR, data)) => {
+            WithdrawSingle::try_from((data, accounts))?.process()
+        }
         Some((Swap::DISCRIMINATOR, data)) => Swap::try_from((data, accounts))?.process(),
+        Some((SwapExactOut::DISCRIMINATOR, data)) => {
+            SwapExactOut::try_from((data, accounts))?.process()
+        }
+        Some((Lock::DISCRIMINATOR, _)) => Lock::try_from(accounts)?.process(),
+        Some((Unlock::DISCRIMINATOR, _)) => Unlock::try_from(accounts)?.process(),
+        Some((SetWithdrawOnly::DISCRIMINATOR, _)) => SetWithdrawOnly::try_from(accounts)?.process(),
+        Some((UpdateFee::DISCRIMINATOR, data)) => UpdateFee::try_from((data, accounts))?.process(),
+        Some((ProposeAuthority::DISCRIMINATOR, data)) => {
+            ProposeAuthority::try_from((data, accounts))?.process()
+        }
+        Some((AcceptAuthority::DISCRIMINATOR, _)) => AcceptAuthority::try_from(accounts)?.process(),
+        Some((SetProtocolFee::DISCRIMINATOR, data)) => {
+            SetProtocolFee::try_from((data, accounts))?.process()
+        }
+        Some((CollectProtocolFees::DISCRIMINATOR, _)) => {
+            CollectProtocolFees::try_from(accounts)?.process()
+        }
+        Some((MigrateConfig::DISCRIMINATOR, _)) => MigrateConfig::try_from(accounts)?.process(),
+        Some((RouteSwap::DISCRIMINATOR, data)) => RouteSwap::try_from((data, accounts))?.process(),
+        Some((RampAmp::DISCRIMINATOR, data)) => RampAmp::try_from((data, accounts))?.process(),
+        Some((StopRampAmp::DISCRIMINATOR, _)) => StopRampAmp::try_from(accounts)?.process(),
+        Some((UpdateLpMetadata::DISCRIMINATOR, data)) => {
+            UpdateLpMetadata::try_from((data, accounts))?.process()
+        }
+        Some((FlashLoan::DISCRIMINATOR, data)) => FlashLoan::try_from((data, accounts))?.process(),
+        Some((InitializeRegistry::DISCRIMINATOR, data)) => {
+            InitializeRegistry::try_from((data, accounts))?.process()
+        }
+        Some((UpdateRegistry::DISCRIMINATOR, data)) => {
+            UpdateRegistry::try_from((data, accounts))?.process()
+        }
+        Some((RegisterPool::DISCRIMINATOR, _)) => RegisterPool::try_from(accounts)?.process(),
+        Some((CreateLpMetadata::DISCRIMINATOR, _)) => CreateLpMetadata::try_from(accounts)?.process(),
         _ => Err(ProgramError::InvalidInstructionData),
     }
 }
\ No newline at end of file
@@ -0,0 +1,275 @@
+// =============================================================================
+// LP 代币元数据（Metaplex Token Metadata）
+// =============================================================================
+// LP Mint 由旧版 Token Program 创建，没有元数据扩展，
+// 钱包通过 Metaplex 的 Metadata 账户显示名称和符号：
+// - 地址：[METADATA_SEED, METADATA_PROGRAM_ID, mint] 在 Metaplex 程序下的 PDA
+// - 名称和符号由交易对的两个 Mint 的 Metaplex 符号决定，没有元数据的 Mint 使用地址的 base58 前缀
+// - mint authority 和 update authority 都是 Config PDA，只有池子的 authority 可以修改 URI
+//
+// 指令数据使用 Borsh 编码，这里手动编码，保持只依赖 core 和 solana_address，
+// 客户端与程序共用
+
+use solana_address::Address;
+
+// metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s
+pub const METADATA_PROGRAM_ID: Address = Address::new_from_array([
+    0x0b, 0x70, 0x65, 0xb1, 0xe3, 0xd1, 0x7c, 0x45, 0x38, 0x9d, 0x52, 0x7f, 0x6b, 0x04, 0xc3, 0xcd,
+    0x58, 0xb8, 0x6c, 0x73, 0x1a, 0xa0, 0xfd, 0xb5, 0x49, 0xb6, 0xd1, 0xbc, 0x03, 0xf8, 0x29, 0x46,
+]);
+
+// Metadata：[METADATA_SEED, METADATA_PROGRAM_ID, mint]
+pub const METADATA_SEED: &[u8] = b"metadata";
+
+// Metaplex 对各字段的长度限制
+pub const MAX_NAME_LEN: usize = 32;
+pub const MAX_SYMBOL_LEN: usize = 10;
+pub const MAX_URI_LEN: usize = 200;
+
+// Metaplex 指令判别器
+pub const CREATE_METADATA_ACCOUNT_V3: u8 = 33;
+pub const UPDATE_METADATA_ACCOUNT_V2: u8 = 15;
+
+// 编码后的指令数据最大长度：
+// [discriminator][Option 标记][DataV2][三个 Option 字段]，DataV2 中的字符串带 u32 长度前缀
+pub const MAX_INSTRUCTION_DATA_LEN: usize =
+    1 + 1 + (4 + MAX_NAME_LEN) + (4 + MAX_SYMBOL_LEN) + (4 + MAX_URI_LEN) + 2 + 3 + 3;
+
+// =============================================================================
+// 名称和符号
+// =============================================================================
+
+// 没有元数据的 Mint 取 base58 地址的前 4 个字符
+const PREFIX_LEN: usize = 4;
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+// 32 字节地址的 base58 编码最多 44 个字符
+const BASE58_MAX_LEN: usize = 44;
+
+// Metaplex Metadata 账户开头的字段：
+// [key = MetadataV1][update_authority][mint][name][symbol][uri]...
+// 字符串带 u32 长度前缀，Metaplex 用 '\0' 把 name / symbol 补齐到最大长度
+const METADATA_V1_KEY: u8 = 4;
+const METADATA_NAME_OFFSET: usize = 1 + 32 + 32;
+
+// 从 Metadata 账户数据中读取名称和符号，去掉末尾补齐的 '\0'
+// 不是 MetadataV1 或字段不完整时返回 None
+pub fn read_name_and_symbol(data: &[u8]) -> Option<(&str, &str)> {
+    if data.first() != Some(&METADATA_V1_KEY) {
+        return None;
+    }
+
+    let (name, rest) = read_string(data.get(METADATA_NAME_OFFSET..)?, MAX_NAME_LEN)?;
+    let (symbol, _) = read_string(rest, MAX_SYMBOL_LEN)?;
+    Some((name, symbol))
+}
+
+// Borsh 字符串：[长度: u32][UTF-8 字节]，返回去掉 '\0' 的字符串和剩余的字节
+fn read_string(data: &[u8], max_len: usize) -> Option<(&str, &[u8])> {
+    let len = u32::from_le_bytes(data.get(..4)?.try_into().ok()?) as usize;
+    if len > max_len {
+        return None;
+    }
+    let bytes = data.get(4..4 + len)?;
+    let value = core::str::from_utf8(bytes).ok()?.trim_end_matches('\0');
+    Some((value, &data[4 + len..]))
+}
+
+// 交易对一侧的标签：Mint 的符号，没有符号时使用 base58 前缀
+// 只保留符号开头的可见 ASCII 字符（不含分隔符 '-'），保证拼接后仍是合法的 UTF-8
+#[derive(Clone, Copy)]
+struct Label {
+    bytes: [u8; MAX_SYMBOL_LEN],
+    len: usize,
+}
+
+impl Label {
+    fn new(mint: &Address, symbol: Option<&str>) -> Self {
+        let mut bytes = [0u8; MAX_SYMBOL_LEN];
+        let mut len = 0;
+        for byte in symbol.unwrap_or_default().bytes().take(MAX_SYMBOL_LEN) {
+            if !byte.is_ascii_graphic() || byte == b'-' {
+                break;
+            }
+            bytes[len] = byte;
+            len += 1;
+        }
+
+        if len == 0 {
+            bytes[..PREFIX_LEN].copy_from_slice(&base58_prefix(mint));
+            len = PREFIX_LEN;
+        }
+        Self { bytes, len }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+
+    // 符号放不下两个完整标签时，每个标签最多保留 PREFIX_LEN 个字符
+    fn short(&self) -> &[u8] {
+        &self.bytes[..self.len.min(PREFIX_LEN)]
+    }
+}
+
+// LP 代币的名称和符号
+// 名称："X-Y LP"，符号："X-Y"，X / Y 为两个 Mint 的符号（见 Label）
+// 符号超过 MAX_SYMBOL_LEN 时 X / Y 各保留前 4 个字符
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LpTokenName {
+    name: [u8; MAX_NAME_LEN],
+    name_len: usize,
+    symbol: [u8; MAX_SYMBOL_LEN],
+    symbol_len: usize,
+}
+
+impl LpTokenName {
+    // symbol_x / symbol_y 为两个 Mint 的 Metaplex 符号，Mint 没有元数据时传入 None
+    pub fn new(mint_x: &Address, symbol_x: Option<&str>, mint_y: &Address, symbol_y: Option<&str>) -> Self {
+        let x = Label::new(mint_x, symbol_x);
+        let y = Label::new(mint_y, symbol_y);
+
+        let mut lp = Self {
+            name: [0u8; MAX_NAME_LEN],
+            name_len: 0,
+            symbol: [0u8; MAX_SYMBOL_LEN],
+            symbol_len: 0,
+        };
+
+        // 两个标签最多 MAX_SYMBOL_LEN 个字符，名称一定不超过 MAX_NAME_LEN
+        for part in [x.as_bytes(), b"-", y.as_bytes(), b" LP"] {
+            lp.name[lp.name_len..lp.name_len + part.len()].copy_from_slice(part);
+            lp.name_len += part.len();
+        }
+
+        let (x, y) = if x.len + 1 + y.len <= MAX_SYMBOL_LEN {
+            (x.as_bytes(), y.as_bytes())
+        } else {
+            (x.short(), y.short())
+        };
+        for part in [x, b"-", y] {
+            lp.symbol[lp.symbol_len..lp.symbol_len + part.len()].copy_from_slice(part);
+            lp.symbol_len += part.len();
+        }
+        lp
+    }
+
+    // 读取已有的 LP Metadata 中的名称和符号，更新 URI 时保持不变
+    pub fn from_metadata(data: &[u8]) -> Option<Self> {
+        let (name, symbol) = read_name_and_symbol(data)?;
+
+        let mut lp = Self {
+            name: [0u8; MAX_NAME_LEN],
+            name_len: name.len(),
+            symbol: [0u8; MAX_SYMBOL_LEN],
+            symbol_len: symbol.len(),
+        };
+        lp.name[..name.len()].copy_from_slice(name.as_bytes());
+        lp.symbol[..symbol.len()].copy_from_slice(symbol.as_bytes());
+        Some(lp)
+    }
+
+    pub fn name(&self) -> &str {
+        // 由 ASCII 标签拼接或从合法的 UTF-8 字符串复制，一定是合法的 UTF-8
+        core::str::from_utf8(&self.name[..self.name_len]).unwrap()
+    }
+
+    pub fn symbol(&self) -> &str {
+        core::str::from_utf8(&self.symbol[..self.symbol_len]).unwrap()
+    }
+}
+
+// 地址 base58 编码的前 PREFIX_LEN 个字符
+fn base58_prefix(address: &Address) -> [u8; PREFIX_LEN] {
+    let bytes = address.to_bytes();
+
+    // 按大端序把 32 字节看作一个整数，反复除以 58，余数为从低位开始的各位数字
+    let mut number = bytes;
+    let mut digits = [0u8; BASE58_MAX_LEN];
+    let mut len = 0;
+    let mut start = bytes.iter().take_while(|byte| **byte == 0).count();
+    let leading_zeros = start;
+
+    while start < number.len() {
+        let mut remainder = 0u32;
+        for byte in number[start..].iter_mut() {
+            let value = (remainder << 8) | *byte as u32;
+            *byte = (value / 58) as u8;
+            remainder = value % 58;
+        }
+        digits[len] = BASE58_ALPHABET[remainder as usize];
+        len += 1;
+
+        while start < number.len() && number[start] == 0 {
+            start += 1;
+        }
+    }
+
+    // 每个前导零字节编码为一个 '1'，之后是从高位开始的数字
+    let mut prefix = [BASE58_ALPHABET[0]; PREFIX_LEN];
+    for (slot, digit) in prefix.iter_mut().skip(leading_zeros).zip(digits[..len].iter().rev()) {
+        *slot = *digit;
+    }
+    prefix
+}
+
+// =============================================================================
+// 指令数据编码
+// =============================================================================
+
+struct Writer<'a> {
+    buffer: &'a mut [u8],
+    len: usize,
+}
+
+impl Writer<'_> {
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.buffer[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    // Borsh 字符串：[长度: u32][UTF-8 字节]
+    fn string(&mut self, value: &str) {
+        self.bytes(&(value.len() as u32).to_le_bytes());
+        self.bytes(value.as_bytes());
+    }
+
+    // DataV2：name, symbol, uri, seller_fee_basis_points = 0，
+    // creators / collection / uses 都为 None
+    fn data_v2(&mut self, name: &LpTokenName, uri: &str) {
+        self.string(name.name());
+        self.string(name.symbol());
+        self.string(uri);
+        self.bytes(&0u16.to_le_bytes());
+        self.bytes(&[0, 0, 0]);
+    }
+}
+
+// CreateMetadataAccountV3：[33][DataV2][is_mutable = true][collection_details = None]
+// 返回写入 buffer 的长度；uri 超过 MAX_URI_LEN 或 buffer 不足 MAX_INSTRUCTION_DATA_LEN 时返回 None
+pub fn create_metadata_data(name: &LpTokenName, uri: &str, buffer: &mut [u8]) -> Option<usize> {
+    if uri.len() > MAX_URI_LEN || buffer.len() < MAX_INSTRUCTION_DATA_LEN {
+        return None;
+    }
+
+    let mut writer = Writer { buffer, len: 0 };
+    writer.bytes(&[CREATE_METADATA_ACCOUNT_V3]);
+    writer.data_v2(name, uri);
+    writer.bytes(&[1, 0]);
+    Some(writer.len)
+}
+
+// UpdateMetadataAccountV2：[15][Some(DataV2)][update_authority = None][primary_sale_happened = None][is_mutable = None]
+// Metaplex 用新的 DataV2 整体替换，因此名称和符号需要一起写入
+pub fn update_metadata_data(name: &LpTokenName, uri: &str, buffer: &mut [u8]) -> Option<usize> {
+    if uri.len() > MAX_URI_LEN || buffer.len() < MAX_INSTRUCTION_DATA_LEN {
+        return None;
+    }
+
+    let mut writer = Writer { buffer, len: 0 };
+    writer.bytes(&[UPDATE_METADATA_ACCOUNT_V2, 1]);
+    writer.data_v2(name, uri);
+    writer.bytes(&[0, 0, 0]);
+    Some(writer.len)
+}
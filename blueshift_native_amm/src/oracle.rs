@@ -0,0 +1,111 @@
+// =============================================================================
+// 时间加权平均价格 (TWAP)
+// =============================================================================
+// 与 Uniswap v2 相同：池子记录价格对时间的累计值，
+// 每次交换 / 存款 / 取款前，用操作前的储备把累计值推进到当前时间
+// 任意两次观测之间的累计值之差除以经过的时间，就是这段时间的平均价格
+//
+// 单笔交易只能影响它之后的价格，想要操纵 TWAP 需要在整个窗口内持续维持偏离的价格
+//
+// 价格使用 Q64.64 定点数：恒定乘积池子 price = (quote << 64) / base，
+// StableSwap 池子使用曲线的边际价格（见 stable::spot_price），平衡附近的价格接近 1 而不是储备之比
+// 累计值允许回绕（wrapping），只要观测窗口内回绕不超过一次，差值仍然正确
+//
+// 只依赖 core，借贷等其他程序可以直接使用
+
+use crate::curve::Curve;
+use crate::stable;
+
+// Q64.64 定点数的小数位数
+pub const PRICE_FRACTIONAL_BITS: u32 = 64;
+
+// 某一时刻的累计价格
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Observation {
+    // 以 Y 计价的 X 价格（Y / X）对时间的累计值
+    pub price_x_cumulative: u128,
+    // 以 X 计价的 Y 价格（X / Y）对时间的累计值
+    pub price_y_cumulative: u128,
+    // 最后一次推进累计值的 unix 时间戳，0 表示还没有观测
+    pub timestamp: i64,
+}
+
+// 两次观测之间的平均价格（Q64.64）
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TwapPrice {
+    pub price_x: u128,
+    pub price_y: u128,
+}
+
+// 现货价格：1 个 base 值多少 quote（Q64.64）
+// 任一储备为 0 时池子没有价格
+#[inline(always)]
+pub fn spot_price(reserve_base: u64, reserve_quote: u64) -> Option<u128> {
+    if reserve_base == 0 || reserve_quote == 0 {
+        return None;
+    }
+    // reserve_quote < 2^64，左移 64 位后不会超出 u128
+    Some(((reserve_quote as u128) << PRICE_FRACTIONAL_BITS) / reserve_base as u128)
+}
+
+// 按池子的曲线计算现货价格：1 个 base 值多少 quote（Q64.64）
+// 任一储备为 0，或者 StableSwap 的边际价格超出 u128 时没有价格
+#[inline(always)]
+pub fn curve_spot_price(curve: Curve, reserve_base: u64, reserve_quote: u64) -> Option<u128> {
+    match curve {
+        Curve::ConstantProduct => spot_price(reserve_base, reserve_quote),
+        Curve::StableSwap { amp } => stable::spot_price(amp, reserve_base, reserve_quote).ok(),
+    }
+}
+
+impl Observation {
+    // 假设从上次观测到 timestamp 之间储备一直是 (reserve_x, reserve_y)、曲线一直是 curve，推进累计值
+    // - 第一次观测只记录时间
+    // - 时间没有前进时保持不变
+    // - 空池子没有价格，只推进时间
+    pub fn advance(&self, curve: Curve, reserve_x: u64, reserve_y: u64, timestamp: i64) -> Self {
+        if self.timestamp == 0 {
+            return Self {
+                timestamp,
+                ..*self
+            };
+        }
+        if timestamp <= self.timestamp {
+            return *self;
+        }
+
+        let elapsed = (timestamp - self.timestamp) as u128;
+        let mut next = Self {
+            timestamp,
+            ..*self
+        };
+
+        if let (Some(price_x), Some(price_y)) = (
+            curve_spot_price(curve, reserve_x, reserve_y),
+            curve_spot_price(curve, reserve_y, reserve_x),
+        ) {
+            next.price_x_cumulative = self
+                .price_x_cumulative
+                .wrapping_add(price_x.wrapping_mul(elapsed));
+            next.price_y_cumulative = self
+                .price_y_cumulative
+                .wrapping_add(price_y.wrapping_mul(elapsed));
+        }
+
+        next
+    }
+}
+
+// 计算 start 到 end 之间的平均价格
+// start 必须是有效观测且早于 end
+pub fn twap(start: &Observation, end: &Observation) -> Option<TwapPrice> {
+    if start.timestamp == 0 || end.timestamp <= start.timestamp {
+        return None;
+    }
+
+    let elapsed = (end.timestamp - start.timestamp) as u128;
+    Some(TwapPrice {
+        price_x: end.price_x_cumulative.wrapping_sub(start.price_x_cumulative) / elapsed,
+        price_y: end.price_y_cumulative.wrapping_sub(start.price_y_cumulative) / elapsed,
+    })
+}
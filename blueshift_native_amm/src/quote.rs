@@ -0,0 +1,140 @@
+// =============================================================================
+// 报价
+// =============================================================================
+// 发送交易之前在链下计算指令的结果，与链上使用完全相同的曲线函数和取整：
+// - 交换：Swap::process 中的 Curve::swap_exact_in 和 curve::protocol_fee
+// - 存款：Deposit::process 中的 curve::deposit_amounts，首次存款为 curve::initial_liquidity
+// - 取款：Withdraw::process 中的 curve::withdraw_amounts
+// - 闪电贷：FlashLoan::process 中的 curve::flash_loan_fee
+//
+// 储备是金库余额减去尚未领取的协议费（见 pool_reserves），
+// StableSwap 的放大系数按交易执行时刻计算（见 pool_curve）
+//
+// 不包含 Token-2022 TransferFee：带该扩展的 Mint 实际到账 / 需要转出的数量
+// 需要调用者另外按 Mint 的费率换算
+//
+// 只依赖 core，链下客户端可以直接使用
+
+use crate::curve::{self, Curve, CurveError, LiquidityAmounts, FEE_DENOMINATOR};
+use crate::layout::{Config, CurveType};
+use crate::{oracle, stable};
+
+// 精确输入交换的报价
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SwapQuote {
+    // 转入金库的数量（包含手续费）
+    pub amount_in: u64,
+    // 用户收到的数量
+    pub amount_out: u64,
+    // 手续费总额（以输入代币计）
+    pub fee: u64,
+    // 手续费中归协议所有的部分，其余留在池子里归 LP
+    pub protocol_fee: u64,
+    // 价格影响（基点）：实际输出相对于按交换前边际价格计算的输出少了多少，不含手续费
+    pub price_impact_bps: u16,
+}
+
+// 金库余额中属于 LP 的部分，与链上 Config::reserves 相同
+// 金库余额少于未领取的协议费时账户数据不一致，返回 None
+pub fn pool_reserves(config: &Config, vault_x_amount: u64, vault_y_amount: u64) -> Option<(u64, u64)> {
+    Some((
+        vault_x_amount.checked_sub(config.protocol_fees_x())?,
+        vault_y_amount.checked_sub(config.protocol_fees_y())?,
+    ))
+}
+
+// timestamp 时刻交换使用的曲线，与链上 Config::curve 相同
+// 未知的曲线类型返回 None
+pub fn pool_curve(config: &Config, timestamp: i64) -> Option<Curve> {
+    match config.curve_type() {
+        t if t == CurveType::ConstantProduct as u8 => Some(Curve::ConstantProduct),
+        t if t == CurveType::StableSwap as u8 => Some(Curve::StableSwap {
+            amp: stable::current_amp(
+                config.amp_initial(),
+                config.amp_target(),
+                config.ramp_start(),
+                config.ramp_end(),
+                timestamp,
+            ),
+        }),
+        _ => None,
+    }
+}
+
+// 交换前的边际价格：1 个输入代币可以换到多少输出代币（Q64.64，不含手续费）
+pub fn spot_price(curve: Curve, reserve_in: u64, reserve_out: u64) -> Result<u128, CurveError> {
+    match curve {
+        Curve::ConstantProduct => {
+            oracle::spot_price(reserve_in, reserve_out).ok_or(CurveError::ZeroLiquidity)
+        }
+        Curve::StableSwap { amp } => stable::spot_price(amp, reserve_in, reserve_out),
+    }
+}
+
+// 精确输入交换的报价
+// fee 和 protocol_fee 以基点表示，即 Config 中的 fee 和 protocol_fee
+pub fn quote_swap_exact_in(
+    curve: Curve,
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    fee: u16,
+    protocol_fee: u16,
+) -> Result<SwapQuote, CurveError> {
+    let result = curve.swap_exact_in(reserve_in, reserve_out, amount_in, fee)?;
+    let protocol_fee = curve::protocol_fee(result.fee, protocol_fee)?;
+
+    // 按边际价格，扣除手续费后的输入应得到的输出
+    let price = spot_price(curve, reserve_in, reserve_out)?;
+    let ideal_out = ((result.amount_in - result.fee) as u128)
+        .checked_mul(price)
+        .map(|value| value >> oracle::PRICE_FRACTIONAL_BITS)
+        .ok_or(CurveError::Overflow)?;
+
+    // 取整可能让实际输出略高于理想输出，此时价格影响为 0
+    let price_impact_bps = match ideal_out.checked_sub(result.amount_out as u128) {
+        Some(shortfall) if ideal_out > 0 => {
+            (shortfall * FEE_DENOMINATOR as u128).div_ceil(ideal_out) as u16
+        }
+        _ => 0,
+    };
+
+    Ok(SwapQuote {
+        amount_in: result.amount_in,
+        amount_out: result.amount_out,
+        fee: result.fee,
+        protocol_fee,
+        price_impact_bps,
+    })
+}
+
+// 铸造 lp 份 LP 需要转入的 (x, y)
+// 首次存款（supply == 0）由用户自行决定存入数量，返回 ZeroLiquidity，见 quote_initial_deposit
+pub fn quote_deposit(
+    reserve_x: u64,
+    reserve_y: u64,
+    supply: u64,
+    lp: u64,
+) -> Result<LiquidityAmounts, CurveError> {
+    curve::deposit_amounts(reserve_x, reserve_y, supply, lp)
+}
+
+// 首次存款 (x, y) 铸造给用户的 LP：sqrt(x * y) 减去永久锁定的 MINIMUM_LIQUIDITY
+pub fn quote_initial_deposit(x: u64, y: u64) -> Result<u64, CurveError> {
+    curve::initial_liquidity(x, y)
+}
+
+// 销毁 lp 份 LP 可以取回的 (x, y)，取出全部 LP 时返回全部储备
+pub fn quote_withdraw(
+    reserve_x: u64,
+    reserve_y: u64,
+    supply: u64,
+    lp: u64,
+) -> Result<LiquidityAmounts, CurveError> {
+    curve::withdraw_amounts(reserve_x, reserve_y, supply, lp)
+}
+
+// 借出 amount 需要额外归还的手续费，fee 为 Config 中的交换手续费（基点）
+pub fn quote_flash_loan_fee(amount: u64, fee: u16) -> Result<u64, CurveError> {
+    curve::flash_loan_fee(amount, fee)
+}
@@ -0,0 +1,343 @@
+// =============================================================================
+// StableSwap 曲线（Curve 风格，两种代币）
+// =============================================================================
+// 不变量：A * n^n * (x + y) + D = A * D * n^n + D^(n+1) / (n^n * x * y)，n = 2
+//
+// - A（放大系数）越大，价格在 1:1 附近越平坦，适合锚定资产
+// - A -> 0 时退化为恒定乘积，A -> ∞ 时趋近恒定和
+//
+// D 和另一侧储备都用牛顿迭代求解，中间结果可能超过 u128，
+// 这里用一个只支持乘法和除法的最小 256 位整数，保持只依赖 core
+//
+// 舍入方向与恒定乘积相同，始终对池子有利：
+// - 精确输入：手续费从输入中扣除，输出额外减 1
+// - 精确输出：需要的输入额外加 1，再向上取整加回手续费
+
+use crate::curve::{mul_div_ceil, mul_div_floor, CurveError, SwapResult, FEE_DENOMINATOR};
+
+// 放大系数的取值范围
+pub const MIN_AMP: u64 = 1;
+pub const MAX_AMP: u64 = 1_000_000;
+
+// 调整放大系数时，单次最多放大或缩小的倍数
+pub const MAX_AMP_CHANGE: u64 = 10;
+
+// 调整放大系数的最短持续时间（秒），避免瞬间改变价格
+pub const MIN_RAMP_DURATION: i64 = 86_400;
+
+// 牛顿迭代的最大次数
+const MAX_ITERATIONS: usize = 255;
+
+// n^n，n = 2
+const N_COINS: u128 = 2;
+const N_COINS_POW: u128 = 4;
+
+// =============================================================================
+// 256 位中间结果
+// =============================================================================
+#[derive(Clone, Copy)]
+struct U256 {
+    hi: u128,
+    lo: u128,
+}
+
+impl U256 {
+    // a * b 的完整 256 位乘积
+    fn mul(a: u128, b: u128) -> Self {
+        const MASK: u128 = u64::MAX as u128;
+        let (a_hi, a_lo) = (a >> 64, a & MASK);
+        let (b_hi, b_lo) = (b >> 64, b & MASK);
+
+        let lo_lo = a_lo * b_lo;
+        let hi_lo = a_hi * b_lo;
+        let lo_hi = a_lo * b_hi;
+        let hi_hi = a_hi * b_hi;
+
+        // 中间两项的低 64 位与 lo_lo 的高 64 位相加，最多产生 2 位进位
+        let middle = (lo_lo >> 64) + (hi_lo & MASK) + (lo_hi & MASK);
+
+        Self {
+            hi: hi_hi + (hi_lo >> 64) + (lo_hi >> 64) + (middle >> 64),
+            lo: (middle << 64) | (lo_lo & MASK),
+        }
+    }
+
+    fn checked_add(self, value: u128) -> Option<Self> {
+        let (lo, carry) = self.lo.overflowing_add(value);
+        Some(Self {
+            hi: self.hi.checked_add(carry as u128)?,
+            lo,
+        })
+    }
+
+    // 向下取整的除法，商超过 u128 时返回 None
+    fn checked_div(self, divisor: u128) -> Option<u128> {
+        if divisor == 0 || self.hi >= divisor {
+            return None;
+        }
+        if self.hi == 0 {
+            return Some(self.lo / divisor);
+        }
+
+        // 逐位长除法，余数始终小于 divisor
+        let mut remainder = self.hi;
+        let mut quotient = 0u128;
+        for bit in (0..128).rev() {
+            let carry = remainder >> 127;
+            remainder = (remainder << 1) | ((self.lo >> bit) & 1);
+            if carry == 1 || remainder >= divisor {
+                remainder = remainder.wrapping_sub(divisor);
+                quotient |= 1 << bit;
+            }
+        }
+        Some(quotient)
+    }
+}
+
+// a * b / c，向下取整
+#[inline(always)]
+fn mul_div(a: u128, b: u128, c: u128) -> Result<u128, CurveError> {
+    if c == 0 {
+        return Err(CurveError::ZeroLiquidity);
+    }
+    U256::mul(a, b).checked_div(c).ok_or(CurveError::Overflow)
+}
+
+// =============================================================================
+// 不变量
+// =============================================================================
+
+// 计算不变量 D
+pub fn compute_d(amp: u64, reserve_x: u64, reserve_y: u64) -> Result<u128, CurveError> {
+    if reserve_x == 0 || reserve_y == 0 {
+        return Err(CurveError::ZeroLiquidity);
+    }
+    if !(MIN_AMP..=MAX_AMP).contains(&amp) {
+        return Err(CurveError::InvalidAmp);
+    }
+
+    let (x, y) = (reserve_x as u128, reserve_y as u128);
+    let sum = x + y;
+    // D_P 先除以较小的储备，第一次除法的结果更大，向下取整的相对误差更小
+    // 储备极不平衡时，先除以较大的储备会让误差大到 D 在一组相邻的值之间来回跳动，无法收敛
+    let (small, large) = (x.min(y), x.max(y));
+    let ann = amp as u128 * N_COINS_POW;
+
+    // D = (Ann * S + n * D_P) * D / ((Ann - 1) * D + (n + 1) * D_P)
+    // 其中 D_P = D^(n+1) / (n^n * x * y)
+    let mut d = sum;
+    for _ in 0..MAX_ITERATIONS {
+        let d_p = mul_div(mul_div(d, d, small * N_COINS)?, d, large * N_COINS)?;
+
+        let numerator = (ann * sum)
+            .checked_add(d_p.checked_mul(N_COINS).ok_or(CurveError::Overflow)?)
+            .ok_or(CurveError::Overflow)?;
+        let denominator = ((ann - 1) * d)
+            .checked_add(d_p.checked_mul(N_COINS + 1).ok_or(CurveError::Overflow)?)
+            .ok_or(CurveError::Overflow)?;
+
+        let previous = d;
+        d = mul_div(numerator, d, denominator)?;
+        if d.abs_diff(previous) <= 1 {
+            return Ok(d);
+        }
+    }
+
+    Err(CurveError::NotConverged)
+}
+
+// 已知不变量 D 和一侧储备，计算另一侧储备
+pub fn compute_y(amp: u64, reserve_known: u128, d: u128) -> Result<u128, CurveError> {
+    if reserve_known == 0 || d == 0 {
+        return Err(CurveError::ZeroLiquidity);
+    }
+    if !(MIN_AMP..=MAX_AMP).contains(&amp) {
+        return Err(CurveError::InvalidAmp);
+    }
+
+    let ann = amp as u128 * N_COINS_POW;
+
+    // y^2 + (b - D) * y = c
+    // c = D^(n+1) / (n^n * x * Ann)，b = x + D / Ann
+    let c = mul_div(mul_div(d, d, reserve_known * N_COINS)?, d, ann * N_COINS)?;
+    let b = reserve_known.checked_add(d / ann).ok_or(CurveError::Overflow)?;
+
+    // y = (y^2 + c) / (2y + b - D)
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let denominator = (y * 2)
+            .checked_add(b)
+            .and_then(|value| value.checked_sub(d))
+            .filter(|value| *value > 0)
+            .ok_or(CurveError::Overflow)?;
+
+        let previous = y;
+        y = U256::mul(y, y)
+            .checked_add(c)
+            .and_then(|value| value.checked_div(denominator))
+            .ok_or(CurveError::Overflow)?;
+        if y.abs_diff(previous) <= 1 {
+            return Ok(y);
+        }
+    }
+
+    Err(CurveError::NotConverged)
+}
+
+// =============================================================================
+// 交换
+// =============================================================================
+
+// 按精确输入计算交换结果，与 curve::swap_exact_in 的含义相同
+pub fn swap_exact_in(
+    amp: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    fee: u16,
+) -> Result<SwapResult, CurveError> {
+    if fee >= FEE_DENOMINATOR {
+        return Err(CurveError::InvalidFee);
+    }
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(CurveError::ZeroLiquidity);
+    }
+    if amount_in == 0 {
+        return Err(CurveError::ZeroAmount);
+    }
+
+    // 扣除手续费后的有效输入，向下取整
+    let amount_in_after_fee = mul_div_floor(amount_in, (FEE_DENOMINATOR - fee) as u64, FEE_DENOMINATOR as u64)?;
+
+    // 交换后的金库余额必须仍能用 u64 表示
+    reserve_in.checked_add(amount_in).ok_or(CurveError::Overflow)?;
+
+    let d = compute_d(amp, reserve_in, reserve_out)?;
+    let new_reserve_out = compute_y(amp, reserve_in as u128 + amount_in_after_fee as u128, d)?;
+
+    // 牛顿迭代的结果可能有 1 的误差，输出额外减 1
+    let amount_out = (reserve_out as u128)
+        .saturating_sub(new_reserve_out)
+        .saturating_sub(1) as u64;
+    if amount_out == 0 {
+        return Err(CurveError::ZeroAmount);
+    }
+
+    Ok(SwapResult {
+        amount_in,
+        amount_out,
+        fee: amount_in - amount_in_after_fee,
+    })
+}
+
+// 按精确输出计算交换结果，与 curve::swap_exact_out 的含义相同
+pub fn swap_exact_out(
+    amp: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+    fee: u16,
+) -> Result<SwapResult, CurveError> {
+    if fee >= FEE_DENOMINATOR {
+        return Err(CurveError::InvalidFee);
+    }
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(CurveError::ZeroLiquidity);
+    }
+    if amount_out == 0 {
+        return Err(CurveError::ZeroAmount);
+    }
+    if amount_out >= reserve_out {
+        return Err(CurveError::InsufficientLiquidity);
+    }
+
+    let d = compute_d(amp, reserve_in, reserve_out)?;
+    let new_reserve_in = compute_y(amp, (reserve_out - amount_out) as u128, d)?;
+
+    // 牛顿迭代的结果可能有 1 的误差，需要的输入额外加 1
+    let amount_in_after_fee = new_reserve_in
+        .saturating_sub(reserve_in as u128)
+        .checked_add(1)
+        .ok_or(CurveError::Overflow)?;
+    let amount_in_after_fee = u64::try_from(amount_in_after_fee).map_err(|_| CurveError::Overflow)?;
+
+    // 加回手续费：in = in_after_fee * 10_000 / (10_000 - fee)，向上取整
+    let amount_in = mul_div_ceil(
+        amount_in_after_fee,
+        FEE_DENOMINATOR as u64,
+        (FEE_DENOMINATOR - fee) as u64,
+    )?;
+
+    // 交换后的金库余额必须仍能用 u64 表示
+    reserve_in.checked_add(amount_in).ok_or(CurveError::Overflow)?;
+
+    Ok(SwapResult {
+        amount_in,
+        amount_out,
+        fee: amount_in - amount_in_after_fee,
+    })
+}
+
+// 边际价格：当前储备下 1 个输入代币可以换到多少输出代币（Q64.64，不含手续费）
+// 对不变量求偏导，price = -dy/dx = (4·Ann·x²·y² + D³·y) / (4·Ann·x²·y² + D³·x)
+// 分子分母同除以 x·y·D，避免 x²·y² 超出 u128：
+// price = (4·Ann·x·y / D + D² / x) / (4·Ann·x·y / D + D² / y)
+// 储备极端失衡导致 D² / x 超出 u128 时返回 Overflow
+pub fn spot_price(amp: u64, reserve_in: u64, reserve_out: u64) -> Result<u128, CurveError> {
+    let d = compute_d(amp, reserve_in, reserve_out)?;
+    let (x, y) = (reserve_in as u128, reserve_out as u128);
+    let ann = amp as u128 * N_COINS_POW;
+
+    // x, y < 2^64，x * y 不会超出 u128
+    let product_term = mul_div(x * y, ann * N_COINS_POW, d)?;
+    let numerator = product_term
+        .checked_add(mul_div(d, d, x)?)
+        .ok_or(CurveError::Overflow)?;
+    let denominator = product_term
+        .checked_add(mul_div(d, d, y)?)
+        .ok_or(CurveError::Overflow)?;
+
+    mul_div(numerator, 1 << 64, denominator)
+}
+
+// =============================================================================
+// 放大系数调整
+// =============================================================================
+
+// 在 [ramp_start, ramp_end] 之间线性地从 amp_initial 过渡到 amp_target
+pub fn current_amp(
+    amp_initial: u64,
+    amp_target: u64,
+    ramp_start: i64,
+    ramp_end: i64,
+    timestamp: i64,
+) -> u64 {
+    if timestamp >= ramp_end || ramp_end <= ramp_start {
+        return amp_target;
+    }
+    if timestamp <= ramp_start {
+        return amp_initial;
+    }
+
+    let elapsed = (timestamp - ramp_start) as u128;
+    let duration = (ramp_end - ramp_start) as u128;
+    if amp_target >= amp_initial {
+        amp_initial + ((amp_target - amp_initial) as u128 * elapsed / duration) as u64
+    } else {
+        amp_initial - ((amp_initial - amp_target) as u128 * elapsed / duration) as u64
+    }
+}
+
+// 检查一次调整是否合法：目标在范围内，变化倍数不超过 MAX_AMP_CHANGE，持续时间足够
+pub fn check_ramp(current: u64, target: u64, ramp_start: i64, ramp_end: i64) -> Result<(), CurveError> {
+    if !(MIN_AMP..=MAX_AMP).contains(&target) {
+        return Err(CurveError::InvalidAmp);
+    }
+    if ramp_end.saturating_sub(ramp_start) < MIN_RAMP_DURATION {
+        return Err(CurveError::InvalidAmp);
+    }
+    if target > current.saturating_mul(MAX_AMP_CHANGE) || target.saturating_mul(MAX_AMP_CHANGE) < current {
+        return Err(CurveError::InvalidAmp);
+    }
+    Ok(())
+}
@@ -1,5 +1,3 @@
-// 引入 core::mem::size_of 用于在编译时获取类型的大小
-use core::mem::size_of;
 // 引入 pinocchio 框架的核心类型：AccountView（账户视图）、Address（地址类型）
 use pinocchio::{AccountView, Address};
 // 引入 Ref 类型，用于创建对账户数据的引用包装器
@@ -8,51 +6,56 @@ use pinocchio::account::{Ref, RefMut};
 use pinocchio::cpi::Seed;
 // 引入 ProgramError 枚举，定义 Solana 程序的标准错误类型
 use pinocchio::error::ProgramError;
+// 引入 TWAP 观测类型
+use crate::oracle::Observation;
+// 引入交换曲线和 StableSwap 放大系数计算
+use crate::curve::Curve;
+use crate::stable;
+// 引入 AMM 自定义错误
+use crate::errors::AmmError;
 
-// 使用 C 语言内存布局，确保结构体字段按声明顺序在内存中连续排列
-// 这对于将原始字节数组直接解释为结构体至关重要
-#[repr(C)]
-pub struct Config {
-    // AMM 状态字段，存储 AmmState 枚举的 u8 值（0-3）
-    state: u8,
-    // 种子值，用于 PDA（Program Derived Address）派生，存储为 8 字节小端序
-    seed: [u8; 8],
-    // 拥有此配置账户权限的地址（32 字节）
-    authority: Address,
-    // 第一个代币的 mint 账户地址（32 字节）
-    mint_x: Address,
-    // 第二个代币的 mint 账户地址（32 字节）
-    mint_y: Address,
-    // 手续费比率，存储为 2 字节数组（可能是 bps 或其他费率表示）
-    fee: [u8;2],
-    // 配置账户的 PDA bump seed，用于验证地址派生
-    config_bump: [u8;1],
-}
-
-// 使用 u8 内存布局的枚举，确保每个枚举值只占 1 字节
-// 这样可以直接与 Config 结构体中的 state: u8 字段对应
-#[repr(u8)]
-pub enum AmmState {
-    // 未初始化状态，账户刚创建但未设置
-    Uninitialized = 0u8,
-    // 已初始化状态，AMM 正常运行
-    Initialized = 1u8,
-    // 已禁用状态，暂停所有操作
-    Disabled = 2u8,
-    // 仅提现状态，允许流动性移除但不允许添加或交易
-    WithdrawOnly = 3u8,
-}
+// Config 的布局、AmmState 和 CurveType 定义在 layout 模块中，与链下客户端共用
+// 这里只实现依赖 pinocchio 的加载、校验和修改方法
+pub use crate::layout::{AmmState, Config, CurveType, PairRecord, PoolEntry, Registry};
+use crate::layout::{CONFIG_SEED, PAIR_SEED, REGISTRY_SEED};
 
 // Config 结构体的实现块，定义相关方法
 impl Config {
-    // 常量 LEN：在编译时计算 Config 结构体的字节大小
-    // 用于验证账户数据长度是否正确
-    pub const LEN: usize = size_of::<Config>();
+    // 根据数据长度和版本字节识别账户的布局版本
+    // 版本 0 没有版本字节，只能通过长度识别
+    pub fn stored_version(account_info: &AccountView) -> Result<u8, ProgramError> {
+        Self::version_of(&account_info.try_borrow()?).ok_or(ProgramError::InvalidAccountData)
+    }
+
+    // 验证账户地址是用数据中保存的 seed、两个 mint 和 config_bump 派生的 Config PDA
+    // 这些字段在所有版本中的偏移量都相同，可以用于还不能按当前布局解释的旧版本账户
+    // 版本 0 只能通过长度识别，迁移前用这个检查排除长度相同的其他账户
+    pub fn check_address(account_info: &AccountView) -> Result<(), ProgramError> {
+        let data = account_info.try_borrow()?;
+        if data.len() < Self::LEN_V0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let field = |offset: usize, len: usize| &data[offset..offset + len];
+        let address = Address::create_program_address(
+            &[
+                CONFIG_SEED,
+                field(core::mem::offset_of!(Config, seed), 8),
+                field(core::mem::offset_of!(Config, mint_x), 32),
+                field(core::mem::offset_of!(Config, mint_y), 32),
+                field(core::mem::offset_of!(Config, config_bump), 1),
+            ],
+            &crate::ID,
+        )?;
+        if address.ne(account_info.address()) {
+            return Err(AmmError::InvalidAddress.into());
+        }
+        Ok(())
+    }
 
     // 安全地加载 Config 账户数据，返回引用包装器 Ref<Self>
     // 使用内联提示（inline）避免函数调用开销
     #[inline(always)]
-    pub fn load(account_info: &AccountView) -> Result<Ref<Self>, ProgramError> {
+    pub fn load(account_info: &AccountView) -> Result<Ref<'_, Self>, ProgramError> {
         // 验证账户数据长度是否与 Config 结构体大小匹配
         if account_info.data_len() != Self::LEN {
             return Err(ProgramError::InvalidAccountData);
@@ -63,13 +66,22 @@ impl Config {
         }
         // 创建对账户数据的引用映射，零拷贝转换为 Config 引用
         // try_borrow() 获取可变借用检查，然后使用 Ref::map 将字节切片映射为 Config
-        Ok(Ref::map(account_info.try_borrow()?, |data| unsafe {
+        let config = Ref::map(account_info.try_borrow()?, |data| unsafe {
             Self::from_bytes_unchecked(data)
-        }))
+        });
+        // 长度相同但版本不同的布局也不能按当前布局解释
+        if config.version != Self::VERSION {
+            return Err(AmmError::InvalidConfigVersion.into());
+        }
+        Ok(config)
     }
 
-    // 不安全的加载方法，直接获取 &Self 引用而不经过借用检查
-    // 性能更高但需要调用者确保没有可变别名冲突
+    /// 不安全的加载方法，直接获取 &Self 引用而不经过借用检查
+    /// 性能更高但需要调用者确保没有可变别名冲突
+    ///
+    /// # Safety
+    ///
+    /// 返回的引用存在期间，调用者必须保证没有对同一账户数据的可变借用
     #[inline(always)]
     pub unsafe fn load_unchecked(account_info: &AccountView) -> Result<&Self, ProgramError > {
         // 验证账户数据长度
@@ -87,101 +99,238 @@ impl Config {
         ))
     }
 
-    // 核心：将不可变字节切片转换为 Config 引用（零拷贝）
-    // 这就是"为什么这样读取数据"的关键
+    // 存款和交换要求池子处于正常运行状态，其它状态返回对应的错误
+    #[inline(always)]
+    pub fn check_active(&self) -> Result<(), ProgramError> {
+        match self.state {
+            s if s == AmmState::Initialized as u8 => Ok(()),
+            s if s == AmmState::Disabled as u8 => Err(AmmError::PoolDisabled.into()),
+            s if s == AmmState::WithdrawOnly as u8 => Err(AmmError::PoolWithdrawOnly.into()),
+            _ => Err(AmmError::PoolNotInitialized.into()),
+        }
+    }
+
+    // 取款只在池子被禁用时拒绝
+    #[inline(always)]
+    pub fn check_withdrawable(&self) -> Result<(), ProgramError> {
+        if self.state == AmmState::Disabled as u8 {
+            return Err(AmmError::PoolDisabled.into());
+        }
+        Ok(())
+    }
+
+    // 从金库余额中扣除尚未领取的协议费，得到参与曲线计算的池子储备
+    #[inline(always)]
+    pub fn reserves(&self, vault_x_amount: u64, vault_y_amount: u64) -> Result<(u64, u64), ProgramError> {
+        Ok((
+            vault_x_amount
+                .checked_sub(self.protocol_fees_x())
+                .ok_or(ProgramError::InvalidAccountData)?,
+            vault_y_amount
+                .checked_sub(self.protocol_fees_y())
+                .ok_or(ProgramError::InvalidAccountData)?,
+        ))
+    }
+
     #[inline(always)]
-    pub unsafe fn from_bytes_unchecked( bytes: &[u8]) -> &Self {
-        // 获取字节切片的指针，将其重新解释为 Config 类型的常量指针，然后解引用
-        // &* 创建引用，避免实际解引用的开销
-        &*(bytes.as_ptr() as *const Config)
+    pub fn load_mut(account_info: &AccountView) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_info.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        let config = RefMut::map(account_info.try_borrow_mut()?, |data| unsafe {
+            Self::from_bytes_unchecked_mut(data)
+        });
+        if config.version != Self::VERSION {
+            return Err(AmmError::InvalidConfigVersion.into());
+        }
+        Ok(config)
     }
 
-    // 将可变字节切片转换为可变 Config 引用（零拷贝）
-    // 允许直接修改账户数据
+    // 不检查版本的可写加载，只用于写入整个布局的指令：
+    // Initialize 刚创建的账户和 MigrateConfig 刚扩容的账户还没有写入当前版本号
+    // 长度和所有者的检查与 load_mut 相同，借用同样经过运行时检查
     #[inline(always)]
-    pub unsafe fn from_bytes_unchecked_mut( bytes: &mut [u8]) -> &mut Self {
-        // 同上，但使用可变指针
-        &mut *(bytes.as_mut_ptr() as *mut Config)
+    pub fn load_mut_unchecked(account_info: &AccountView) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_info.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(RefMut::map(account_info.try_borrow_mut()?, |data| unsafe {
+            Self::from_bytes_unchecked_mut(data)
+        }))
     }
 
-    // Getter：返回 AMM 状态值
     #[inline(always)]
-    pub fn state(&self) -> u8 {
-        self.state
+    pub fn set_state(&mut self, state: u8) -> Result<(), ProgramError> {
+        if state.gt(&(AmmState::WithdrawOnly as u8)){
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.state = state;
+        Ok(())
     }
 
-    // Getter：返回 seed 的 u64 表示（从小端字节数组转换）
     #[inline(always)]
-    pub fn seed(&self) -> u64 {
-        u64::from_le_bytes(self.seed)
+    pub fn set_fee(&mut self, fee: u16) -> Result<(), ProgramError> {
+        if fee.ge(&10_000){
+            return Err(AmmError::InvalidFee.into());
+        };
+        self.fee = fee.to_le_bytes();
+        Ok(())
     }
 
-    // Getter：返回权限管理员的地址引用
     #[inline(always)]
-    pub fn authority(&self) -> &Address {
-        &self.authority
+    pub fn set_version(&mut self, version: u8) {
+        self.version = version;
     }
 
-    // Getter：返回第一个代币的 mint 地址
+    // 协议费是交换手续费中的一部分，最多 100%
     #[inline(always)]
-    pub fn mint_x(&self) -> &Address {
-        &self.mint_x
+    pub fn set_protocol_fee(&mut self, protocol_fee: u16) -> Result<(), ProgramError> {
+        if protocol_fee.gt(&10_000){
+            return Err(AmmError::InvalidFee.into());
+        };
+        self.protocol_fee = protocol_fee.to_le_bytes();
+        Ok(())
     }
 
-    // Getter：返回第二个代币的 mint 地址
     #[inline(always)]
-    pub fn mint_y(&self) -> &Address {
-        &self.mint_y
+    pub fn set_fee_recipient(&mut self, fee_recipient: Address) {
+        self.fee_recipient = fee_recipient;
     }
 
-    // Getter：返回手续费字节数组引用
+    // 累计一笔协议费，is_x 表示手续费以哪一侧的代币收取
     #[inline(always)]
-    pub fn fee(&self) -> &[u8;2] {
-        &self.fee
+    pub fn accrue_protocol_fee(&mut self, is_x: bool, amount: u64) -> Result<(), ProgramError> {
+        let fees = if is_x {
+            &mut self.protocol_fees_x
+        } else {
+            &mut self.protocol_fees_y
+        };
+        *fees = u64::from_le_bytes(*fees)
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            .to_le_bytes();
+        Ok(())
     }
 
-    // Getter：返回配置账户的 bump seed
+    // 取出全部已累计的协议费并清零，返回 (x, y)
     #[inline(always)]
-    pub fn config_bump(&self) -> &[u8;1] {
-        &self.config_bump
+    pub fn take_protocol_fees(&mut self) -> (u64, u64) {
+        let fees = (self.protocol_fees_x(), self.protocol_fees_y());
+        self.protocol_fees_x = [0u8;8];
+        self.protocol_fees_y = [0u8;8];
+        fees
     }
 
+    // 把 version 之后新增的字段设置为默认值，并写入当前版本号
+    // - 版本 1：没有待接受的 authority，不收取协议费，没有接收者
+    //   lp_bump 需要用账户地址派生，由调用者设置
+    // - 版本 2：还没有 TWAP 观测
+    // - 版本 3：恒定乘积曲线
     #[inline(always)]
-    pub fn load_mut(account_info: &AccountView) -> Result<RefMut<Self>, ProgramError> {
-        if account_info.data_len() != Self::LEN {
-            return Err(ProgramError::InvalidAccountData);
+    pub fn init_fields_since(&mut self, version: u8) {
+        if version < 1 {
+            self.set_pending_authority(Address::default());
+            self.protocol_fee = [0u8;2];
+            self.set_fee_recipient(Address::default());
+            self.protocol_fees_x = [0u8;8];
+            self.protocol_fees_y = [0u8;8];
         }
-        if !account_info.owned_by(&crate::ID) {
-            return Err(ProgramError::InvalidAccountOwner);
+        if version < 2 {
+            self.set_observation(Observation::default());
         }
-        Ok(RefMut::map(account_info.try_borrow_mut()?, |data| unsafe {
-            Self::from_bytes_unchecked_mut(data)
-        }))
+        if version < 3 {
+            self.curve_type = CurveType::ConstantProduct as u8;
+            self.set_amp_ramp(0, 0, 0, 0);
+        }
+        self.set_version(Self::VERSION);
     }
 
+    // 设置曲线类型，StableSwap 需要合法的初始放大系数
     #[inline(always)]
-    pub unsafe fn load_mut_unchecked(account_info: &AccountView) -> Result<&mut Self, ProgramError > {
-        if account_info.data_len() != Self::LEN {
-            return Err(ProgramError::InvalidAccountData);
+    pub fn set_curve(&mut self, curve_type: u8, amp: u64) -> Result<(), ProgramError> {
+        match curve_type {
+            t if t == CurveType::ConstantProduct as u8 => self.set_amp_ramp(0, 0, 0, 0),
+            t if t == CurveType::StableSwap as u8 => {
+                if !(stable::MIN_AMP..=stable::MAX_AMP).contains(&amp) {
+                    return Err(AmmError::InvalidAmp.into());
+                }
+                self.set_amp_ramp(amp, amp, 0, 0);
+            }
+            _ => return Err(AmmError::InvalidCurve.into()),
         }
-        Ok(unsafe {Self::from_bytes_unchecked_mut(account_info.borrow_unchecked_mut())})
+        self.curve_type = curve_type;
+        Ok(())
     }
 
     #[inline(always)]
-    pub fn set_state(&mut self, state: u8) -> Result<(), ProgramError> {
-        if state.ge(&(AmmState::WithdrawOnly as u8)){
-            return Err(ProgramError::InvalidAccountData);
+    pub fn set_amp_ramp(&mut self, amp_initial: u64, amp_target: u64, ramp_start: i64, ramp_end: i64) {
+        self.amp_initial = amp_initial.to_le_bytes();
+        self.amp_target = amp_target.to_le_bytes();
+        self.ramp_start = ramp_start.to_le_bytes();
+        self.ramp_end = ramp_end.to_le_bytes();
+    }
+
+    // 返回 timestamp 时刻的放大系数
+    #[inline(always)]
+    pub fn current_amp(&self, timestamp: i64) -> u64 {
+        stable::current_amp(
+            u64::from_le_bytes(self.amp_initial),
+            u64::from_le_bytes(self.amp_target),
+            i64::from_le_bytes(self.ramp_start),
+            i64::from_le_bytes(self.ramp_end),
+            timestamp,
+        )
+    }
+
+    // 返回 timestamp 时刻交换使用的曲线
+    #[inline(always)]
+    pub fn curve(&self, timestamp: i64) -> Result<Curve, ProgramError> {
+        match self.curve_type {
+            t if t == CurveType::ConstantProduct as u8 => Ok(Curve::ConstantProduct),
+            t if t == CurveType::StableSwap as u8 => Ok(Curve::StableSwap {
+                amp: self.current_amp(timestamp),
+            }),
+            _ => Err(AmmError::InvalidCurve.into()),
         }
-        self.state = state as u8;
-        Ok(())
     }
 
+    // 返回最后一次记录的 TWAP 观测
     #[inline(always)]
-    pub fn set_fee(&mut self, fee: u16) -> Result<(), ProgramError> {
-        if fee.ge(&10_000){
-            return Err(ProgramError::InvalidAccountData);
-        };
-        self.fee = fee.to_le_bytes();
+    pub fn observation(&self) -> Observation {
+        Observation {
+            price_x_cumulative: u128::from_le_bytes(self.price_x_cumulative),
+            price_y_cumulative: u128::from_le_bytes(self.price_y_cumulative),
+            timestamp: i64::from_le_bytes(self.last_update_timestamp),
+        }
+    }
+
+    #[inline(always)]
+    pub fn set_observation(&mut self, observation: Observation) {
+        self.price_x_cumulative = observation.price_x_cumulative.to_le_bytes();
+        self.price_y_cumulative = observation.price_y_cumulative.to_le_bytes();
+        self.last_update_timestamp = observation.timestamp.to_le_bytes();
+    }
+
+    // 读取到 timestamp 为止的累计价格，不修改账户
+    // 上次更新之后储备没有变化，因此可以直接用当前储备推进
+    // 价格按池子的曲线计算，StableSwap 使用 timestamp 时刻的放大系数
+    // 借贷等程序保存两次 observe 的结果，再用 crate::oracle::twap 计算平均价格
+    #[inline(always)]
+    pub fn observe(&self, reserve_x: u64, reserve_y: u64, timestamp: i64) -> Result<Observation, ProgramError> {
+        Ok(self.observation().advance(self.curve(timestamp)?, reserve_x, reserve_y, timestamp))
+    }
+
+    // 在储备变化之前调用，用变化前的储备把累计价格推进到 timestamp
+    #[inline(always)]
+    pub fn update_oracle(&mut self, reserve_x: u64, reserve_y: u64, timestamp: i64) -> Result<(), ProgramError> {
+        let observation = self.observe(reserve_x, reserve_y, timestamp)?;
+        self.set_observation(observation);
         Ok(())
     }
 
@@ -195,6 +344,11 @@ impl Config {
         self.authority = authority;
     }
 
+    #[inline(always)]
+    pub fn set_pending_authority(&mut self, pending_authority: Address) {
+        self.pending_authority = pending_authority;
+    }
+
     #[inline(always)]
     pub fn set_mint_x(&mut self, mint_x: Address) {
         self.mint_x = mint_x;
@@ -210,6 +364,11 @@ impl Config {
         self.config_bump = config_bump;
     }
 
+    #[inline(always)]
+    pub fn set_lp_bump(&mut self, lp_bump: [u8; 1]) {
+        self.lp_bump = lp_bump;
+    }
+
     #[inline(always)]
     pub fn set_inner(
         &mut self,
@@ -225,19 +384,177 @@ impl Config {
         self.set_authority(authority);
         self.set_mint_x(mint_x);
         self.set_mint_y(mint_y);
-        self.set_fee(fee);
+        self.set_fee(fee)?;
         self.set_config_bump(config_bump);
+        self.init_fields_since(0);
         Ok(())
     }
 
+}
+
+// =============================================================================
+// Config PDA 签名
+// =============================================================================
+// Config 作为金库和 LP Mint 的 authority 参与 CPI 时使用的种子：
+// [CONFIG_SEED, seed, mint_x, mint_y, config_bump]
+//
+// 种子从 Config 中复制出来，Config 作为签名者参与 CPI 之前需要先释放对账户数据的借用
+pub struct ConfigSigner {
+    seed: [u8; 8],
+    mint_x: Address,
+    mint_y: Address,
+    bump: [u8; 1],
+}
+
+impl ConfigSigner {
+    #[inline(always)]
+    pub fn new(seed: u64, mint_x: &Address, mint_y: &Address, bump: [u8; 1]) -> Self {
+        Self {
+            seed: seed.to_le_bytes(),
+            mint_x: *mint_x,
+            mint_y: *mint_y,
+            bump,
+        }
+    }
+
+    #[inline(always)]
+    pub fn seeds(&self) -> [Seed<'_>; 5] {
+        [
+            Seed::from(CONFIG_SEED),
+            Seed::from(&self.seed),
+            Seed::from(self.mint_x.as_ref()),
+            Seed::from(self.mint_y.as_ref()),
+            Seed::from(&self.bump),
+        ]
+    }
+}
+
+impl Config {
+    #[inline(always)]
+    pub fn signer(&self) -> ConfigSigner {
+        ConfigSigner::new(self.seed(), self.mint_x(), self.mint_y(), *self.config_bump())
+    }
+}
+
+// =============================================================================
+// 池子注册表
+// =============================================================================
+
+impl Registry {
+    // 加载只读的注册表，地址必须是使用保存的 bump 派生的 [REGISTRY_SEED]
+    #[inline(always)]
+    pub fn load(account_info: &AccountView) -> Result<Ref<'_, Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_info.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        let registry = Ref::map(account_info.try_borrow()?, |data| unsafe {
+            Self::from_bytes_unchecked(data)
+        });
+        let pda = Address::create_program_address(&[REGISTRY_SEED, registry.bump()], &crate::ID)?;
+        if pda.ne(account_info.address()) {
+            return Err(AmmError::InvalidAddress.into());
+        }
+        Ok(registry)
+    }
+
+    // 加载可写的注册表，地址必须是使用保存的 bump 派生的 [REGISTRY_SEED]
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountView) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_info.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        let registry = RefMut::map(account_info.try_borrow_mut()?, |data| unsafe {
+            Self::from_bytes_unchecked_mut(data)
+        });
+        let pda = Address::create_program_address(&[REGISTRY_SEED, registry.bump()], &crate::ID)?;
+        if pda.ne(account_info.address()) {
+            return Err(AmmError::InvalidAddress.into());
+        }
+        Ok(registry)
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, authority: Address, one_pool_per_fee_tier: bool, bump: [u8;1]) {
+        self.set_authority(authority);
+        self.set_one_pool_per_fee_tier(one_pool_per_fee_tier);
+        self.bump = bump;
+        self.pair_count = [0u8;8];
+        self.pool_count = [0u8;8];
+    }
+
+    #[inline(always)]
+    pub fn set_authority(&mut self, authority: Address) {
+        self.authority = authority;
+    }
+
+    #[inline(always)]
+    pub fn set_one_pool_per_fee_tier(&mut self, one_pool_per_fee_tier: bool) {
+        self.one_pool_per_fee_tier = one_pool_per_fee_tier as u8;
+    }
+
+    // 记录一个新注册的池子，new_pair 表示它是这个交易对的第一个池子
     #[inline(always)]
-    pub fn has_authority(&self) -> Option<Address> {
-        let auth = unsafe {core::ptr::addr_of!(self.authority).read_unaligned()};
+    pub fn add_pool(&mut self, new_pair: bool) -> Result<(), ProgramError> {
+        if new_pair {
+            self.pair_count = self
+                .pair_count()
+                .checked_add(1)
+                .ok_or(ProgramError::ArithmeticOverflow)?
+                .to_le_bytes();
+        }
+        self.pool_count = self
+            .pool_count()
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            .to_le_bytes();
+        Ok(())
+    }
+}
 
-        if auth == Address::default(){
-            None
-        }else {
-            Some(auth)
+impl PairRecord {
+    // 加载可写的交易对记录，地址必须是使用保存的 bump 派生的 [PAIR_SEED, mint_x, mint_y]
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountView) -> Result<RefMut<'_, [u8]>, ProgramError> {
+        if !account_info.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        let data = account_info.try_borrow_mut()?;
+        let (pair, _) = Self::from_bytes(&data).ok_or(ProgramError::InvalidAccountData)?;
+        let pda = Address::create_program_address(
+            &[PAIR_SEED, pair.mint_x().as_ref(), pair.mint_y().as_ref(), pair.bump()],
+            &crate::ID,
+        )?;
+        if pda.ne(account_info.address()) {
+            return Err(AmmError::InvalidAddress.into());
         }
+        Ok(data)
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, mint_x: Address, mint_y: Address, bump: [u8;1]) {
+        self.mint_x = mint_x;
+        self.mint_y = mint_y;
+        self.bump = bump;
     }
-}
\ No newline at end of file
+}
+
+impl PoolEntry {
+    #[inline(always)]
+    pub fn set_inner(&mut self, config: Address, seed: u64, fee_tier: u16, curve_type: u8) {
+        self.config = config;
+        self.seed = seed.to_le_bytes();
+        self.fee_tier = fee_tier.to_le_bytes();
+        self.curve_type = curve_type;
+    }
+
+    #[inline(always)]
+    pub fn set_fee_tier(&mut self, fee_tier: u16) {
+        self.fee_tier = fee_tier.to_le_bytes();
+    }
+}
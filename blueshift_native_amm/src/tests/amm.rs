@@ -0,0 +1,569 @@
+// =============================================================================
+// AMM 指令集成测试
+// =============================================================================
+// 完整流程：初始化 -> 首次存款 -> 按比例存款 -> 双向交换 -> 部分取款 -> 取出用户的全部 LP
+// 以及过期、滑点和池子状态的失败情况
+
+use solana_sdk::program_error::ProgramError;
+use solana_sdk::pubkey::Pubkey;
+
+use super::fixtures::{metadata_address, metadata_program_id, program_id, token_account, token_program_id, Pool, FEE, NOT_EXPIRED, NOW, SEED, USER_BALANCE};
+use crate::metadata::MAX_URI_LEN;
+use crate::curve::MINIMUM_LIQUIDITY;
+use crate::{curve, AmmError, AmmState, Config, CurveType, PairRecord, Registry};
+
+const INITIAL_X: u64 = 1_000_000;
+const INITIAL_Y: u64 = 4_000_000;
+// sqrt(INITIAL_X * INITIAL_Y)
+const INITIAL_LP: u64 = 2_000_000;
+
+// =============================================================================
+// 正常流程
+// =============================================================================
+
+#[test]
+fn test_initialize_creates_config_lp_mint_and_vaults() {
+    let pool = Pool::initialized();
+
+    assert_eq!(pool.state(), AmmState::Initialized as u8);
+    assert_eq!(pool.lp_supply(), 0);
+    assert_eq!(pool.reserves(), (0, 0));
+}
+
+#[test]
+fn test_initialize_accepts_pre_created_vault() {
+    let mut pool = Pool::setup();
+    pool.process_ok(&pool.initialize_registry_ix(true));
+
+    // Config 的地址可以提前算出，别人抢先创建了金库 X（还转入了一些代币）
+    let vault_x = token_account(&pool.mollusk, &pool.mint_x, &pool.config, 1);
+    pool.accounts.insert(pool.vault_x, vault_x);
+
+    pool.process_ok(&pool.initialize_ix());
+
+    assert_eq!(pool.state(), AmmState::Initialized as u8);
+    assert_eq!(pool.reserves(), (1, 0));
+}
+
+#[test]
+fn test_first_deposit_mints_sqrt_and_locks_minimum_liquidity() {
+    let pool = Pool::with_liquidity(INITIAL_X, INITIAL_Y);
+
+    assert_eq!(pool.reserves(), (INITIAL_X, INITIAL_Y));
+    assert_eq!(pool.lp_supply(), INITIAL_LP);
+    assert_eq!(pool.token_amount(&pool.user_lp_ata), INITIAL_LP - MINIMUM_LIQUIDITY);
+    assert_eq!(pool.token_amount(&pool.locked_lp_ata), MINIMUM_LIQUIDITY);
+    assert_eq!(pool.token_amount(&pool.user_x_ata), USER_BALANCE - INITIAL_X);
+    assert_eq!(pool.token_amount(&pool.user_y_ata), USER_BALANCE - INITIAL_Y);
+}
+
+#[test]
+fn test_proportional_deposit() {
+    let mut pool = Pool::with_liquidity(INITIAL_X, INITIAL_Y);
+
+    // 再铸造一半的 LP，需要按比例存入一半的储备
+    pool.process_ok(&pool.deposit_ix(INITIAL_LP / 2, INITIAL_X, INITIAL_Y, NOT_EXPIRED));
+
+    assert_eq!(pool.reserves(), (INITIAL_X * 3 / 2, INITIAL_Y * 3 / 2));
+    assert_eq!(pool.lp_supply(), INITIAL_LP * 3 / 2);
+}
+
+#[test]
+fn test_swap_x_to_y_matches_curve() {
+    let mut pool = Pool::with_liquidity(INITIAL_X, INITIAL_Y);
+    let amount_in = 10_000;
+    let expected = curve::swap_exact_in(INITIAL_X, INITIAL_Y, amount_in, FEE).unwrap();
+
+    pool.process_ok(&pool.swap_ix(true, amount_in, expected.amount_out, NOT_EXPIRED));
+
+    assert_eq!(pool.reserves(), (INITIAL_X + amount_in, INITIAL_Y - expected.amount_out));
+    assert_eq!(
+        pool.token_amount(&pool.user_y_ata),
+        USER_BALANCE - INITIAL_Y + expected.amount_out
+    );
+}
+
+#[test]
+fn test_swap_y_to_x_matches_curve() {
+    let mut pool = Pool::with_liquidity(INITIAL_X, INITIAL_Y);
+    let amount_in = 40_000;
+    let expected = curve::swap_exact_in(INITIAL_Y, INITIAL_X, amount_in, FEE).unwrap();
+
+    pool.process_ok(&pool.swap_ix(false, amount_in, expected.amount_out, NOT_EXPIRED));
+
+    assert_eq!(pool.reserves(), (INITIAL_X - expected.amount_out, INITIAL_Y + amount_in));
+    assert_eq!(
+        pool.token_amount(&pool.user_x_ata),
+        USER_BALANCE - INITIAL_X + expected.amount_out
+    );
+}
+
+#[test]
+fn test_partial_and_full_withdraw() {
+    let mut pool = Pool::with_liquidity(INITIAL_X, INITIAL_Y);
+    pool.process_ok(&pool.swap_ix(true, 10_000, 0, NOT_EXPIRED));
+    let (reserve_x, reserve_y) = pool.reserves();
+
+    // 部分取款：按比例向下取整
+    let expected = curve::withdraw_amounts(reserve_x, reserve_y, INITIAL_LP, INITIAL_LP / 4).unwrap();
+    pool.process_ok(&pool.withdraw_ix(INITIAL_LP / 4, expected.x, expected.y, NOT_EXPIRED));
+
+    assert_eq!(pool.reserves(), (reserve_x - expected.x, reserve_y - expected.y));
+    assert_eq!(pool.lp_supply(), INITIAL_LP - INITIAL_LP / 4);
+
+    // 取出用户的全部 LP：锁定的 MINIMUM_LIQUIDITY 对应的储备留在池子里
+    let (reserve_x, reserve_y) = pool.reserves();
+    let user_lp = pool.token_amount(&pool.user_lp_ata);
+    let expected = curve::withdraw_amounts(reserve_x, reserve_y, pool.lp_supply(), user_lp).unwrap();
+    pool.process_ok(&pool.withdraw_ix(user_lp, 0, 0, NOT_EXPIRED));
+
+    assert_eq!(pool.reserves(), (reserve_x - expected.x, reserve_y - expected.y));
+    assert_eq!(pool.lp_supply(), MINIMUM_LIQUIDITY);
+    assert_eq!(pool.token_amount(&pool.user_lp_ata), 0);
+    assert!(pool.reserves().0 > 0 && pool.reserves().1 > 0);
+}
+
+// =============================================================================
+// 首次存款
+// =============================================================================
+
+#[test]
+fn test_first_deposit_rejects_liquidity_below_minimum() {
+    let mut pool = Pool::initialized();
+
+    // sqrt(1_000 * 1_000) 正好等于锁定数量，用户什么都得不到
+    pool.process_err(
+        &pool.first_deposit_ix(0, 1_000, 1_000, NOT_EXPIRED),
+        ProgramError::Custom(AmmError::InsufficientLiquidity as u32),
+    );
+    pool.process_ok(&pool.first_deposit_ix(1, 1_001, 1_001, NOT_EXPIRED));
+    assert_eq!(pool.token_amount(&pool.user_lp_ata), 1);
+}
+
+#[test]
+fn test_first_deposit_amount_is_minimum_lp() {
+    let mut pool = Pool::initialized();
+
+    pool.process_err(
+        &pool.first_deposit_ix(INITIAL_LP - MINIMUM_LIQUIDITY + 1, INITIAL_X, INITIAL_Y, NOT_EXPIRED),
+        ProgramError::Custom(AmmError::SlippageExceeded as u32),
+    );
+    pool.process_ok(&pool.first_deposit_ix(INITIAL_LP - MINIMUM_LIQUIDITY, INITIAL_X, INITIAL_Y, NOT_EXPIRED));
+}
+
+#[test]
+fn test_first_deposit_requires_locked_lp_accounts() {
+    let mut pool = Pool::initialized();
+
+    pool.process_err(
+        &pool.deposit_ix(0, INITIAL_X, INITIAL_Y, NOT_EXPIRED),
+        ProgramError::NotEnoughAccountKeys,
+    );
+
+    // 锁定账户的 owner 必须是池子的锁定 PDA，不能是用户自己
+    pool.locked_lp_owner = pool.user;
+    pool.locked_lp_ata = pool.user_lp_ata;
+    pool.process_err(
+        &pool.first_deposit_ix(0, INITIAL_X, INITIAL_Y, NOT_EXPIRED),
+        ProgramError::Custom(AmmError::InvalidAddress as u32),
+    );
+}
+
+// =============================================================================
+// 过期
+// =============================================================================
+
+#[test]
+fn test_expired_instructions_fail() {
+    let mut pool = Pool::with_liquidity(INITIAL_X, INITIAL_Y);
+    let expired = NOW - 1;
+
+    pool.process_err(&pool.deposit_ix(1_000, INITIAL_X, INITIAL_Y, expired), ProgramError::Custom(AmmError::Expired as u32));
+    pool.process_err(&pool.swap_ix(true, 1_000, 0, expired), ProgramError::Custom(AmmError::Expired as u32));
+    pool.process_err(&pool.withdraw_ix(1_000, 0, 0, expired), ProgramError::Custom(AmmError::Expired as u32));
+
+    assert_eq!(pool.reserves(), (INITIAL_X, INITIAL_Y));
+}
+
+// =============================================================================
+// 滑点
+// =============================================================================
+
+#[test]
+fn test_deposit_slippage() {
+    let mut pool = Pool::with_liquidity(INITIAL_X, INITIAL_Y);
+
+    // 铸造一半 LP 需要 INITIAL_X / 2 个 X，上限少 1
+    pool.process_err(
+        &pool.deposit_ix(INITIAL_LP / 2, INITIAL_X / 2 - 1, INITIAL_Y, NOT_EXPIRED),
+        ProgramError::Custom(AmmError::SlippageExceeded as u32),
+    );
+}
+
+#[test]
+fn test_swap_slippage() {
+    let mut pool = Pool::with_liquidity(INITIAL_X, INITIAL_Y);
+    let expected = curve::swap_exact_in(INITIAL_X, INITIAL_Y, 10_000, FEE).unwrap();
+
+    pool.process_err(
+        &pool.swap_ix(true, 10_000, expected.amount_out + 1, NOT_EXPIRED),
+        ProgramError::Custom(AmmError::SlippageExceeded as u32),
+    );
+}
+
+#[test]
+fn test_withdraw_slippage() {
+    let mut pool = Pool::with_liquidity(INITIAL_X, INITIAL_Y);
+
+    // 取出一半 LP 只能拿到一半储备
+    pool.process_err(
+        &pool.withdraw_ix(INITIAL_LP / 2, INITIAL_X / 2 + 1, 0, NOT_EXPIRED),
+        ProgramError::Custom(AmmError::SlippageExceeded as u32),
+    );
+}
+
+// =============================================================================
+// 指令数据
+// =============================================================================
+
+#[test]
+fn test_swap_rejects_invalid_direction_flag() {
+    let mut pool = Pool::with_liquidity(INITIAL_X, INITIAL_Y);
+
+    // is_x 只能是 0 或 1
+    let mut swap = pool.swap_ix(true, 10_000, 0, NOT_EXPIRED);
+    swap.data[1] = 2;
+    pool.process_err(&swap, ProgramError::InvalidInstructionData);
+
+    // SwapExactOut 的数据布局与 Swap 相同
+    swap.data[0] = *crate::SwapExactOut::DISCRIMINATOR;
+    pool.process_err(&swap, ProgramError::InvalidInstructionData);
+}
+
+#[test]
+fn test_zap_rejects_invalid_side_flag() {
+    let mut pool = Pool::with_liquidity(INITIAL_X, INITIAL_Y);
+
+    // DepositSingle / WithdrawSingle 与 Deposit / Withdraw 使用相同的账户
+    // 数据布局：[is_x: u8][amount: u64][min: u64][expirations: i64]，is_x 只能是 0 或 1
+    for (discriminator, mut instruction) in [
+        (*crate::DepositSingle::DISCRIMINATOR, pool.deposit_ix(0, 0, 0, NOT_EXPIRED)),
+        (*crate::WithdrawSingle::DISCRIMINATOR, pool.withdraw_ix(0, 0, 0, NOT_EXPIRED)),
+    ] {
+        let mut data = vec![discriminator, 2];
+        data.extend_from_slice(&10_000u64.to_le_bytes());
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(&NOT_EXPIRED.to_le_bytes());
+        instruction.data = data;
+
+        pool.process_err(&instruction, ProgramError::InvalidInstructionData);
+    }
+}
+
+// =============================================================================
+// 池子状态
+// =============================================================================
+
+#[test]
+fn test_disabled_pool_rejects_everything() {
+    let mut pool = Pool::with_liquidity(INITIAL_X, INITIAL_Y);
+    pool.process_ok(&pool.set_state_ix(AmmState::Disabled));
+    assert_eq!(pool.state(), AmmState::Disabled as u8);
+
+    pool.process_err(&pool.deposit_ix(1_000, INITIAL_X, INITIAL_Y, NOT_EXPIRED), ProgramError::Custom(AmmError::PoolDisabled as u32));
+    pool.process_err(&pool.swap_ix(true, 1_000, 0, NOT_EXPIRED), ProgramError::Custom(AmmError::PoolDisabled as u32));
+    pool.process_err(&pool.withdraw_ix(1_000, 0, 0, NOT_EXPIRED), ProgramError::Custom(AmmError::PoolDisabled as u32));
+
+    // 恢复后可以正常交易
+    pool.process_ok(&pool.set_state_ix(AmmState::Initialized));
+    pool.process_ok(&pool.swap_ix(true, 1_000, 0, NOT_EXPIRED));
+}
+
+#[test]
+fn test_withdraw_only_pool_allows_only_withdraw() {
+    let mut pool = Pool::with_liquidity(INITIAL_X, INITIAL_Y);
+    pool.process_ok(&pool.set_state_ix(AmmState::WithdrawOnly));
+    assert_eq!(pool.state(), AmmState::WithdrawOnly as u8);
+
+    pool.process_err(&pool.deposit_ix(1_000, INITIAL_X, INITIAL_Y, NOT_EXPIRED), ProgramError::Custom(AmmError::PoolWithdrawOnly as u32));
+    pool.process_err(&pool.swap_ix(true, 1_000, 0, NOT_EXPIRED), ProgramError::Custom(AmmError::PoolWithdrawOnly as u32));
+
+    pool.process_ok(&pool.withdraw_ix(INITIAL_LP - MINIMUM_LIQUIDITY, 0, 0, NOT_EXPIRED));
+    assert_eq!(pool.lp_supply(), MINIMUM_LIQUIDITY);
+}
+
+// =============================================================================
+// 闪电贷
+// =============================================================================
+// Mollusk 中没有专门的 receiver 程序，以 Token Program 作为 receiver，
+// 让它执行一次 Transfer 把借款转回金库
+
+#[test]
+fn test_flash_loan_repaid_with_fee() {
+    let mut pool = Pool::with_liquidity(INITIAL_X, INITIAL_Y);
+    let borrow = 100_000;
+    let fee = curve::flash_loan_fee(borrow, FEE).unwrap();
+    assert_eq!(fee, 300);
+
+    // 多归还的部分留在池子里
+    pool.process_ok(&pool.flash_loan_repay_x_ix(borrow, borrow + fee));
+    assert_eq!(pool.reserves(), (INITIAL_X + fee, INITIAL_Y));
+    assert_eq!(pool.token_amount(&pool.user_x_ata), USER_BALANCE - INITIAL_X - fee);
+}
+
+#[test]
+fn test_flash_loan_not_repaid() {
+    let mut pool = Pool::with_liquidity(INITIAL_X, INITIAL_Y);
+    let borrow = 100_000;
+    let fee = curve::flash_loan_fee(borrow, FEE).unwrap();
+
+    // 只归还本金，或少归还 1 都失败
+    pool.process_err(
+        &pool.flash_loan_repay_x_ix(borrow, borrow),
+        ProgramError::Custom(AmmError::FlashLoanNotRepaid as u32),
+    );
+    pool.process_err(
+        &pool.flash_loan_repay_x_ix(borrow, borrow + fee - 1),
+        ProgramError::Custom(AmmError::FlashLoanNotRepaid as u32),
+    );
+    assert_eq!(pool.reserves(), (INITIAL_X, INITIAL_Y));
+}
+
+#[test]
+fn test_flash_loan_rejects_invalid_requests() {
+    let mut pool = Pool::with_liquidity(INITIAL_X, INITIAL_Y);
+
+    // 借出数量为 0，或超过储备
+    pool.process_err(&pool.flash_loan_repay_x_ix(0, 0), ProgramError::Custom(AmmError::ZeroAmount as u32));
+    pool.process_err(
+        &pool.flash_loan_repay_x_ix(INITIAL_X + 1, INITIAL_X + 1),
+        ProgramError::Custom(AmmError::InsufficientLiquidity as u32),
+    );
+
+    // receiver 不能是本程序
+    pool.process_err(
+        &pool.flash_loan_ix(1_000, 0, program_id(), vec![], &[]),
+        ProgramError::IncorrectProgramId,
+    );
+
+    // 与交换相同，禁用的池子不能借出
+    pool.process_ok(&pool.set_state_ix(AmmState::Disabled));
+    pool.process_err(
+        &pool.flash_loan_repay_x_ix(1_000, 1_003),
+        ProgramError::Custom(AmmError::PoolDisabled as u32),
+    );
+}
+
+// =============================================================================
+// 配置迁移
+// =============================================================================
+
+#[test]
+fn test_migrate_baseline_config() {
+    let mut pool = Pool::with_baseline_config();
+    pool.process_ok(&pool.migrate_config_ix());
+
+    // 原有字段不变，新字段为默认值，lp_bump 重新派生
+    let config = pool.config_account();
+    assert_eq!(config.version(), Config::VERSION);
+    assert_eq!(config.state(), AmmState::Initialized as u8);
+    assert_eq!(config.seed(), SEED);
+    assert_eq!(config.authority().as_ref(), pool.user.as_ref());
+    assert_eq!(config.mint_x().as_ref(), pool.mint_x.as_ref());
+    assert_eq!(config.mint_y().as_ref(), pool.mint_y.as_ref());
+    assert_eq!(u16::from_le_bytes(*config.fee()), FEE);
+    assert_eq!(config.has_pending_authority(), None);
+    assert_eq!(config.protocol_fee(), 0);
+    assert_eq!(config.curve_type(), CurveType::ConstantProduct as u8);
+    let (_, lp_bump) = Pubkey::find_program_address(&[b"mint_lp", pool.config.as_ref()], &program_id());
+    assert_eq!(*config.lp_bump(), [lp_bump]);
+
+    // 已经是当前版本，不能再次迁移
+    pool.process_err(&pool.migrate_config_ix(), ProgramError::InvalidAccountData);
+}
+
+// =============================================================================
+// 曲线错误
+// =============================================================================
+
+#[test]
+fn test_swap_on_empty_pool_returns_zero_liquidity() {
+    let mut pool = Pool::initialized();
+
+    pool.process_err(&pool.swap_ix(true, 1_000, 0, NOT_EXPIRED), ProgramError::Custom(AmmError::ZeroLiquidity as u32));
+}
+
+// =============================================================================
+// LP 元数据
+// =============================================================================
+// Mollusk 中没有 Metaplex 程序，这里只验证 CPI 之前的检查
+
+#[test]
+fn test_update_lp_metadata_rejects_invalid_requests() {
+    let mut pool = Pool::initialized();
+    let uri = b"https://example.com/lp.json";
+
+    // 只有池子的 authority 可以修改
+    pool.process_err(
+        &pool.update_lp_metadata_ix(Pubkey::new_unique(), uri, metadata_program_id()),
+        ProgramError::IncorrectAuthority,
+    );
+
+    // Config 只会为 Metaplex 程序签名
+    pool.process_err(
+        &pool.update_lp_metadata_ix(pool.user, uri, token_program_id()),
+        ProgramError::IncorrectProgramId,
+    );
+
+    // URI 超出 Metaplex 的长度限制，或不是 UTF-8
+    pool.process_err(
+        &pool.update_lp_metadata_ix(pool.user, &[b'a'; MAX_URI_LEN + 1], metadata_program_id()),
+        ProgramError::Custom(AmmError::InvalidMetadataUri as u32),
+    );
+    pool.process_err(
+        &pool.update_lp_metadata_ix(pool.user, &[0xff, 0xfe], metadata_program_id()),
+        ProgramError::Custom(AmmError::InvalidMetadataUri as u32),
+    );
+
+    // 还没有创建 Metadata 时没有可以沿用的名称和符号
+    pool.process_err(
+        &pool.update_lp_metadata_ix(pool.user, uri, metadata_program_id()),
+        ProgramError::UninitializedAccount,
+    );
+}
+
+#[test]
+fn test_create_lp_metadata_rejects_invalid_requests() {
+    let mut pool = Pool::initialized();
+    let mint_x_metadata = metadata_address(&pool.mint_x);
+
+    // 只有池子的 authority 可以为已有的池子创建元数据
+    let other = Pubkey::new_unique();
+    pool.accounts.insert(other, solana_account::Account::new(100_000_000_000, 0, &Pubkey::default()));
+    pool.process_err(
+        &pool.create_lp_metadata_ix(other, metadata_program_id(), mint_x_metadata),
+        ProgramError::IncorrectAuthority,
+    );
+
+    // Config 只会为 Metaplex 程序签名
+    pool.process_err(
+        &pool.create_lp_metadata_ix(pool.user, token_program_id(), mint_x_metadata),
+        ProgramError::IncorrectProgramId,
+    );
+
+    // 名称来自 Mint 的 Metadata，地址必须是对应 Mint 的 Metadata PDA
+    pool.process_err(
+        &pool.create_lp_metadata_ix(pool.user, metadata_program_id(), metadata_address(&pool.mint_y)),
+        ProgramError::Custom(AmmError::InvalidAddress as u32),
+    );
+}
+
+// =============================================================================
+// 池子注册表
+// =============================================================================
+
+#[test]
+fn test_initialize_registers_pool_in_pair() {
+    let mut pool = Pool::initialized();
+
+    let (pair, entries) = PairRecord::from_bytes(&pool.accounts[&pool.pair_record()].data).unwrap();
+    assert_eq!(pair.mint_x().as_ref(), pool.mint_x.as_ref());
+    assert_eq!(pair.mint_y().as_ref(), pool.mint_y.as_ref());
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].config().as_ref(), pool.config.as_ref());
+    assert_eq!(entries[0].seed(), SEED);
+    assert_eq!(entries[0].fee_tier(), FEE);
+
+    let registry = Registry::from_bytes(&pool.accounts[&pool.registry()].data).unwrap();
+    assert_eq!((registry.pair_count(), registry.pool_count()), (1, 1));
+
+    // 同一个池子只能注册一次
+    pool.process_err(
+        &pool.register_pool_ix(),
+        ProgramError::Custom(AmmError::PoolAlreadyRegistered as u32),
+    );
+}
+
+#[test]
+fn test_initialize_requires_registry() {
+    let mut pool = Pool::setup();
+
+    // 注册表尚未创建
+    pool.process_err(&pool.initialize_ix(), ProgramError::InvalidAccountData);
+}
+
+#[test]
+fn test_initialize_registry_requires_upgrade_authority() {
+    let mut pool = Pool::setup();
+
+    // 其他签名者不能抢先创建注册表
+    pool.user = Pubkey::new_unique();
+    pool.accounts.insert(pool.user, solana_account::Account::new(100_000_000_000, 0, &Pubkey::default()));
+    pool.process_err(&pool.initialize_registry_ix(true), ProgramError::IncorrectAuthority);
+}
+
+#[test]
+fn test_update_fee_updates_fee_tier() {
+    let mut pool = Pool::initialized();
+    pool.process_ok(&pool.update_fee_ix(FEE * 2));
+
+    let (_, entries) = PairRecord::from_bytes(&pool.accounts[&pool.pair_record()].data).unwrap();
+    assert_eq!(entries[0].fee_tier(), FEE * 2);
+    assert_eq!(pool.config_account().fee(), &(FEE * 2).to_le_bytes());
+}
+
+#[test]
+fn test_update_fee_without_registry_accounts() {
+    let mut pool = Pool::initialized();
+
+    // 只传入 [authority, config]：修改手续费，但不同步交易对记录
+    let mut update_fee = pool.update_fee_ix(FEE * 2);
+    update_fee.accounts.truncate(2);
+    pool.process_ok(&update_fee);
+
+    assert_eq!(pool.config_account().fee(), &(FEE * 2).to_le_bytes());
+    let (_, entries) = PairRecord::from_bytes(&pool.accounts[&pool.pair_record()].data).unwrap();
+    assert_eq!(entries[0].fee_tier(), FEE);
+
+    // 只传入注册表、缺少交易对记录
+    let mut update_fee = pool.update_fee_ix(FEE);
+    update_fee.accounts.truncate(3);
+    pool.process_err(&update_fee, ProgramError::NotEnoughAccountKeys);
+}
+
+#[test]
+fn test_initialize_with_prefunded_pair_record() {
+    let mut pool = Pool::setup();
+    pool.process_ok(&pool.initialize_registry_ix(true));
+
+    // 别人预先向交易对记录的地址转入了 lamports
+    pool.accounts.insert(pool.pair_record(), solana_account::Account::new(1, 0, &Pubkey::default()));
+    pool.process_ok(&pool.initialize_ix());
+
+    let (_, entries) = PairRecord::from_bytes(&pool.accounts[&pool.pair_record()].data).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].config().as_ref(), pool.config.as_ref());
+    assert_eq!(pool.accounts[&pool.pair_record()].owner, program_id());
+}
+
+#[test]
+fn test_register_non_canonical_baseline_pool() {
+    let mut pool = Pool::setup_non_canonical();
+    pool.insert_baseline_config();
+    pool.process_ok(&pool.initialize_registry_ix(true));
+    pool.process_ok(&pool.migrate_config_ix());
+
+    // 注册表创建之前的池子，两个 mint 不是规范顺序，交易对记录按规范顺序保存
+    pool.process_ok(&pool.register_pool_ix());
+
+    let (pair, entries) = PairRecord::from_bytes(&pool.accounts[&pool.pair_record()].data).unwrap();
+    assert_eq!(pair.mint_x().as_ref(), pool.mint_y.as_ref());
+    assert_eq!(pair.mint_y().as_ref(), pool.mint_x.as_ref());
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].config().as_ref(), pool.config.as_ref());
+
+    // 修改手续费时同样能找到这个交易对记录
+    pool.process_ok(&pool.update_fee_ix(FEE * 2));
+    let (_, entries) = PairRecord::from_bytes(&pool.accounts[&pool.pair_record()].data).unwrap();
+    assert_eq!(entries[0].fee_tier(), FEE * 2);
+}
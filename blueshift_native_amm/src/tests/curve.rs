@@ -0,0 +1,434 @@
+// =============================================================================
+// 曲线性质测试 - 使用 proptest 随机生成储备、供应量和数量
+// =============================================================================
+// 验证 curve 模块承诺的不变量：
+// 1. 交换后 k = x * y 不会减少
+// 2. 存款 / 取款后每份 LP 对应的储备量不会减少
+// 3. 存入后立刻取出不会获利
+
+use proptest::prelude::*;
+use crate::curve::{self, CurveError, FEE_DENOMINATOR};
+
+// 比较 a / b <= c / d，交叉相乘避免除法舍入
+fn ratio_le(a: u64, b: u64, c: u64, d: u64) -> bool {
+    (a as u128) * (d as u128) <= (c as u128) * (b as u128)
+}
+
+fn reserve() -> impl Strategy<Value = u64> {
+    1u64..=u64::MAX / 2
+}
+
+fn fee() -> impl Strategy<Value = u16> {
+    0u16..FEE_DENOMINATOR
+}
+
+// =============================================================================
+// 交换
+// =============================================================================
+proptest! {
+    #[test]
+    fn swap_never_decreases_k(
+        reserve_in in reserve(),
+        reserve_out in reserve(),
+        amount_in in 1u64..=u64::MAX / 2,
+        fee in fee(),
+    ) {
+        let Ok(result) = curve::swap_exact_in(reserve_in, reserve_out, amount_in, fee) else {
+            return Ok(());
+        };
+
+        prop_assert!(result.amount_out < reserve_out);
+        prop_assert_eq!(result.amount_in, amount_in);
+        prop_assert!(result.fee <= amount_in);
+
+        let k_before = (reserve_in as u128) * (reserve_out as u128);
+        let k_after = ((reserve_in + amount_in) as u128) * ((reserve_out - result.amount_out) as u128);
+        prop_assert!(k_after >= k_before);
+    }
+
+    #[test]
+    fn swap_output_never_exceeds_fee_free_quote(
+        reserve_in in reserve(),
+        reserve_out in reserve(),
+        amount_in in 1u64..=u64::MAX / 2,
+        fee in fee(),
+    ) {
+        let Ok(with_fee) = curve::swap_exact_in(reserve_in, reserve_out, amount_in, fee) else {
+            return Ok(());
+        };
+        let without_fee = curve::swap_exact_in(reserve_in, reserve_out, amount_in, 0).unwrap();
+
+        prop_assert!(with_fee.amount_out <= without_fee.amount_out);
+    }
+
+    #[test]
+    fn swap_exact_out_never_decreases_k(
+        reserve_in in reserve(),
+        reserve_out in reserve(),
+        amount_out in 1u64..=u64::MAX / 2,
+        fee in fee(),
+    ) {
+        let Ok(result) = curve::swap_exact_out(reserve_in, reserve_out, amount_out, fee) else {
+            return Ok(());
+        };
+
+        prop_assert_eq!(result.amount_out, amount_out);
+        prop_assert!(result.fee <= result.amount_in);
+
+        let k_before = (reserve_in as u128) * (reserve_out as u128);
+        let k_after = ((reserve_in + result.amount_in) as u128) * ((reserve_out - amount_out) as u128);
+        prop_assert!(k_after >= k_before);
+    }
+
+    #[test]
+    fn swap_exact_out_input_buys_at_least_requested_output(
+        reserve_in in reserve(),
+        reserve_out in reserve(),
+        amount_out in 1u64..=u64::MAX / 2,
+        fee in fee(),
+    ) {
+        let Ok(exact_out) = curve::swap_exact_out(reserve_in, reserve_out, amount_out, fee) else {
+            return Ok(());
+        };
+        let exact_in = curve::swap_exact_in(reserve_in, reserve_out, exact_out.amount_in, fee).unwrap();
+
+        // 用精确输出算出的输入去做精确输入交换，得到的输出不会少于请求的数量
+        prop_assert!(exact_in.amount_out >= amount_out);
+    }
+
+    #[test]
+    fn swap_round_trip_never_profits(
+        reserve_x in reserve(),
+        reserve_y in reserve(),
+        amount_in in 1u64..=u64::MAX / 4,
+        fee in fee(),
+    ) {
+        let Ok(first) = curve::swap_exact_in(reserve_x, reserve_y, amount_in, fee) else {
+            return Ok(());
+        };
+        let Ok(second) = curve::swap_exact_in(
+            reserve_y - first.amount_out,
+            reserve_x + amount_in,
+            first.amount_out,
+            fee,
+        ) else {
+            return Ok(());
+        };
+
+        prop_assert!(second.amount_out <= amount_in);
+    }
+}
+
+// =============================================================================
+// 存款 / 取款
+// =============================================================================
+proptest! {
+    #[test]
+    fn deposit_never_decreases_value_per_share(
+        reserve_x in reserve(),
+        reserve_y in reserve(),
+        supply in 1u64..=u64::MAX / 2,
+        lp in 1u64..=u64::MAX / 2,
+    ) {
+        let Ok(amounts) = curve::deposit_amounts(reserve_x, reserve_y, supply, lp) else {
+            return Ok(());
+        };
+
+        // (reserve + amount) / (supply + lp) >= reserve / supply
+        prop_assert!(ratio_le(reserve_x, supply, reserve_x + amounts.x, supply + lp));
+        prop_assert!(ratio_le(reserve_y, supply, reserve_y + amounts.y, supply + lp));
+    }
+
+    #[test]
+    fn withdraw_never_decreases_value_per_share(
+        reserve_x in reserve(),
+        reserve_y in reserve(),
+        supply in 2u64..=u64::MAX / 2,
+        lp_seed in any::<u64>(),
+    ) {
+        // 不取出全部 LP，否则剩余供应量为 0
+        let lp = lp_seed % (supply - 1) + 1;
+        let amounts = curve::withdraw_amounts(reserve_x, reserve_y, supply, lp).unwrap();
+
+        prop_assert!(amounts.x <= reserve_x);
+        prop_assert!(amounts.y <= reserve_y);
+
+        // (reserve - amount) / (supply - lp) >= reserve / supply
+        prop_assert!(ratio_le(reserve_x, supply, reserve_x - amounts.x, supply - lp));
+        prop_assert!(ratio_le(reserve_y, supply, reserve_y - amounts.y, supply - lp));
+    }
+
+    #[test]
+    fn deposit_then_withdraw_never_profits(
+        reserve_x in reserve(),
+        reserve_y in reserve(),
+        supply in 1u64..=u64::MAX / 2,
+        lp in 1u64..=u64::MAX / 2,
+    ) {
+        let Ok(deposited) = curve::deposit_amounts(reserve_x, reserve_y, supply, lp) else {
+            return Ok(());
+        };
+        let withdrawn = curve::withdraw_amounts(
+            reserve_x + deposited.x,
+            reserve_y + deposited.y,
+            supply + lp,
+            lp,
+        ).unwrap();
+
+        prop_assert!(withdrawn.x <= deposited.x);
+        prop_assert!(withdrawn.y <= deposited.y);
+    }
+}
+
+// =============================================================================
+// 首次存款
+// =============================================================================
+// 铸造的 LP 总量 (lp + MINIMUM_LIQUIDITY) 是 sqrt(x * y) 向下取整
+proptest! {
+    #[test]
+    fn initial_liquidity_is_floor_sqrt(x in 1u64..=u64::MAX, y in 1u64..=u64::MAX) {
+        let product = x as u128 * y as u128;
+        match curve::initial_liquidity(x, y) {
+            Ok(lp) => {
+                let total = (lp + curve::MINIMUM_LIQUIDITY) as u128;
+                prop_assert!(total * total <= product);
+                prop_assert!((total + 1).checked_mul(total + 1).is_none_or(|square| square > product));
+            }
+            Err(error) => {
+                prop_assert_eq!(error, CurveError::InsufficientLiquidity);
+                prop_assert!(product < (curve::MINIMUM_LIQUIDITY as u128 + 1).pow(2));
+            }
+        }
+    }
+}
+
+// =============================================================================
+// 协议费
+// =============================================================================
+
+proptest! {
+    // 协议费从池子储备中划出后，交换仍不会让 k 减少
+    #[test]
+    fn swap_never_decreases_k_after_protocol_fee(
+        reserve_in in reserve(),
+        reserve_out in reserve(),
+        amount_in in 1u64..=u64::MAX / 2,
+        fee in fee(),
+        protocol_fee in 0u16..=FEE_DENOMINATOR,
+    ) {
+        let Ok(result) = curve::swap_exact_in(reserve_in, reserve_out, amount_in, fee) else {
+            return Ok(());
+        };
+        let protocol = curve::protocol_fee(result.fee, protocol_fee).unwrap();
+        prop_assert!(protocol <= result.fee);
+
+        let k_before = reserve_in as u128 * reserve_out as u128;
+        let k_after = (reserve_in + result.amount_in - protocol) as u128
+            * (reserve_out - result.amount_out) as u128;
+        prop_assert!(k_after >= k_before);
+    }
+}
+
+// =============================================================================
+// 单边存入 / 取出
+// =============================================================================
+// 单边操作会改变两种储备的比例，因此用 sqrt(k) / supply 衡量每份 LP 的价值：
+// k_after * supply_before^2 >= k_before * supply_after^2
+// 数值范围限制在 2^30 以内，保证乘积不超过 u128
+
+fn small() -> impl Strategy<Value = u64> {
+    1u64..=1 << 30
+}
+
+fn value_per_share_not_decreased(
+    (reserve_x, reserve_y, supply): (u64, u64, u64),
+    (new_reserve_x, new_reserve_y, new_supply): (u64, u64, u64),
+) -> bool {
+    let k_before = reserve_x as u128 * reserve_y as u128;
+    let k_after = new_reserve_x as u128 * new_reserve_y as u128;
+    k_after * (supply as u128).pow(2) >= k_before * (new_supply as u128).pow(2)
+}
+
+proptest! {
+    #[test]
+    fn zap_in_never_decreases_value_per_share(
+        reserve_in in small(),
+        reserve_out in small(),
+        supply in small(),
+        amount_in in small(),
+        fee in fee(),
+    ) {
+        let Ok(result) = curve::zap_in(reserve_in, reserve_out, supply, amount_in, fee) else {
+            return Ok(());
+        };
+
+        prop_assert!(result.amount_in <= amount_in);
+        prop_assert!(result.swap_amount <= result.amount_in);
+        prop_assert!(result.lp > 0);
+
+        // 换得的代币留在池子里，金库中只有输入一侧增加
+        prop_assert!(value_per_share_not_decreased(
+            (reserve_in, reserve_out, supply),
+            (reserve_in + result.amount_in, reserve_out, supply + result.lp),
+        ));
+    }
+
+    #[test]
+    fn zap_out_never_decreases_value_per_share(
+        reserve_keep in small(),
+        reserve_swap in small(),
+        supply in 2u64..=1 << 30,
+        lp_seed in any::<u64>(),
+        fee in fee(),
+    ) {
+        let lp = lp_seed % (supply - 1) + 1;
+        let Ok(result) = curve::zap_out(reserve_keep, reserve_swap, supply, lp, fee) else {
+            return Ok(());
+        };
+
+        prop_assert!(result.amount_out < reserve_keep);
+
+        // 换掉的代币留在池子里，金库中只有保留一侧减少
+        prop_assert!(value_per_share_not_decreased(
+            (reserve_keep, reserve_swap, supply),
+            (reserve_keep - result.amount_out, reserve_swap, supply - lp),
+        ));
+    }
+
+    #[test]
+    fn zap_in_then_zap_out_never_profits(
+        reserve_in in small(),
+        reserve_out in small(),
+        supply in small(),
+        amount_in in small(),
+        fee in fee(),
+    ) {
+        let Ok(zap_in) = curve::zap_in(reserve_in, reserve_out, supply, amount_in, fee) else {
+            return Ok(());
+        };
+        let Ok(zap_out) = curve::zap_out(
+            reserve_in + zap_in.amount_in,
+            reserve_out,
+            supply + zap_in.lp,
+            zap_in.lp,
+            fee,
+        ) else {
+            return Ok(());
+        };
+
+        prop_assert!(zap_out.amount_out <= zap_in.amount_in);
+    }
+}
+
+// =============================================================================
+// 边界情况
+// =============================================================================
+#[test]
+fn swap_rejects_empty_pool_and_zero_amount() {
+    assert_eq!(curve::swap_exact_in(0, 100, 10, 30), Err(CurveError::ZeroLiquidity));
+    assert_eq!(curve::swap_exact_in(100, 0, 10, 30), Err(CurveError::ZeroLiquidity));
+    assert_eq!(curve::swap_exact_in(100, 100, 0, 30), Err(CurveError::ZeroAmount));
+    assert_eq!(curve::swap_exact_in(100, 100, 10, FEE_DENOMINATOR), Err(CurveError::InvalidFee));
+}
+
+#[test]
+fn swap_rounds_output_down() {
+    // 无手续费：100 * 10 / (100 + 10) = 9.09 -> 9
+    let result = curve::swap_exact_in(100, 100, 10, 0).unwrap();
+    assert_eq!(result.amount_out, 9);
+    assert_eq!(result.fee, 0);
+
+    // 30 bps：有效输入 9970 * 1_000_000 / (1_000_000 + 9970) = 9871.58 -> 9871
+    let result = curve::swap_exact_in(1_000_000, 1_000_000, 10_000, 30).unwrap();
+    assert_eq!(result.fee, 30);
+    assert_eq!(result.amount_out, 9871);
+}
+
+#[test]
+fn swap_exact_out_rounds_input_up() {
+    // 无手续费：100 * 9 / (100 - 9) = 9.89 -> 10
+    let result = curve::swap_exact_out(100, 100, 9, 0).unwrap();
+    assert_eq!(result.amount_in, 10);
+    assert_eq!(result.fee, 0);
+
+    // 不能取走全部或超过储备
+    assert_eq!(curve::swap_exact_out(100, 100, 100, 0), Err(CurveError::InsufficientLiquidity));
+    assert_eq!(curve::swap_exact_out(100, 100, 0, 0), Err(CurveError::ZeroAmount));
+}
+
+#[test]
+fn deposit_rounds_up_and_withdraw_rounds_down() {
+    // 10 * 1 / 3 = 3.33
+    let deposit = curve::deposit_amounts(10, 10, 3, 1).unwrap();
+    assert_eq!((deposit.x, deposit.y), (4, 4));
+
+    let withdraw = curve::withdraw_amounts(10, 10, 3, 1).unwrap();
+    assert_eq!((withdraw.x, withdraw.y), (3, 3));
+}
+
+#[test]
+fn zap_in_uses_roughly_half_for_swap_in_a_balanced_pool() {
+    let result = curve::zap_in(1_000_000, 1_000_000, 1_000_000, 10_000, 30).unwrap();
+
+    // 无手续费时略少于一半，手续费让需要交换的部分略多一点
+    assert!(result.swap_amount > 4_950 && result.swap_amount < 5_050);
+    assert!(result.amount_in <= 10_000);
+    assert!(result.lp > 0);
+}
+
+#[test]
+fn zap_charges_protocol_fee_on_internal_swap() {
+    let cp = curve::Curve::ConstantProduct;
+
+    // 协议费与同样数量的 Swap 相同
+    let zap_in = curve::zap_in_with(cp, 1_000_000, 1_000_000, 1_000_000, 10_000, 30, 5_000).unwrap();
+    let swap = curve::swap_exact_in(1_000_000, 1_000_000, zap_in.swap_amount, 30).unwrap();
+    assert_eq!(zap_in.protocol_fee, curve::protocol_fee(swap.fee, 5_000).unwrap());
+    assert!(zap_in.protocol_fee > 0);
+
+    // 协议费不计入储备，LP 不会多于不收协议费时
+    let without = curve::zap_in(1_000_000, 1_000_000, 1_000_000, 10_000, 30).unwrap();
+    assert_eq!(without.protocol_fee, 0);
+    assert!(zap_in.lp <= without.lp);
+
+    let zap_out = curve::zap_out_with(cp, 1_000_000, 1_000_000, 1_000_000, 10_000, 30, 5_000).unwrap();
+    assert!(zap_out.protocol_fee > 0);
+    assert_eq!(curve::zap_out(1_000_000, 1_000_000, 1_000_000, 10_000, 30).unwrap().protocol_fee, 0);
+}
+
+#[test]
+fn protocol_fee_rounds_down_and_rejects_more_than_whole_fee() {
+    assert_eq!(curve::protocol_fee(30, 5_000), Ok(15));
+    assert_eq!(curve::protocol_fee(3, 5_000), Ok(1));
+    assert_eq!(curve::protocol_fee(30, 10_000), Ok(30));
+    assert_eq!(curve::protocol_fee(30, 10_001), Err(CurveError::InvalidFee));
+}
+
+#[test]
+fn flash_loan_fee_rounds_up() {
+    assert_eq!(curve::flash_loan_fee(100_000, 30), Ok(300));
+    assert_eq!(curve::flash_loan_fee(1, 30), Ok(1));
+    assert_eq!(curve::flash_loan_fee(1, 0), Ok(0));
+    assert_eq!(curve::flash_loan_fee(1, 10_000), Err(CurveError::InvalidFee));
+}
+
+#[test]
+fn zap_out_rejects_last_liquidity() {
+    assert_eq!(curve::zap_out(100, 100, 10, 10, 30), Err(CurveError::InsufficientLiquidity));
+}
+
+#[test]
+fn withdraw_all_returns_entire_reserves() {
+    let withdraw = curve::withdraw_amounts(7, 11, 5, 5).unwrap();
+    assert_eq!((withdraw.x, withdraw.y), (7, 11));
+
+    assert_eq!(curve::withdraw_amounts(7, 11, 5, 6), Err(CurveError::InsufficientLiquidity));
+}
+
+#[test]
+fn initial_liquidity_locks_minimum() {
+    assert_eq!(curve::initial_liquidity(1_000_000, 4_000_000), Ok(2_000_000 - curve::MINIMUM_LIQUIDITY));
+    assert_eq!(curve::initial_liquidity(u64::MAX, u64::MAX), Ok(u64::MAX - curve::MINIMUM_LIQUIDITY));
+    assert_eq!(curve::initial_liquidity(1_000, 1_000), Err(CurveError::InsufficientLiquidity));
+    assert_eq!(curve::initial_liquidity(0, 1_000_000), Err(CurveError::ZeroAmount));
+}
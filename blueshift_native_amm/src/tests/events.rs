@@ -0,0 +1,136 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::events::{
+    kind, parse_logs, AmmEvent, DepositEvent, Event, StateChangedEvent, SwapEvent, EVENT_VERSION,
+};
+
+const PROGRAM: &str = "22222222222222222222222222222222222222222222";
+const OTHER: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+
+fn swap_event() -> SwapEvent {
+    SwapEvent {
+        pool: [1u8; 32],
+        user: [2u8; 32],
+        is_x: 1,
+        amount_in: 1_000,
+        amount_out: 987,
+        fee: 3,
+        protocol_fee: 1,
+        reserve_x: 101_000,
+        reserve_y: 99_013,
+    }
+}
+
+fn deposit_event() -> DepositEvent {
+    DepositEvent {
+        pool: [1u8; 32],
+        user: [3u8; 32],
+        amount_x: 500,
+        amount_y: 0,
+        swap_amount: 250,
+        lp_amount: 240,
+        reserve_x: 101_500,
+        reserve_y: 99_013,
+        lp_supply: 100_240,
+    }
+}
+
+// 与 helpers::emit 传给 sol_log_data 的字段相同，按运行时的格式编码成一行日志
+fn log_line<E: Event>(event: &E) -> String {
+    format!(
+        "Program data: {} {}",
+        STANDARD.encode([E::KIND, EVENT_VERSION]),
+        STANDARD.encode(event.as_bytes()),
+    )
+}
+
+#[test]
+fn event_round_trips_through_log_line() {
+    let swap = swap_event();
+    assert_eq!(AmmEvent::from_log(&log_line(&swap)), Some(AmmEvent::Swap(swap)));
+
+    let deposit = deposit_event();
+    assert_eq!(AmmEvent::from_log(&log_line(&deposit)), Some(AmmEvent::Deposit(deposit)));
+
+    // 长度不是 3 的倍数的事件需要填充
+    let state = StateChangedEvent {
+        pool: [1u8; 32],
+        authority: [4u8; 32],
+        state: 2,
+    };
+    assert_eq!(size_of::<StateChangedEvent>() % 3, 2);
+    assert_eq!(AmmEvent::from_log(&log_line(&state)), Some(AmmEvent::StateChanged(state)));
+}
+
+#[test]
+fn decode_rejects_unknown_kind_version_zero_and_short_data() {
+    let swap = swap_event();
+    let data = [&[kind::SWAP, EVENT_VERSION][..], swap.as_bytes()].concat();
+    assert_eq!(AmmEvent::decode(&data), Some(AmmEvent::Swap(swap)));
+
+    let mut unknown = data.clone();
+    unknown[0] = 200;
+    assert_eq!(AmmEvent::decode(&unknown), None);
+
+    let mut version_zero = data.clone();
+    version_zero[1] = 0;
+    assert_eq!(AmmEvent::decode(&version_zero), None);
+
+    assert_eq!(AmmEvent::decode(&data[..data.len() - 1]), None);
+    assert_eq!(AmmEvent::decode(&[]), None);
+}
+
+#[test]
+fn newer_versions_with_appended_fields_still_decode() {
+    let swap = swap_event();
+    let data = [&[kind::SWAP, EVENT_VERSION + 1][..], swap.as_bytes(), &[9u8; 16]].concat();
+
+    assert_eq!(AmmEvent::decode(&data), Some(AmmEvent::Swap(swap)));
+}
+
+#[test]
+fn from_log_rejects_malformed_lines() {
+    assert_eq!(AmmEvent::from_log("Program log: Instruction: Swap"), None);
+    assert_eq!(AmmEvent::from_log("Program data: AQ"), None);
+    assert_eq!(AmmEvent::from_log("Program data: A=E="), None);
+    assert_eq!(AmmEvent::from_log("Program data: !!!!"), None);
+
+    // 解码后超出缓冲区
+    let long = format!("Program data: {}", STANDARD.encode([0u8; 300]));
+    assert_eq!(AmmEvent::from_log(&long), None);
+}
+
+#[test]
+fn parse_logs_only_returns_events_emitted_by_the_program() {
+    let swap = swap_event();
+    let deposit = deposit_event();
+
+    let logs = [
+        // 其它程序输出的同样格式的数据
+        format!("Program {OTHER} invoke [1]"),
+        log_line(&deposit),
+        format!("Program {OTHER} success"),
+        format!("Program {PROGRAM} invoke [1]"),
+        "Program log: Instruction: Swap".to_string(),
+        // CPI 到 Token Program 期间输出的数据不属于本程序
+        format!("Program {OTHER} invoke [2]"),
+        log_line(&deposit),
+        format!("Program {OTHER} consumed 4645 of 190000 compute units"),
+        format!("Program {OTHER} success"),
+        log_line(&swap),
+        format!("Program {PROGRAM} consumed 20000 of 200000 compute units"),
+        format!("Program {PROGRAM} success"),
+        // 不在任何程序中
+        log_line(&deposit),
+        format!("Program {PROGRAM} invoke [1]"),
+        format!("Program {OTHER} invoke [2]"),
+        format!("Program {OTHER} failed: custom program error: 0x1"),
+        log_line(&deposit),
+        format!("Program {PROGRAM} success"),
+    ];
+
+    let events: Vec<AmmEvent> = parse_logs(PROGRAM, &logs).collect();
+
+    assert_eq!(events, vec![AmmEvent::Swap(swap), AmmEvent::Deposit(deposit)]);
+}
@@ -0,0 +1,631 @@
+// =============================================================================
+// AMM 集成测试夹具 - 使用 Mollusk 测试框架
+// =============================================================================
+// 运行前需要先构建程序：cargo build-sbf
+// Mollusk 从 target/deploy/blueshift_native_amm.so 加载程序
+//
+// Pool 保存所有账户的当前状态，每条指令执行成功后用结果更新账户，
+// 因此可以像在链上一样按顺序执行一系列指令
+
+use std::collections::HashMap;
+
+use mollusk_svm::program::keyed_account_for_system_program;
+use mollusk_svm::result::{Check, InstructionResult};
+use mollusk_svm::Mollusk;
+use solana_account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::program_error::ProgramError;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{AmmState, Config};
+
+// =============================================================================
+// 常量
+// =============================================================================
+pub const SEED: u64 = 42;
+pub const FEE: u16 = 30;
+pub const LP_DECIMALS: u8 = 6;
+pub const MINT_DECIMALS: u8 = 6;
+pub const USER_BALANCE: u64 = 1_000_000_000_000;
+
+// cargo build-sbf 的输出，Mollusk 按路径加载（不带 .so 后缀）
+const PROGRAM_PATH: &str = "target/deploy/blueshift_native_amm";
+
+// 测试中的当前时间，以及一个尚未过期的截止时间
+pub const NOW: i64 = 1_700_000_000;
+pub const NOT_EXPIRED: i64 = NOW + 60;
+
+// SPL Token 账户布局
+const MINT_LEN: usize = 82;
+const TOKEN_ACCOUNT_LEN: usize = 165;
+
+pub fn program_id() -> Pubkey {
+    Pubkey::new_from_array(crate::ID.to_bytes())
+}
+
+pub fn token_program_id() -> Pubkey {
+    mollusk_svm_programs_token::token::keyed_account().0
+}
+
+pub fn associated_token_program_id() -> Pubkey {
+    mollusk_svm_programs_token::associated_token::keyed_account().0
+}
+
+pub fn bpf_loader_upgradeable_id() -> Pubkey {
+    Pubkey::new_from_array(crate::layout::BPF_LOADER_UPGRADEABLE_ID.to_bytes())
+}
+
+// 本程序的 ProgramData：[program_id] 在 BPF Upgradeable Loader 下的 PDA
+pub fn program_data_address() -> Pubkey {
+    Pubkey::find_program_address(&[program_id().as_ref()], &bpf_loader_upgradeable_id()).0
+}
+
+pub fn metadata_program_id() -> Pubkey {
+    Pubkey::new_from_array(crate::metadata::METADATA_PROGRAM_ID.to_bytes())
+}
+
+// Mint 的 Metaplex Metadata：[b"metadata", METADATA_PROGRAM_ID, mint]
+pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[crate::metadata::METADATA_SEED, metadata_program_id().as_ref(), mint.as_ref()],
+        &metadata_program_id(),
+    )
+    .0
+}
+
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), token_program_id().as_ref(), mint.as_ref()],
+        &associated_token_program_id(),
+    )
+    .0
+}
+
+// =============================================================================
+// 账户数据
+// =============================================================================
+
+// 已初始化的 Mint：[mint_authority: COption<Pubkey>][supply][decimals][is_initialized][freeze_authority]
+pub fn mint_account(mollusk: &Mollusk, authority: &Pubkey, supply: u64) -> Account {
+    let mut data = vec![0u8; MINT_LEN];
+    data[0..4].copy_from_slice(&1u32.to_le_bytes());
+    data[4..36].copy_from_slice(authority.as_ref());
+    data[36..44].copy_from_slice(&supply.to_le_bytes());
+    data[44] = MINT_DECIMALS;
+    data[45] = 1;
+
+    Account {
+        lamports: mollusk.sysvars.rent.minimum_balance(MINT_LEN),
+        data,
+        owner: token_program_id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+// 已初始化的 Token Account：[mint][owner][amount][delegate][state][is_native][delegated_amount][close_authority]
+pub fn token_account(mollusk: &Mollusk, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+    let mut data = vec![0u8; TOKEN_ACCOUNT_LEN];
+    data[0..32].copy_from_slice(mint.as_ref());
+    data[32..64].copy_from_slice(owner.as_ref());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    data[108] = 1;
+
+    Account {
+        lamports: mollusk.sysvars.rent.minimum_balance(TOKEN_ACCOUNT_LEN),
+        data,
+        owner: token_program_id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+// =============================================================================
+// 池子夹具
+// =============================================================================
+pub struct Pool {
+    pub mollusk: Mollusk,
+    pub accounts: HashMap<Pubkey, Account>,
+
+    pub user: Pubkey,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub mint_lp: Pubkey,
+    pub config: Pubkey,
+    pub vault_x: Pubkey,
+    pub vault_y: Pubkey,
+    pub user_x_ata: Pubkey,
+    pub user_y_ata: Pubkey,
+    pub user_lp_ata: Pubkey,
+    pub locked_lp_owner: Pubkey,
+    pub locked_lp_ata: Pubkey,
+}
+
+impl Pool {
+    // 创建 Mollusk 环境、两个 Mint 和用户的代币账户，池子尚未初始化
+    pub fn setup() -> Self {
+        Self::setup_with_order(true)
+    }
+
+    // 与 setup 相同，但 mint_x 和 mint_y 不是规范顺序，模拟注册表创建之前的池子
+    pub fn setup_non_canonical() -> Self {
+        Self::setup_with_order(false)
+    }
+
+    fn setup_with_order(canonical: bool) -> Self {
+        assert!(
+            std::path::Path::new(&format!("{PROGRAM_PATH}.so")).exists(),
+            "{PROGRAM_PATH}.so 不存在，运行集成测试前先执行 cargo build-sbf",
+        );
+        let mut mollusk = Mollusk::new(&program_id(), PROGRAM_PATH);
+        mollusk_svm_programs_token::token::add_program(&mut mollusk);
+        mollusk_svm_programs_token::associated_token::add_program(&mut mollusk);
+        mollusk.sysvars.clock.unix_timestamp = NOW;
+
+        let user = Pubkey::new_unique();
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mint_x, mint_y) = if (mint_a < mint_b) == canonical { (mint_a, mint_b) } else { (mint_b, mint_a) };
+
+        let (config, _) = Pubkey::find_program_address(
+            &[b"config", &SEED.to_le_bytes(), mint_x.as_ref(), mint_y.as_ref()],
+            &program_id(),
+        );
+        let (mint_lp, _) = Pubkey::find_program_address(&[b"mint_lp", config.as_ref()], &program_id());
+
+        let vault_x = associated_token_address(&config, &mint_x);
+        let vault_y = associated_token_address(&config, &mint_y);
+        let user_x_ata = associated_token_address(&user, &mint_x);
+        let user_y_ata = associated_token_address(&user, &mint_y);
+        let user_lp_ata = associated_token_address(&user, &mint_lp);
+        let (locked_lp_owner, _) = Pubkey::find_program_address(&[b"locked_lp", config.as_ref()], &program_id());
+        let locked_lp_ata = associated_token_address(&locked_lp_owner, &mint_lp);
+
+        let mut accounts = HashMap::new();
+        let mint_authority = Pubkey::new_unique();
+        accounts.insert(user, Account::new(100_000_000_000, 0, &Pubkey::default()));
+        accounts.insert(mint_x, mint_account(&mollusk, &mint_authority, USER_BALANCE));
+        accounts.insert(mint_y, mint_account(&mollusk, &mint_authority, USER_BALANCE));
+        accounts.insert(user_x_ata, token_account(&mollusk, &mint_x, &user, USER_BALANCE));
+        accounts.insert(user_y_ata, token_account(&mollusk, &mint_y, &user, USER_BALANCE));
+
+        let (system_program, system_program_account) = keyed_account_for_system_program();
+        let (token_program, token_program_account) = mollusk_svm_programs_token::token::keyed_account();
+        let (ata_program, ata_program_account) = mollusk_svm_programs_token::associated_token::keyed_account();
+        accounts.insert(system_program, system_program_account);
+        accounts.insert(token_program, token_program_account);
+        accounts.insert(ata_program, ata_program_account);
+
+        // 本程序的 ProgramData，用户是升级权限：[tag = 3][slot][Some(authority)]
+        let mut program_data = 3u32.to_le_bytes().to_vec();
+        program_data.extend_from_slice(&0u64.to_le_bytes());
+        program_data.push(1);
+        program_data.extend_from_slice(user.as_ref());
+        accounts.insert(
+            program_data_address(),
+            Account {
+                lamports: mollusk.sysvars.rent.minimum_balance(program_data.len()),
+                data: program_data,
+                owner: bpf_loader_upgradeable_id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+
+        Self {
+            mollusk,
+            accounts,
+            user,
+            mint_x,
+            mint_y,
+            mint_lp,
+            config,
+            vault_x,
+            vault_y,
+            user_x_ata,
+            user_y_ata,
+            user_lp_ata,
+            locked_lp_owner,
+            locked_lp_ata,
+        }
+    }
+
+    // 写入最初发布的程序创建的 108 字节 Config（版本 0），用户是 authority
+    // 版本 0 没有 pending_authority、lp_bump 和版本字节
+    pub fn with_baseline_config() -> Self {
+        let mut pool = Self::setup();
+        pool.insert_baseline_config();
+        pool
+    }
+
+    pub fn insert_baseline_config(&mut self) {
+        let (_, config_bump) = Pubkey::find_program_address(
+            &[b"config", &SEED.to_le_bytes(), self.mint_x.as_ref(), self.mint_y.as_ref()],
+            &program_id(),
+        );
+
+        let mut data = vec![AmmState::Initialized as u8];
+        data.extend_from_slice(&SEED.to_le_bytes());
+        data.extend_from_slice(self.user.as_ref());
+        data.extend_from_slice(self.mint_x.as_ref());
+        data.extend_from_slice(self.mint_y.as_ref());
+        data.extend_from_slice(&FEE.to_le_bytes());
+        data.push(config_bump);
+        assert_eq!(data.len(), Config::LEN_V0);
+
+        let config = Account {
+            lamports: self.mollusk.sysvars.rent.minimum_balance(data.len()),
+            data,
+            owner: program_id(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.accounts.insert(self.config, config);
+    }
+
+    // 创建注册表（每个手续费档位只能有一个池子）后初始化池子，并为用户创建 LP 代币账户
+    pub fn initialized() -> Self {
+        let mut pool = Self::setup();
+        pool.process_ok(&pool.initialize_registry_ix(true));
+        pool.process_ok(&pool.initialize_ix());
+
+        let lp_account = token_account(&pool.mollusk, &pool.mint_lp, &pool.user, 0);
+        pool.accounts.insert(pool.user_lp_ata, lp_account);
+        pool
+    }
+
+    // 初始化池子并完成首次存款，共铸造 sqrt(x * y) 份 LP，其中 MINIMUM_LIQUIDITY 份被锁定
+    pub fn with_liquidity(x: u64, y: u64) -> Self {
+        let mut pool = Self::initialized();
+        pool.process_ok(&pool.first_deposit_ix(0, x, y, NOT_EXPIRED));
+        pool
+    }
+
+    // =========================================================================
+    // 执行指令
+    // =========================================================================
+
+    pub fn process(&mut self, instruction: &Instruction, checks: &[Check]) -> InstructionResult {
+        let accounts: Vec<(Pubkey, Account)> = instruction
+            .accounts
+            .iter()
+            .map(|meta| {
+                let account = self.accounts.get(&meta.pubkey).cloned().unwrap_or_default();
+                (meta.pubkey, account)
+            })
+            .collect();
+
+        let result = self
+            .mollusk
+            .process_and_validate_instruction(instruction, &accounts, checks);
+
+        // 只有成功的指令才会改变账户
+        if result.program_result.is_ok() {
+            for (key, account) in &result.resulting_accounts {
+                self.accounts.insert(*key, account.clone());
+            }
+        }
+        result
+    }
+
+    pub fn process_ok(&mut self, instruction: &Instruction) -> InstructionResult {
+        self.process(instruction, &[Check::success()])
+    }
+
+    pub fn process_err(&mut self, instruction: &Instruction, error: ProgramError) -> InstructionResult {
+        self.process(instruction, &[Check::err(error)])
+    }
+
+    // =========================================================================
+    // 读取状态
+    // =========================================================================
+
+    pub fn token_amount(&self, account: &Pubkey) -> u64 {
+        let data = &self.accounts[account].data;
+        u64::from_le_bytes(data[64..72].try_into().unwrap())
+    }
+
+    pub fn lp_supply(&self) -> u64 {
+        let data = &self.accounts[&self.mint_lp].data;
+        u64::from_le_bytes(data[36..44].try_into().unwrap())
+    }
+
+    pub fn reserves(&self) -> (u64, u64) {
+        (self.token_amount(&self.vault_x), self.token_amount(&self.vault_y))
+    }
+
+    pub fn config_account(&self) -> &Config {
+        Config::from_bytes(&self.accounts[&self.config].data).unwrap()
+    }
+
+    pub fn state(&self) -> u8 {
+        let data = &self.accounts[&self.config].data;
+        assert_eq!(data.len(), Config::LEN);
+        unsafe { Config::from_bytes_unchecked(data) }.state()
+    }
+
+    // =========================================================================
+    // 指令构造
+    // =========================================================================
+
+    pub fn initialize_ix(&self) -> Instruction {
+        let mut data = vec![*crate::Initialize::DISCRIMINATOR];
+        data.extend_from_slice(&SEED.to_le_bytes());
+        data.extend_from_slice(&FEE.to_le_bytes());
+        data.push(LP_DECIMALS);
+        data.extend_from_slice(self.user.as_ref());
+
+        Instruction::new_with_bytes(
+            program_id(),
+            &data,
+            vec![
+                AccountMeta::new(self.user, true),
+                AccountMeta::new(self.mint_lp, false),
+                AccountMeta::new_readonly(self.mint_x, false),
+                AccountMeta::new_readonly(self.mint_y, false),
+                AccountMeta::new(self.vault_x, false),
+                AccountMeta::new(self.vault_y, false),
+                AccountMeta::new(self.config, false),
+                AccountMeta::new_readonly(token_program_id(), false),
+                AccountMeta::new_readonly(token_program_id(), false),
+                AccountMeta::new_readonly(token_program_id(), false),
+                AccountMeta::new_readonly(keyed_account_for_system_program().0, false),
+                AccountMeta::new_readonly(associated_token_program_id(), false),
+                AccountMeta::new(self.registry(), false),
+                AccountMeta::new(self.pair_record(), false),
+            ],
+        )
+    }
+
+    // Deposit 和 Withdraw 使用相同的账户
+    fn liquidity_accounts(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.user, true),
+            AccountMeta::new(self.mint_lp, false),
+            AccountMeta::new_readonly(self.mint_x, false),
+            AccountMeta::new_readonly(self.mint_y, false),
+            AccountMeta::new(self.vault_x, false),
+            AccountMeta::new(self.vault_y, false),
+            AccountMeta::new(self.user_x_ata, false),
+            AccountMeta::new(self.user_y_ata, false),
+            AccountMeta::new(self.user_lp_ata, false),
+            AccountMeta::new(self.config, false),
+            AccountMeta::new_readonly(token_program_id(), false),
+            AccountMeta::new_readonly(token_program_id(), false),
+            AccountMeta::new_readonly(token_program_id(), false),
+        ]
+    }
+
+    pub fn deposit_ix(&self, lp: u64, max_x: u64, max_y: u64, expirations: i64) -> Instruction {
+        let mut data = vec![*crate::Deposit::DISCRIMINATOR];
+        data.extend_from_slice(&lp.to_le_bytes());
+        data.extend_from_slice(&max_x.to_le_bytes());
+        data.extend_from_slice(&max_y.to_le_bytes());
+        data.extend_from_slice(&expirations.to_le_bytes());
+
+        Instruction::new_with_bytes(program_id(), &data, self.liquidity_accounts())
+    }
+
+    // 首次存款：额外传入锁定最低流动性需要的账户，lp 为可接受的最少 LP
+    pub fn first_deposit_ix(&self, lp: u64, x: u64, y: u64, expirations: i64) -> Instruction {
+        let mut instruction = self.deposit_ix(lp, x, y, expirations);
+        instruction.accounts.extend([
+            AccountMeta::new_readonly(self.locked_lp_owner, false),
+            AccountMeta::new(self.locked_lp_ata, false),
+            AccountMeta::new_readonly(keyed_account_for_system_program().0, false),
+            AccountMeta::new_readonly(associated_token_program_id(), false),
+        ]);
+        instruction
+    }
+
+    pub fn withdraw_ix(&self, lp: u64, min_x: u64, min_y: u64, expirations: i64) -> Instruction {
+        let mut data = vec![*crate::Withdraw::DISCRIMINATOR];
+        data.extend_from_slice(&lp.to_le_bytes());
+        data.extend_from_slice(&min_x.to_le_bytes());
+        data.extend_from_slice(&min_y.to_le_bytes());
+        data.extend_from_slice(&expirations.to_le_bytes());
+
+        Instruction::new_with_bytes(program_id(), &data, self.liquidity_accounts())
+    }
+
+    pub fn swap_ix(&self, is_x: bool, amount: u64, min: u64, expirations: i64) -> Instruction {
+        let mut data = vec![*crate::Swap::DISCRIMINATOR, is_x as u8];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&min.to_le_bytes());
+        data.extend_from_slice(&expirations.to_le_bytes());
+
+        Instruction::new_with_bytes(
+            program_id(),
+            &data,
+            vec![
+                AccountMeta::new(self.user, true),
+                AccountMeta::new_readonly(self.mint_x, false),
+                AccountMeta::new_readonly(self.mint_y, false),
+                AccountMeta::new(self.user_x_ata, false),
+                AccountMeta::new(self.user_y_ata, false),
+                AccountMeta::new(self.vault_x, false),
+                AccountMeta::new(self.vault_y, false),
+                AccountMeta::new(self.config, false),
+                AccountMeta::new_readonly(token_program_id(), false),
+                AccountMeta::new_readonly(token_program_id(), false),
+            ],
+        )
+    }
+
+    // MigrateConfig：用户支付扩容的租金
+    pub fn migrate_config_ix(&self) -> Instruction {
+        Instruction::new_with_bytes(
+            program_id(),
+            &[*crate::MigrateConfig::DISCRIMINATOR],
+            vec![
+                AccountMeta::new(self.user, true),
+                AccountMeta::new(self.config, false),
+                AccountMeta::new_readonly(keyed_account_for_system_program().0, false),
+            ],
+        )
+    }
+
+    // Lock / Unlock / SetWithdrawOnly
+    pub fn set_state_ix(&self, state: AmmState) -> Instruction {
+        let discriminator = match state {
+            AmmState::Disabled => *crate::Lock::DISCRIMINATOR,
+            AmmState::Initialized => *crate::Unlock::DISCRIMINATOR,
+            AmmState::WithdrawOnly => *crate::SetWithdrawOnly::DISCRIMINATOR,
+            AmmState::Uninitialized => panic!("pool cannot be reset to Uninitialized"),
+        };
+
+        Instruction::new_with_bytes(
+            program_id(),
+            &[discriminator],
+            vec![
+                AccountMeta::new_readonly(self.user, true),
+                AccountMeta::new(self.config, false),
+            ],
+        )
+    }
+
+    // UpdateLpMetadata：authority 签名，metadata_program 可以替换为其他程序测试校验
+    pub fn update_lp_metadata_ix(&self, authority: Pubkey, uri: &[u8], metadata_program: Pubkey) -> Instruction {
+        let mut data = vec![*crate::UpdateLpMetadata::DISCRIMINATOR];
+        data.extend_from_slice(uri);
+
+        let metadata = metadata_address(&self.mint_lp);
+
+        Instruction::new_with_bytes(
+            program_id(),
+            &data,
+            vec![
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new(self.config, false),
+                AccountMeta::new(metadata, false),
+                AccountMeta::new_readonly(metadata_program, false),
+            ],
+        )
+    }
+
+    // CreateLpMetadata：authority 签名并支付租金，mint_x_metadata 可以替换为其他账户测试校验
+    pub fn create_lp_metadata_ix(&self, authority: Pubkey, metadata_program: Pubkey, mint_x_metadata: Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            program_id(),
+            &[*crate::CreateLpMetadata::DISCRIMINATOR],
+            vec![
+                AccountMeta::new(authority, true),
+                AccountMeta::new_readonly(self.config, false),
+                AccountMeta::new_readonly(self.mint_lp, false),
+                AccountMeta::new(metadata_address(&self.mint_lp), false),
+                AccountMeta::new_readonly(metadata_program, false),
+                AccountMeta::new_readonly(keyed_account_for_system_program().0, false),
+                AccountMeta::new_readonly(mint_x_metadata, false),
+                AccountMeta::new_readonly(metadata_address(&self.mint_y), false),
+            ],
+        )
+    }
+
+    // FlashLoan：前 10 个账户与 Swap 相同，之后是 receiver 程序和传给它的账户
+    pub fn flash_loan_ix(
+        &self,
+        amount_x: u64,
+        amount_y: u64,
+        receiver_program: Pubkey,
+        receiver_accounts: Vec<AccountMeta>,
+        receiver_data: &[u8],
+    ) -> Instruction {
+        let mut data = vec![*crate::FlashLoan::DISCRIMINATOR];
+        data.extend_from_slice(&amount_x.to_le_bytes());
+        data.extend_from_slice(&amount_y.to_le_bytes());
+        data.extend_from_slice(receiver_data);
+
+        // 账户与 Swap 相同，只替换指令数据
+        let mut instruction = self.swap_ix(true, 0, 0, NOT_EXPIRED);
+        instruction.data = data;
+        instruction.accounts.push(AccountMeta::new_readonly(receiver_program, false));
+        instruction.accounts.extend(receiver_accounts);
+        instruction
+    }
+
+    // 以 Token Program 作为 receiver：用户把 amount 个 X 转回金库
+    pub fn flash_loan_repay_x_ix(&self, borrow: u64, repay: u64) -> Instruction {
+        // Token Program 的 Transfer：[3][amount: u64]
+        let mut transfer = vec![3];
+        transfer.extend_from_slice(&repay.to_le_bytes());
+
+        self.flash_loan_ix(
+            borrow,
+            0,
+            token_program_id(),
+            vec![
+                AccountMeta::new(self.user_x_ata, false),
+                AccountMeta::new(self.vault_x, false),
+                AccountMeta::new_readonly(self.user, true),
+            ],
+            &transfer,
+        )
+    }
+
+    // 注册表和这个池子的交易对记录，mint_x 先于 mint_y 创建，两个 mint 已按规范顺序排列
+    pub fn registry(&self) -> Pubkey {
+        Pubkey::find_program_address(&[crate::layout::REGISTRY_SEED], &program_id()).0
+    }
+
+    // 交易对记录按规范顺序派生，与池子的 mint 顺序无关
+    pub fn pair_record(&self) -> Pubkey {
+        let (mint_x, mint_y) = if self.mint_x < self.mint_y {
+            (self.mint_x, self.mint_y)
+        } else {
+            (self.mint_y, self.mint_x)
+        };
+        Pubkey::find_program_address(
+            &[crate::layout::PAIR_SEED, mint_x.as_ref(), mint_y.as_ref()],
+            &program_id(),
+        )
+        .0
+    }
+
+    // InitializeRegistry：用户是程序的升级权限，同时作为 payer 和 authority
+    pub fn initialize_registry_ix(&self, one_pool_per_fee_tier: bool) -> Instruction {
+        let mut data = vec![*crate::InitializeRegistry::DISCRIMINATOR];
+        data.extend_from_slice(self.user.as_ref());
+        data.push(one_pool_per_fee_tier as u8);
+
+        Instruction::new_with_bytes(
+            program_id(),
+            &data,
+            vec![
+                AccountMeta::new(self.user, true),
+                AccountMeta::new(self.registry(), false),
+                AccountMeta::new_readonly(program_data_address(), false),
+                AccountMeta::new_readonly(Pubkey::default(), false),
+            ],
+        )
+    }
+
+    pub fn register_pool_ix(&self) -> Instruction {
+        Instruction::new_with_bytes(
+            program_id(),
+            &[*crate::RegisterPool::DISCRIMINATOR],
+            vec![
+                AccountMeta::new(self.user, true),
+                AccountMeta::new(self.registry(), false),
+                AccountMeta::new_readonly(self.config, false),
+                AccountMeta::new(self.pair_record(), false),
+                AccountMeta::new_readonly(Pubkey::default(), false),
+            ],
+        )
+    }
+
+    // UpdateFee：同时传入注册表和交易对记录以同步手续费档位，只保留前两个账户时不同步
+    pub fn update_fee_ix(&self, fee: u16) -> Instruction {
+        let mut data = vec![*crate::UpdateFee::DISCRIMINATOR];
+        data.extend_from_slice(&fee.to_le_bytes());
+
+        Instruction::new_with_bytes(
+            program_id(),
+            &data,
+            vec![
+                AccountMeta::new_readonly(self.user, true),
+                AccountMeta::new(self.config, false),
+                AccountMeta::new_readonly(self.registry(), false),
+                AccountMeta::new(self.pair_record(), false),
+            ],
+        )
+    }
+}
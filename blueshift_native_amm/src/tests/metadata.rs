@@ -0,0 +1,144 @@
+// =============================================================================
+// LP 元数据测试
+// =============================================================================
+// 名称和符号取自两个 Mint 的符号，没有元数据时使用地址的 base58 前缀
+// 指令数据按 Metaplex 的 Borsh 格式编码
+
+use solana_address::Address;
+
+use crate::metadata::{
+    create_metadata_data, read_name_and_symbol, update_metadata_data, LpTokenName, CREATE_METADATA_ACCOUNT_V3,
+    MAX_INSTRUCTION_DATA_LEN, MAX_NAME_LEN, MAX_SYMBOL_LEN, MAX_URI_LEN, UPDATE_METADATA_ACCOUNT_V2,
+};
+
+// So11111111111111111111111111111111111111112
+const WSOL: Address = Address::new_from_array([
+    0x06, 0x9b, 0x88, 0x57, 0xfe, 0xab, 0x81, 0x84, 0xfb, 0x68, 0x7f, 0x63, 0x46, 0x18, 0xc0, 0x35,
+    0xda, 0xc4, 0x39, 0xdc, 0x1a, 0xeb, 0x3b, 0x55, 0x98, 0xa0, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x01,
+]);
+
+// EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v
+const USDC: Address = Address::new_from_array([
+    0xc6, 0xfa, 0x7a, 0xf3, 0xbe, 0xdb, 0xad, 0x3a, 0x3d, 0x65, 0xf3, 0x6a, 0xab, 0xc9, 0x74, 0x31,
+    0xb1, 0xbb, 0xe4, 0xc2, 0xd2, 0xf6, 0xe0, 0xe4, 0x7c, 0xa6, 0x02, 0x03, 0x45, 0x2f, 0x5d, 0x61,
+]);
+
+// Borsh 字符串
+fn string(value: &str) -> Vec<u8> {
+    let mut bytes = (value.len() as u32).to_le_bytes().to_vec();
+    bytes.extend_from_slice(value.as_bytes());
+    bytes
+}
+
+// Metaplex Metadata 账户：[key = 4][update_authority][mint][name][symbol][uri]，字符串用 '\0' 补齐
+fn metadata_account(name: &str, symbol: &str) -> Vec<u8> {
+    let padded = |value: &str, len: usize| {
+        let mut bytes = value.as_bytes().to_vec();
+        bytes.resize(len, 0);
+        let mut field = (len as u32).to_le_bytes().to_vec();
+        field.extend(bytes);
+        field
+    };
+
+    let mut data = vec![4];
+    data.extend([0u8; 64]);
+    data.extend(padded(name, MAX_NAME_LEN));
+    data.extend(padded(symbol, MAX_SYMBOL_LEN));
+    data.extend(padded("", MAX_URI_LEN));
+    data
+}
+
+#[test]
+fn lp_name_uses_mint_symbols() {
+    let name = LpTokenName::new(&WSOL, Some("SOL"), &USDC, Some("USDC"));
+    assert_eq!(name.name(), "SOL-USDC LP");
+    assert_eq!(name.symbol(), "SOL-USDC");
+
+    // 交易对的顺序决定名称
+    assert_eq!(LpTokenName::new(&USDC, Some("USDC"), &WSOL, Some("SOL")).name(), "USDC-SOL LP");
+
+    // 符号放不下两个完整的标签时各保留 4 个字符，名称保留完整的标签
+    let name = LpTokenName::new(&WSOL, Some("JitoSOL"), &USDC, Some("USDC"));
+    assert_eq!(name.name(), "JitoSOL-USDC LP");
+    assert_eq!(name.symbol(), "Jito-USDC");
+}
+
+#[test]
+fn lp_name_falls_back_to_base58_prefixes() {
+    let name = LpTokenName::new(&WSOL, None, &USDC, None);
+    assert_eq!(name.name(), "So11-EPjF LP");
+    assert_eq!(name.symbol(), "So11-EPjF");
+
+    // 只有一侧有元数据
+    assert_eq!(LpTokenName::new(&WSOL, Some("SOL"), &USDC, None).symbol(), "SOL-EPjF");
+
+    // 空符号、非 ASCII 或以分隔符开头的符号没有可用的标签
+    assert_eq!(LpTokenName::new(&WSOL, Some(""), &USDC, Some("-X")).symbol(), "So11-EPjF");
+    assert_eq!(LpTokenName::new(&WSOL, Some("é"), &USDC, Some("USDC")).symbol(), "So11-USDC");
+
+    // 前导零字节编码为 '1'
+    let zero = Address::new_from_array([0u8; 32]);
+    assert_eq!(LpTokenName::new(&zero, None, &USDC, None).symbol(), "1111-EPjF");
+}
+
+#[test]
+fn read_name_and_symbol_trims_padding() {
+    let data = metadata_account("Wrapped SOL", "SOL");
+    assert_eq!(read_name_and_symbol(&data), Some(("Wrapped SOL", "SOL")));
+
+    let name = LpTokenName::from_metadata(&metadata_account("SOL-USDC LP", "SOL-USDC")).unwrap();
+    assert_eq!(name, LpTokenName::new(&WSOL, Some("SOL"), &USDC, Some("USDC")));
+
+    // 不是 MetadataV1 或数据不完整
+    let mut wrong_key = data.clone();
+    wrong_key[0] = 0;
+    assert_eq!(read_name_and_symbol(&wrong_key), None);
+    assert_eq!(read_name_and_symbol(&data[..100]), None);
+}
+
+#[test]
+fn create_metadata_data_matches_borsh_layout() {
+    let name = LpTokenName::new(&WSOL, None, &USDC, None);
+    let mut buffer = [0u8; MAX_INSTRUCTION_DATA_LEN];
+    let len = create_metadata_data(&name, "", &mut buffer).unwrap();
+
+    let mut expected = vec![CREATE_METADATA_ACCOUNT_V3];
+    expected.extend(string("So11-EPjF LP"));
+    expected.extend(string("So11-EPjF"));
+    expected.extend(string(""));
+    // seller_fee_basis_points, creators / collection / uses = None
+    expected.extend([0, 0, 0, 0, 0]);
+    // is_mutable = true, collection_details = None
+    expected.extend([1, 0]);
+
+    assert_eq!(&buffer[..len], expected.as_slice());
+}
+
+#[test]
+fn update_metadata_data_replaces_uri_and_keeps_name() {
+    let name = LpTokenName::new(&WSOL, None, &USDC, None);
+    let uri = "https://example.com/lp.json";
+    let mut buffer = [0u8; MAX_INSTRUCTION_DATA_LEN];
+    let len = update_metadata_data(&name, uri, &mut buffer).unwrap();
+
+    let mut expected = vec![UPDATE_METADATA_ACCOUNT_V2, 1];
+    expected.extend(string("So11-EPjF LP"));
+    expected.extend(string("So11-EPjF"));
+    expected.extend(string(uri));
+    expected.extend([0, 0, 0, 0, 0]);
+    // update_authority / primary_sale_happened / is_mutable = None
+    expected.extend([0, 0, 0]);
+
+    assert_eq!(&buffer[..len], expected.as_slice());
+}
+
+#[test]
+fn metadata_data_rejects_long_uri_and_short_buffer() {
+    let name = LpTokenName::new(&WSOL, None, &USDC, None);
+    let mut buffer = [0u8; MAX_INSTRUCTION_DATA_LEN];
+
+    let longest = "a".repeat(MAX_URI_LEN);
+    assert!(update_metadata_data(&name, &longest, &mut buffer).is_some());
+    assert_eq!(update_metadata_data(&name, &"a".repeat(MAX_URI_LEN + 1), &mut buffer), None);
+    assert_eq!(create_metadata_data(&name, "", &mut buffer[..MAX_INSTRUCTION_DATA_LEN - 1]), None);
+}
@@ -0,0 +1,12 @@
+// =============================================================================
+// 测试模块入口
+// =============================================================================
+
+pub mod curve;
+pub mod oracle;
+pub mod stable;
+pub mod quote;
+pub mod metadata;
+pub mod events;
+pub mod fixtures;
+pub mod amm;
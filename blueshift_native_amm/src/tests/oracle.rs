@@ -0,0 +1,106 @@
+use crate::curve::Curve;
+use crate::oracle::{self, Observation, TwapPrice};
+use crate::stable;
+
+const ONE: u128 = 1 << 64;
+
+fn observed(timestamp: i64) -> Observation {
+    Observation {
+        timestamp,
+        ..Observation::default()
+    }
+}
+
+#[test]
+fn spot_price_is_q64_and_rejects_empty_pool() {
+    assert_eq!(oracle::spot_price(100, 200), Some(2 * ONE));
+    assert_eq!(oracle::spot_price(200, 100), Some(ONE / 2));
+    assert_eq!(oracle::spot_price(0, 100), None);
+    assert_eq!(oracle::spot_price(100, 0), None);
+    // 最大值不会溢出 u128
+    assert!(oracle::spot_price(1, u64::MAX).is_some());
+}
+
+#[test]
+fn first_observation_only_records_time() {
+    let observation = Observation::default().advance(Curve::ConstantProduct, 100, 200, 1_000);
+
+    assert_eq!(observation, observed(1_000));
+}
+
+#[test]
+fn advance_ignores_time_going_backwards_or_standing_still() {
+    let start = observed(1_000);
+
+    assert_eq!(start.advance(Curve::ConstantProduct, 100, 200, 1_000), start);
+    assert_eq!(start.advance(Curve::ConstantProduct, 100, 200, 999), start);
+}
+
+#[test]
+fn empty_pool_advances_time_without_price() {
+    let observation = observed(1_000).advance(Curve::ConstantProduct, 0, 0, 1_010);
+
+    assert_eq!(observation, observed(1_010));
+}
+
+#[test]
+fn twap_of_constant_price_equals_spot() {
+    let start = observed(1_000);
+    let end = start.advance(Curve::ConstantProduct, 100, 200, 1_060);
+
+    assert_eq!(
+        oracle::twap(&start, &end),
+        Some(TwapPrice {
+            price_x: 2 * ONE,
+            price_y: ONE / 2,
+        })
+    );
+}
+
+#[test]
+fn twap_is_weighted_by_time() {
+    // 价格 1 维持 30 秒，价格 4 维持 10 秒：(1 * 30 + 4 * 10) / 40 = 1.75
+    let start = observed(1_000);
+    let middle = start.advance(Curve::ConstantProduct, 100, 100, 1_030);
+    let end = middle.advance(Curve::ConstantProduct, 100, 400, 1_040);
+
+    let twap = oracle::twap(&start, &end).unwrap();
+    assert_eq!(twap.price_x, ONE * 7 / 4);
+}
+
+#[test]
+fn twap_survives_cumulative_wrapping() {
+    let start = Observation {
+        price_x_cumulative: u128::MAX - ONE,
+        price_y_cumulative: u128::MAX,
+        timestamp: 1_000,
+    };
+    let end = start.advance(Curve::ConstantProduct, 100, 300, 1_010);
+
+    assert!(end.price_x_cumulative < start.price_x_cumulative);
+    let twap = oracle::twap(&start, &end).unwrap();
+    assert_eq!(twap.price_x, 3 * ONE);
+}
+
+#[test]
+fn twap_requires_a_valid_increasing_window() {
+    let start = observed(1_000);
+
+    assert_eq!(oracle::twap(&Observation::default(), &start), None);
+    assert_eq!(oracle::twap(&start, &start), None);
+    assert_eq!(oracle::twap(&start, &observed(900)), None);
+}
+
+#[test]
+fn stable_swap_twap_uses_curve_marginal_price() {
+    // 储备之比为 2，但 StableSwap 的边际价格接近 1
+    let curve = Curve::StableSwap { amp: 100 };
+    let start = observed(1_000);
+    let end = start.advance(curve, 1_000_000, 2_000_000, 1_060);
+
+    let twap = oracle::twap(&start, &end).unwrap();
+    assert_eq!(twap.price_x, stable::spot_price(100, 1_000_000, 2_000_000).unwrap());
+    assert_eq!(twap.price_y, stable::spot_price(100, 2_000_000, 1_000_000).unwrap());
+    assert!(twap.price_x > ONE && twap.price_x < ONE * 11 / 10);
+    assert!(twap.price_y < ONE && twap.price_y > ONE * 9 / 10);
+}
@@ -0,0 +1,141 @@
+// =============================================================================
+// 报价测试
+// =============================================================================
+// 1. 报价与曲线函数逐位一致，价格影响随交易量增加
+// 2. 在 Mollusk 中执行指令，报价与金库 / 用户余额的实际变化完全相同
+
+use crate::curve::{self, Curve, CurveError, MINIMUM_LIQUIDITY};
+use crate::quote::{self, quote_deposit, quote_initial_deposit, quote_swap_exact_in, quote_withdraw};
+use crate::tests::fixtures::{Pool, FEE, NOT_EXPIRED, NOW};
+
+const INITIAL_X: u64 = 1_000_000;
+const INITIAL_Y: u64 = 4_000_000;
+const INITIAL_LP: u64 = 2_000_000;
+
+// =============================================================================
+// 纯计算
+// =============================================================================
+
+#[test]
+fn swap_quote_matches_curve() {
+    for curve in [Curve::ConstantProduct, Curve::StableSwap { amp: 100 }] {
+        let expected = curve.swap_exact_in(INITIAL_X, INITIAL_Y, 10_000, FEE).unwrap();
+        let quote = quote_swap_exact_in(curve, INITIAL_X, INITIAL_Y, 10_000, FEE, 2_000).unwrap();
+
+        assert_eq!(quote.amount_in, expected.amount_in);
+        assert_eq!(quote.amount_out, expected.amount_out);
+        assert_eq!(quote.fee, expected.fee);
+        assert_eq!(quote.protocol_fee, curve::protocol_fee(expected.fee, 2_000).unwrap());
+    }
+}
+
+#[test]
+fn price_impact_grows_with_trade_size() {
+    let small = quote_swap_exact_in(Curve::ConstantProduct, INITIAL_X, INITIAL_Y, 1_000, FEE, 0).unwrap();
+    let large = quote_swap_exact_in(Curve::ConstantProduct, INITIAL_X, INITIAL_Y, 100_000, FEE, 0).unwrap();
+
+    // 恒定乘积中价格影响约为 in / (reserve_in + in)，不含手续费，小额交易另有输出取整的误差
+    assert!(small.price_impact_bps <= 12);
+    assert!((900..=910).contains(&large.price_impact_bps));
+
+    // 相同交易量下，StableSwap 在平衡池子中的价格影响远小于恒定乘积
+    let stable = quote_swap_exact_in(Curve::StableSwap { amp: 100 }, INITIAL_Y, INITIAL_Y, 100_000, FEE, 0).unwrap();
+    let constant = quote_swap_exact_in(Curve::ConstantProduct, INITIAL_Y, INITIAL_Y, 100_000, FEE, 0).unwrap();
+    assert!(stable.price_impact_bps < constant.price_impact_bps / 10);
+}
+
+#[test]
+fn liquidity_quotes_match_curve() {
+    assert_eq!(
+        quote_deposit(INITIAL_X, INITIAL_Y, INITIAL_LP, 333),
+        curve::deposit_amounts(INITIAL_X, INITIAL_Y, INITIAL_LP, 333)
+    );
+    assert_eq!(
+        quote_withdraw(INITIAL_X, INITIAL_Y, INITIAL_LP, 333),
+        curve::withdraw_amounts(INITIAL_X, INITIAL_Y, INITIAL_LP, 333)
+    );
+
+    // 首次存款的数量由用户决定
+    assert_eq!(quote_deposit(0, 0, 0, 333), Err(CurveError::ZeroLiquidity));
+}
+
+// =============================================================================
+// 与链上执行结果对比
+// =============================================================================
+
+// 从 Config 和金库余额得到报价需要的储备和曲线，与链上的读取方式相同
+fn pool_state(pool: &Pool) -> ((u64, u64), Curve, u16) {
+    let config = pool.config_account();
+    let (vault_x, vault_y) = pool.reserves();
+
+    (
+        quote::pool_reserves(config, vault_x, vault_y).unwrap(),
+        quote::pool_curve(config, NOW).unwrap(),
+        u16::from_le_bytes(*config.fee()),
+    )
+}
+
+#[test]
+fn swap_quote_matches_execution() {
+    let mut pool = Pool::with_liquidity(INITIAL_X, INITIAL_Y);
+
+    for (is_x, amount_in) in [(true, 12_345), (false, 54_321), (true, 1)] {
+        let ((reserve_x, reserve_y), curve, fee) = pool_state(&pool);
+        let (reserve_in, reserve_out) = if is_x { (reserve_x, reserve_y) } else { (reserve_y, reserve_x) };
+        let user_out = if is_x { pool.user_y_ata } else { pool.user_x_ata };
+
+        let quote = match quote_swap_exact_in(curve, reserve_in, reserve_out, amount_in, fee, 0) {
+            Ok(quote) => quote,
+            // 输出为 0 的交换在链上同样失败
+            Err(CurveError::ZeroAmount) => continue,
+            Err(error) => panic!("unexpected quote error: {error:?}"),
+        };
+        let balance_before = pool.token_amount(&user_out);
+
+        // min = 报价输出，多 1 都会因滑点失败
+        pool.process_ok(&pool.swap_ix(is_x, amount_in, quote.amount_out, NOT_EXPIRED));
+
+        assert_eq!(pool.token_amount(&user_out) - balance_before, quote.amount_out);
+        let expected = if is_x {
+            (reserve_x + quote.amount_in, reserve_y - quote.amount_out)
+        } else {
+            (reserve_x - quote.amount_out, reserve_y + quote.amount_in)
+        };
+        assert_eq!(pool.reserves(), expected);
+    }
+}
+
+#[test]
+fn liquidity_quotes_match_execution() {
+    let mut pool = Pool::with_liquidity(INITIAL_X, INITIAL_Y);
+    pool.process_ok(&pool.swap_ix(true, 7_777, 0, NOT_EXPIRED));
+
+    // 交换后储备不再是整数比例，存款向上取整
+    let ((reserve_x, reserve_y), _, _) = pool_state(&pool);
+    let deposit = quote_deposit(reserve_x, reserve_y, pool.lp_supply(), 12_345).unwrap();
+    pool.process_ok(&pool.deposit_ix(12_345, deposit.x, deposit.y, NOT_EXPIRED));
+    assert_eq!(pool.reserves(), (reserve_x + deposit.x, reserve_y + deposit.y));
+
+    // 部分取款向下取整
+    let ((reserve_x, reserve_y), _, _) = pool_state(&pool);
+    let withdraw = quote_withdraw(reserve_x, reserve_y, pool.lp_supply(), 54_321).unwrap();
+    pool.process_ok(&pool.withdraw_ix(54_321, withdraw.x, withdraw.y, NOT_EXPIRED));
+    assert_eq!(pool.reserves(), (reserve_x - withdraw.x, reserve_y - withdraw.y));
+
+    // 取出用户的全部 LP，锁定的 LP 对应的储备留在池子里
+    let ((reserve_x, reserve_y), _, _) = pool_state(&pool);
+    let user_lp = pool.token_amount(&pool.user_lp_ata);
+    let withdraw = quote_withdraw(reserve_x, reserve_y, pool.lp_supply(), user_lp).unwrap();
+    pool.process_ok(&pool.withdraw_ix(user_lp, withdraw.x, withdraw.y, NOT_EXPIRED));
+    assert_eq!(pool.reserves(), (reserve_x - withdraw.x, reserve_y - withdraw.y));
+}
+
+#[test]
+fn initial_deposit_quote_matches_execution() {
+    let mut pool = Pool::initialized();
+    let lp = quote_initial_deposit(123_457, 7_654_321).unwrap();
+
+    pool.process_ok(&pool.first_deposit_ix(lp, 123_457, 7_654_321, NOT_EXPIRED));
+    assert_eq!(pool.token_amount(&pool.user_lp_ata), lp);
+    assert_eq!(pool.lp_supply(), lp + MINIMUM_LIQUIDITY);
+}
@@ -0,0 +1,198 @@
+use proptest::prelude::*;
+use crate::curve::{self, Curve, CurveError, FEE_DENOMINATOR};
+use crate::stable::{self, MAX_AMP, MIN_AMP, MIN_RAMP_DURATION};
+
+// =============================================================================
+// 属性测试
+// =============================================================================
+// 储备限制在 2^62 以内，覆盖大额池子，同时避免极端失衡导致的溢出
+
+fn reserve() -> impl Strategy<Value = u64> {
+    1_000u64..=1 << 62
+}
+
+fn amp() -> impl Strategy<Value = u64> {
+    MIN_AMP..=10_000
+}
+
+fn fee() -> impl Strategy<Value = u16> {
+    0u16..FEE_DENOMINATOR
+}
+
+proptest! {
+    #[test]
+    fn swap_never_decreases_d(
+        amp in amp(),
+        reserve_in in reserve(),
+        reserve_out in reserve(),
+        amount_in in 1u64..=1 << 62,
+        fee in fee(),
+    ) {
+        let Ok(result) = stable::swap_exact_in(amp, reserve_in, reserve_out, amount_in, fee) else {
+            return Ok(());
+        };
+
+        let d_before = stable::compute_d(amp, reserve_in, reserve_out).unwrap();
+        let d_after = stable::compute_d(
+            amp,
+            reserve_in + result.amount_in,
+            reserve_out - result.amount_out,
+        ).unwrap();
+        prop_assert!(d_after >= d_before);
+    }
+
+    #[test]
+    fn swap_exact_out_never_decreases_d(
+        amp in amp(),
+        reserve_in in reserve(),
+        reserve_out in reserve(),
+        amount_out_seed in any::<u64>(),
+        fee in fee(),
+    ) {
+        let amount_out = amount_out_seed % reserve_out;
+        let Ok(result) = stable::swap_exact_out(amp, reserve_in, reserve_out, amount_out, fee) else {
+            return Ok(());
+        };
+
+        let d_before = stable::compute_d(amp, reserve_in, reserve_out).unwrap();
+        let d_after = stable::compute_d(
+            amp,
+            reserve_in + result.amount_in,
+            reserve_out - result.amount_out,
+        ).unwrap();
+        prop_assert!(d_after >= d_before);
+    }
+
+    #[test]
+    fn swap_round_trip_never_profits(
+        amp in amp(),
+        reserve_in in reserve(),
+        reserve_out in reserve(),
+        amount_in in 1u64..=1 << 62,
+        fee in fee(),
+    ) {
+        let Ok(first) = stable::swap_exact_in(amp, reserve_in, reserve_out, amount_in, fee) else {
+            return Ok(());
+        };
+        let Ok(second) = stable::swap_exact_in(
+            amp,
+            reserve_out - first.amount_out,
+            reserve_in + first.amount_in,
+            first.amount_out,
+            fee,
+        ) else {
+            return Ok(());
+        };
+
+        prop_assert!(second.amount_out <= amount_in);
+    }
+
+    // 平衡的池子里，StableSwap 的成交价格不差于恒定乘积
+    #[test]
+    fn balanced_pool_quotes_at_least_constant_product(
+        amp in amp(),
+        reserve in reserve(),
+        amount_in_seed in any::<u64>(),
+        fee in fee(),
+    ) {
+        let amount_in = amount_in_seed % reserve + 1;
+        let Ok(constant_product) = curve::swap_exact_in(reserve, reserve, amount_in, fee) else {
+            return Ok(());
+        };
+        let stable = stable::swap_exact_in(amp, reserve, reserve, amount_in, fee).unwrap();
+
+        // StableSwap 额外减 1 保护池子
+        prop_assert!(stable.amount_out + 1 >= constant_product.amount_out);
+    }
+
+    #[test]
+    fn current_amp_stays_between_endpoints(
+        amp_initial in MIN_AMP..=MAX_AMP,
+        amp_target in MIN_AMP..=MAX_AMP,
+        duration in 1i64..=1 << 40,
+        timestamp in any::<i64>(),
+    ) {
+        let amp = stable::current_amp(amp_initial, amp_target, 0, duration, timestamp);
+
+        prop_assert!(amp >= amp_initial.min(amp_target));
+        prop_assert!(amp <= amp_initial.max(amp_target));
+    }
+}
+
+// =============================================================================
+// 边界情况
+// =============================================================================
+
+#[test]
+fn balanced_pool_invariant_is_sum_of_reserves() {
+    assert_eq!(stable::compute_d(100, 1_000_000, 1_000_000), Ok(2_000_000));
+    assert_eq!(stable::compute_d(100, u64::MAX, u64::MAX), Ok(2 * u64::MAX as u128));
+}
+
+#[test]
+fn high_amp_trades_close_to_one_to_one() {
+    let result = stable::swap_exact_in(1_000, 1_000_000_000, 1_000_000_000, 1_000_000, 0).unwrap();
+
+    // 千分之一的交易量，滑点远小于恒定乘积的约 0.1%
+    assert!(result.amount_out > 999_990 && result.amount_out < 1_000_000);
+}
+
+#[test]
+fn rejects_invalid_amp_and_empty_pool() {
+    assert_eq!(stable::compute_d(0, 100, 100), Err(CurveError::InvalidAmp));
+    assert_eq!(stable::compute_d(MAX_AMP + 1, 100, 100), Err(CurveError::InvalidAmp));
+    assert_eq!(stable::swap_exact_in(100, 0, 100, 1, 30), Err(CurveError::ZeroLiquidity));
+    assert_eq!(stable::swap_exact_out(100, 100, 100, 100, 30), Err(CurveError::InsufficientLiquidity));
+}
+
+// 储备相差十几个数量级时，D 仍然收敛，并且与两侧储备对称
+#[test]
+fn compute_d_converges_for_extremely_imbalanced_pool() {
+    for (amp, x, y) in [
+        (2_865, 2_044_902_605_386_435_097, 1_099_666_837),
+        (851_736, 192_990_758_630_335_989, 907),
+        (98_897, 14_787_634_027_187_090_522, 12_200),
+    ] {
+        let d = stable::compute_d(amp, x, y).unwrap();
+        assert_eq!(stable::compute_d(amp, y, x), Ok(d));
+        assert!(d < x as u128 + y as u128);
+    }
+}
+
+#[test]
+fn curve_dispatches_to_selected_invariant() {
+    let constant_product = Curve::ConstantProduct.swap_exact_in(1_000_000, 1_000_000, 10_000, 30).unwrap();
+    let stable = Curve::StableSwap { amp: 100 }.swap_exact_in(1_000_000, 1_000_000, 10_000, 30).unwrap();
+
+    assert_eq!(constant_product, curve::swap_exact_in(1_000_000, 1_000_000, 10_000, 30).unwrap());
+    assert!(stable.amount_out > constant_product.amount_out);
+}
+
+#[test]
+fn current_amp_ramps_linearly() {
+    assert_eq!(stable::current_amp(100, 200, 1_000, 2_000, 500), 100);
+    assert_eq!(stable::current_amp(100, 200, 1_000, 2_000, 1_500), 150);
+    assert_eq!(stable::current_amp(100, 200, 1_000, 2_000, 3_000), 200);
+    assert_eq!(stable::current_amp(200, 100, 1_000, 2_000, 1_250), 175);
+}
+
+#[test]
+fn check_ramp_limits_target_duration_and_change() {
+    assert_eq!(stable::check_ramp(100, 1_000, 0, MIN_RAMP_DURATION), Ok(()));
+    assert_eq!(stable::check_ramp(100, 10, 0, MIN_RAMP_DURATION), Ok(()));
+    assert_eq!(stable::check_ramp(100, 1_001, 0, MIN_RAMP_DURATION), Err(CurveError::InvalidAmp));
+    assert_eq!(stable::check_ramp(100, 9, 0, MIN_RAMP_DURATION), Err(CurveError::InvalidAmp));
+    assert_eq!(stable::check_ramp(100, 200, 0, MIN_RAMP_DURATION - 1), Err(CurveError::InvalidAmp));
+    assert_eq!(stable::check_ramp(100, 0, 0, MIN_RAMP_DURATION), Err(CurveError::InvalidAmp));
+}
+
+#[test]
+fn spot_price_is_one_in_balanced_pool_and_favors_scarce_side() {
+    assert_eq!(stable::spot_price(100, 1_000_000, 1_000_000), Ok(1 << 64));
+
+    // 输入代币稀缺时，1 个输入可以换到多于 1 个输出
+    let scarce_in = stable::spot_price(100, 500_000, 1_500_000).unwrap();
+    let plentiful_in = stable::spot_price(100, 1_500_000, 500_000).unwrap();
+    assert!(scarce_in > 1 << 64);
+    assert!(plentiful_in < 1 << 64);
+}
@@ -0,0 +1,106 @@
+// =============================================================================
+// 账户解析
+// =============================================================================
+// 只解析当前版本的 Config，旧版本的账户需要先在链上执行 MigrateConfig
+// 注册表和交易对记录用于路由枚举某个交易对的全部池子
+// 事件从交易日志中解析，格式见 events 模块
+
+use core::fmt;
+
+use solana_address::Address;
+
+use crate::events::{self, AmmEvent};
+use crate::layout::{Config, PairRecord, PoolEntry, Registry, ID};
+use crate::pda::PoolKeys;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DecodeError {
+    // 数据长度与账户类型不符，或 Config 的版本字节与长度不一致
+    InvalidData,
+    // 旧版本的 Config，程序只接受当前版本
+    OutdatedVersion(u8),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidData => write!(f, "invalid account data"),
+            DecodeError::OutdatedVersion(version) => write!(
+                f,
+                "Config version {version} is outdated, expected {}; run MigrateConfig first",
+                Config::VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+// 把 Config 账户数据解释为 Config（零拷贝）
+pub fn decode_config(data: &[u8]) -> Result<&Config, DecodeError> {
+    match Config::version_of(data) {
+        Some(Config::VERSION) => Config::from_bytes(data).ok_or(DecodeError::InvalidData),
+        Some(version) => Err(DecodeError::OutdatedVersion(version)),
+        None => Err(DecodeError::InvalidData),
+    }
+}
+
+// =============================================================================
+// 注册表
+// =============================================================================
+
+pub fn decode_registry(data: &[u8]) -> Result<&Registry, DecodeError> {
+    Registry::from_bytes(data).ok_or(DecodeError::InvalidData)
+}
+
+// 交易对记录的头部和按注册顺序排列的池子
+pub fn decode_pair_record(data: &[u8]) -> Result<(&PairRecord, &[PoolEntry]), DecodeError> {
+    PairRecord::from_bytes(data).ok_or(DecodeError::InvalidData)
+}
+
+// 交易对中注册过的一个池子
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RegisteredPool {
+    pub keys: PoolKeys,
+    pub seed: u64,
+    pub fee_tier: u16,
+    pub curve_type: u8,
+}
+
+// 列出交易对记录中的全部池子，按手续费档位从低到高排列，同一档位内按注册顺序
+// 结果只由账户数据决定，不同的路由读取同一个账户得到相同的顺序
+// Token Program 没有记录在链上，需要由调用者根据两个 Mint 账户的 owner 提供
+pub fn registered_pools(
+    data: &[u8],
+    token_program_x: &Address,
+    token_program_y: &Address,
+) -> Result<Vec<RegisteredPool>, DecodeError> {
+    let (pair, entries) = decode_pair_record(data)?;
+
+    let mut pools: Vec<RegisteredPool> = entries
+        .iter()
+        .map(|entry| RegisteredPool {
+            keys: PoolKeys::new(entry.seed(), pair.mint_x(), pair.mint_y(), token_program_x, token_program_y),
+            seed: entry.seed(),
+            fee_tier: entry.fee_tier(),
+            curve_type: entry.curve_type(),
+        })
+        .collect();
+    // 稳定排序，保留同一档位内的注册顺序
+    pools.sort_by_key(|pool| pool.fee_tier);
+
+    Ok(pools)
+}
+
+// =============================================================================
+// 事件解析
+// =============================================================================
+
+// 按顺序解析一笔成功交易的日志中本程序输出的事件
+pub fn parse_events<I>(logs: I) -> Vec<AmmEvent>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    events::parse_logs(&ID.to_string(), logs).collect()
+}
@@ -0,0 +1,327 @@
+// =============================================================================
+// 指令构造
+// =============================================================================
+// 账户顺序和读写权限与程序中各指令的 XxxAccounts::try_from 一一对应，
+// 指令数据为 [判别器][layout 中的指令数据结构体]
+
+use solana_address::Address;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+use crate::layout::{
+    discriminator, CurveType, DepositInstructionData, InitializeInstructionData, InitializeRegistryInstructionData,
+    SwapExactOutInstructionData, SwapInstructionData, WithdrawInstructionData, ID,
+};
+use crate::metadata::METADATA_PROGRAM_ID;
+use crate::pda::{
+    locked_lp_owner_address, lp_metadata_address, metadata_address, pair_record_address, program_data_address, registry_address, PoolKeys, ASSOCIATED_TOKEN_PROGRAM_ID, SYSTEM_PROGRAM_ID, TOKEN_PROGRAM_ID,
+};
+
+// 程序使用的地址类型与 Instruction 使用的地址类型按字节转换
+#[inline(always)]
+fn key(address: &Address) -> Pubkey {
+    Pubkey::new_from_array(address.to_bytes())
+}
+
+#[inline(always)]
+fn writable(address: &Address) -> AccountMeta {
+    AccountMeta::new(key(address), false)
+}
+
+#[inline(always)]
+fn readonly(address: &Address) -> AccountMeta {
+    AccountMeta::new_readonly(key(address), false)
+}
+
+#[inline(always)]
+fn signer(address: &Address) -> AccountMeta {
+    AccountMeta::new(key(address), true)
+}
+
+fn instruction(discriminator: u8, data: &[u8], accounts: Vec<AccountMeta>) -> Instruction {
+    let mut bytes = Vec::with_capacity(1 + data.len());
+    bytes.push(discriminator);
+    bytes.extend_from_slice(data);
+
+    Instruction::new_with_bytes(key(&ID), &bytes, accounts)
+}
+
+// =============================================================================
+// Initialize
+// =============================================================================
+// authority 为 None 时创建不可变池子；恒定乘积池子的 amp 会被忽略
+// 池子的两个 mint 必须按规范顺序排列（见 canonical_mints），创建时同时注册到交易对记录
+// 同时为 LP Mint 创建 Metaplex Metadata，名称和符号由两个 Mint 的符号决定（见 LpTokenName）
+#[allow(clippy::too_many_arguments)]
+pub fn initialize(
+    initializer: &Address,
+    pool: &PoolKeys,
+    seed: u64,
+    fee: u16,
+    lp_decimals: u8,
+    authority: Option<&Address>,
+    curve_type: CurveType,
+    amp: u64,
+) -> Instruction {
+    let data = InitializeInstructionData {
+        seed,
+        fee,
+        lp_decimals,
+        authority: authority.map(Address::to_bytes).unwrap_or_default(),
+        curve_type: curve_type as u8,
+        amp,
+    };
+
+    instruction(
+        discriminator::INITIALIZE,
+        data.as_bytes(),
+        vec![
+            signer(initializer),
+            writable(&pool.mint_lp),
+            readonly(&pool.mint_x),
+            readonly(&pool.mint_y),
+            writable(&pool.vault_x),
+            writable(&pool.vault_y),
+            writable(&pool.config),
+            readonly(&TOKEN_PROGRAM_ID),
+            readonly(&pool.token_program_x),
+            readonly(&pool.token_program_y),
+            readonly(&SYSTEM_PROGRAM_ID),
+            readonly(&ASSOCIATED_TOKEN_PROGRAM_ID),
+            writable(&registry_address().0),
+            writable(&pair_record_address(&pool.mint_x, &pool.mint_y).0),
+            writable(&lp_metadata_address(&pool.mint_lp)),
+            readonly(&METADATA_PROGRAM_ID),
+            readonly(&metadata_address(&pool.mint_x)),
+            readonly(&metadata_address(&pool.mint_y)),
+        ],
+    )
+}
+
+// =============================================================================
+// CreateLpMetadata
+// =============================================================================
+// 由池子的 authority 为创建时没有元数据的池子补充创建，authority 同时支付租金
+pub fn create_lp_metadata(authority: &Address, pool: &PoolKeys) -> Instruction {
+    instruction(
+        discriminator::CREATE_LP_METADATA,
+        &[],
+        vec![
+            signer(authority),
+            readonly(&pool.config),
+            readonly(&pool.mint_lp),
+            writable(&lp_metadata_address(&pool.mint_lp)),
+            readonly(&METADATA_PROGRAM_ID),
+            readonly(&SYSTEM_PROGRAM_ID),
+            readonly(&metadata_address(&pool.mint_x)),
+            readonly(&metadata_address(&pool.mint_y)),
+        ],
+    )
+}
+
+// =============================================================================
+// UpdateLpMetadata
+// =============================================================================
+// 由池子的 authority 修改 LP 代币元数据的 URI，指令数据就是 URI 本身，名称和符号保持不变
+pub fn update_lp_metadata(authority: &Address, pool: &PoolKeys, uri: &str) -> Instruction {
+    instruction(
+        discriminator::UPDATE_LP_METADATA,
+        uri.as_bytes(),
+        vec![
+            AccountMeta::new_readonly(key(authority), true),
+            writable(&pool.config),
+            writable(&lp_metadata_address(&pool.mint_lp)),
+            readonly(&METADATA_PROGRAM_ID),
+        ],
+    )
+}
+
+// =============================================================================
+// Deposit / Withdraw
+// =============================================================================
+// 两条指令使用相同的账户，用户的 ATA 由 user 派生
+fn liquidity_accounts(user: &Address, pool: &PoolKeys) -> Vec<AccountMeta> {
+    vec![
+        signer(user),
+        writable(&pool.mint_lp),
+        readonly(&pool.mint_x),
+        readonly(&pool.mint_y),
+        writable(&pool.vault_x),
+        writable(&pool.vault_y),
+        writable(&pool.user_x_ata(user)),
+        writable(&pool.user_y_ata(user)),
+        writable(&pool.user_lp_ata(user)),
+        writable(&pool.config),
+        readonly(&TOKEN_PROGRAM_ID),
+        readonly(&pool.token_program_x),
+        readonly(&pool.token_program_y),
+    ]
+}
+
+// 铸造 amount 个 LP，最多转入 max_x / max_y
+pub fn deposit(user: &Address, pool: &PoolKeys, amount: u64, max_x: u64, max_y: u64, expiration: i64) -> Instruction {
+    let data = DepositInstructionData {
+        amount,
+        max_x,
+        max_y,
+        expirations: expiration,
+    };
+
+    instruction(discriminator::DEPOSIT, data.as_bytes(), liquidity_accounts(user, pool))
+}
+
+// 池子的首次存款：存入 x / y，铸造 sqrt(x * y) - MINIMUM_LIQUIDITY 个 LP，至少 min_lp 个
+// 额外传入锁定 MINIMUM_LIQUIDITY 的账户，锁定的 ATA 不存在时由 user 付费创建
+pub fn first_deposit(user: &Address, pool: &PoolKeys, min_lp: u64, x: u64, y: u64, expiration: i64) -> Instruction {
+    let data = DepositInstructionData {
+        amount: min_lp,
+        max_x: x,
+        max_y: y,
+        expirations: expiration,
+    };
+
+    let mut accounts = liquidity_accounts(user, pool);
+    accounts.extend([
+        readonly(&locked_lp_owner_address(&pool.config)),
+        writable(&pool.locked_lp_ata()),
+        readonly(&SYSTEM_PROGRAM_ID),
+        readonly(&ASSOCIATED_TOKEN_PROGRAM_ID),
+    ]);
+
+    instruction(discriminator::DEPOSIT, data.as_bytes(), accounts)
+}
+
+// 销毁 amount 个 LP，至少取回 min_x / min_y
+pub fn withdraw(user: &Address, pool: &PoolKeys, amount: u64, min_x: u64, min_y: u64, expiration: i64) -> Instruction {
+    let data = WithdrawInstructionData {
+        amount,
+        mint_x: min_x,
+        mint_y: min_y,
+        expiration,
+    };
+
+    instruction(discriminator::WITHDRAW, data.as_bytes(), liquidity_accounts(user, pool))
+}
+
+// =============================================================================
+// Swap / SwapExactOut
+// =============================================================================
+// is_x 为 true 时用 X 换 Y
+fn swap_accounts(user: &Address, pool: &PoolKeys) -> Vec<AccountMeta> {
+    vec![
+        signer(user),
+        readonly(&pool.mint_x),
+        readonly(&pool.mint_y),
+        writable(&pool.user_x_ata(user)),
+        writable(&pool.user_y_ata(user)),
+        writable(&pool.vault_x),
+        writable(&pool.vault_y),
+        writable(&pool.config),
+        readonly(&pool.token_program_x),
+        readonly(&pool.token_program_y),
+    ]
+}
+
+// 转入 amount 个，至少收到 min 个
+pub fn swap(user: &Address, pool: &PoolKeys, is_x: bool, amount: u64, min: u64, expiration: i64) -> Instruction {
+    let data = SwapInstructionData {
+        is_x,
+        amount,
+        min,
+        expirations: expiration,
+    };
+
+    instruction(discriminator::SWAP, data.as_bytes(), swap_accounts(user, pool))
+}
+
+// 正好收到 amount_out 个，最多转入 max_in 个
+pub fn swap_exact_out(
+    user: &Address,
+    pool: &PoolKeys,
+    is_x: bool,
+    amount_out: u64,
+    max_in: u64,
+    expiration: i64,
+) -> Instruction {
+    let data = SwapExactOutInstructionData {
+        is_x,
+        amount_out,
+        max_in,
+        expirations: expiration,
+    };
+
+    instruction(discriminator::SWAP_EXACT_OUT, data.as_bytes(), swap_accounts(user, pool))
+}
+
+// =============================================================================
+// FlashLoan
+// =============================================================================
+// 借出 amount_x / amount_y 到 borrower 的 ATA，然后以 receiver_accounts 和 receiver_data
+// CPI 到 receiver_program，指令结束前金库必须收回本金和手续费（见 quote_flash_loan_fee）
+// receiver_accounts 的签名和可写权限原样传给 receiver
+pub fn flash_loan(
+    borrower: &Address,
+    pool: &PoolKeys,
+    amount_x: u64,
+    amount_y: u64,
+    receiver_program: &Address,
+    receiver_accounts: Vec<AccountMeta>,
+    receiver_data: &[u8],
+) -> Instruction {
+    let mut data = Vec::with_capacity(16 + receiver_data.len());
+    data.extend_from_slice(&amount_x.to_le_bytes());
+    data.extend_from_slice(&amount_y.to_le_bytes());
+    data.extend_from_slice(receiver_data);
+
+    let mut accounts = swap_accounts(borrower, pool);
+    accounts.push(readonly(receiver_program));
+    accounts.extend(receiver_accounts);
+
+    instruction(discriminator::FLASH_LOAN, &data, accounts)
+}
+
+// =============================================================================
+// 注册表
+// =============================================================================
+// 注册表全局只有一个，payer 必须是程序的升级权限，同时支付租金
+pub fn initialize_registry(payer: &Address, authority: &Address, one_pool_per_fee_tier: bool) -> Instruction {
+    let data = InitializeRegistryInstructionData {
+        authority: authority.to_bytes(),
+        one_pool_per_fee_tier,
+    };
+
+    instruction(
+        discriminator::INITIALIZE_REGISTRY,
+        data.as_bytes(),
+        vec![
+            signer(payer),
+            writable(&registry_address().0),
+            readonly(&program_data_address()),
+            readonly(&SYSTEM_PROGRAM_ID),
+        ],
+    )
+}
+
+// 由注册表的 authority 修改是否限制每个手续费档位只有一个池子
+pub fn update_registry(authority: &Address, one_pool_per_fee_tier: bool) -> Instruction {
+    instruction(
+        discriminator::UPDATE_REGISTRY,
+        &[one_pool_per_fee_tier as u8],
+        vec![AccountMeta::new_readonly(key(authority), true), writable(&registry_address().0)],
+    )
+}
+
+// 把注册表创建之前的池子加入交易对记录，payer 支付交易对记录扩容的租金
+pub fn register_pool(payer: &Address, pool: &PoolKeys) -> Instruction {
+    instruction(
+        discriminator::REGISTER_POOL,
+        &[],
+        vec![
+            signer(payer),
+            writable(&registry_address().0),
+            readonly(&pool.config),
+            writable(&pair_record_address(&pool.mint_x, &pool.mint_y).0),
+            readonly(&SYSTEM_PROGRAM_ID),
+        ],
+    )
+}
@@ -0,0 +1,55 @@
+// =============================================================================
+// blueshift_native_amm 链下客户端
+// =============================================================================
+// - PDA 派生：Config、LP Mint、金库和用户的 ATA
+// - 指令构造：按程序要求的顺序和读写权限生成 AccountMeta
+// - 账户解析：把 Config 账户数据解释为 Config，从注册表中列出交易对的全部池子
+// - 报价：与链上完全相同的交换 / 存款 / 取款计算
+// - LP 元数据：Metaplex Metadata 的地址和 LP 代币的名称
+// - 事件：从交易日志中解析程序输出的事件
+//
+// Config 布局、PDA 种子、判别器和指令数据直接使用程序中的 layout 模块，
+// 程序修改布局后客户端会随之改变，不会出现两份定义不一致
+// 报价同样直接使用程序中只依赖 core 的曲线模块
+
+// layout 中的 unsafe fn 使用普通注释说明前提条件，与程序中的写法一致
+#[allow(clippy::missing_safety_doc)]
+#[path = "../../blueshift_native_amm/src/layout.rs"]
+pub mod layout;
+
+#[path = "../../blueshift_native_amm/src/curve.rs"]
+pub mod curve;
+// 事件使用与 layout 相同的 unsafe 字节转换
+#[allow(clippy::missing_safety_doc)]
+#[path = "../../blueshift_native_amm/src/events.rs"]
+pub mod events;
+#[path = "../../blueshift_native_amm/src/metadata.rs"]
+pub mod metadata;
+#[path = "../../blueshift_native_amm/src/oracle.rs"]
+pub mod oracle;
+#[path = "../../blueshift_native_amm/src/quote.rs"]
+pub mod quote;
+#[path = "../../blueshift_native_amm/src/stable.rs"]
+pub mod stable;
+
+pub mod accounts;
+pub mod instructions;
+pub mod pda;
+
+#[cfg(test)]
+pub mod tests;
+
+pub use accounts::*;
+pub use instructions::*;
+pub use layout::{
+    discriminator, is_canonical, AmmState, Config, CurveType, DepositInstructionData, InitializeInstructionData,
+    InitializeRegistryInstructionData, PairRecord, PoolEntry, Registry, SwapExactOutInstructionData,
+    SwapInstructionData, WithdrawInstructionData, ID,
+};
+pub use events::{AmmEvent, Event, EVENT_VERSION};
+pub use metadata::{read_name_and_symbol, LpTokenName, METADATA_PROGRAM_ID};
+pub use pda::*;
+pub use curve::MINIMUM_LIQUIDITY;
+pub use quote::{
+    quote_deposit, quote_flash_loan_fee, quote_initial_deposit, quote_swap_exact_in, quote_withdraw, SwapQuote,
+};
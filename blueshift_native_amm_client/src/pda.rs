@@ -0,0 +1,161 @@
+// =============================================================================
+// PDA 派生
+// =============================================================================
+// 种子与程序中的 Initialize 和各账户检查相同，定义在共用的 layout 模块中
+
+use solana_address::Address;
+
+use crate::layout::{is_canonical, Config, BPF_LOADER_UPGRADEABLE_ID, CONFIG_SEED, ID, LOCKED_LP_SEED, MINT_LP_SEED, PAIR_SEED, REGISTRY_SEED};
+use crate::metadata::{METADATA_PROGRAM_ID, METADATA_SEED};
+
+// TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA
+pub const TOKEN_PROGRAM_ID: Address = Address::new_from_array([
+    0x06, 0xdd, 0xf6, 0xe1, 0xd7, 0x65, 0xa1, 0x93, 0xd9, 0xcb, 0xe1, 0x46, 0xce, 0xeb, 0x79, 0xac,
+    0x1c, 0xb4, 0x85, 0xed, 0x5f, 0x5b, 0x37, 0x91, 0x3a, 0x8c, 0xf5, 0x85, 0x7e, 0xff, 0x00, 0xa9,
+]);
+
+// TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb
+pub const TOKEN_2022_PROGRAM_ID: Address = Address::new_from_array([
+    0x06, 0xdd, 0xf6, 0xe1, 0xee, 0x75, 0x8f, 0xde, 0x18, 0x42, 0x5d, 0xbc, 0xe4, 0x6c, 0xcd, 0xda,
+    0xb6, 0x1a, 0xfc, 0x4d, 0x83, 0xb9, 0x0d, 0x27, 0xfe, 0xbd, 0xf9, 0x28, 0xd8, 0xa1, 0x8b, 0xfc,
+]);
+
+// ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Address = Address::new_from_array([
+    0x8c, 0x97, 0x25, 0x8f, 0x4e, 0x24, 0x89, 0xf1, 0xbb, 0x3d, 0x10, 0x29, 0x14, 0x8e, 0x0d, 0x83,
+    0x0b, 0x5a, 0x13, 0x99, 0xda, 0xff, 0x10, 0x84, 0x04, 0x8e, 0x7b, 0xd8, 0xdb, 0xe9, 0xf8, 0x59,
+]);
+
+// 11111111111111111111111111111111
+pub const SYSTEM_PROGRAM_ID: Address = Address::new_from_array([0u8; 32]);
+
+// Config：[b"config", seed, mint_x, mint_y]
+pub fn config_address(seed: u64, mint_x: &Address, mint_y: &Address) -> (Address, u8) {
+    Address::find_program_address(
+        &[CONFIG_SEED, &seed.to_le_bytes(), mint_x.as_ref(), mint_y.as_ref()],
+        &ID,
+    )
+}
+
+// LP Mint：[b"mint_lp", config]
+pub fn mint_lp_address(config: &Address) -> (Address, u8) {
+    Address::find_program_address(&[MINT_LP_SEED, config.as_ref()], &ID)
+}
+
+// 永久锁定 MINIMUM_LIQUIDITY 的 ATA 的 owner：[b"locked_lp", config]
+// 程序从不以它签名，其中的 LP 无法取出
+pub fn locked_lp_owner_address(config: &Address) -> Address {
+    Address::find_program_address(&[LOCKED_LP_SEED, config.as_ref()], &ID).0
+}
+
+// Mint 的 Metaplex Metadata：[b"metadata", METADATA_PROGRAM_ID, mint]
+// LP 代币的名称和符号取自交易对两个 Mint 的 Metadata
+pub fn metadata_address(mint: &Address) -> Address {
+    Address::find_program_address(
+        &[METADATA_SEED, METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &METADATA_PROGRAM_ID,
+    )
+    .0
+}
+
+// LP 代币的 Metaplex Metadata
+pub fn lp_metadata_address(mint_lp: &Address) -> Address {
+    metadata_address(mint_lp)
+}
+
+// 注册表：[b"registry"]，全局只有一个
+pub fn registry_address() -> (Address, u8) {
+    Address::find_program_address(&[REGISTRY_SEED], &ID)
+}
+
+// 本程序的 ProgramData：[ID] 在 BPF Upgradeable Loader 下的 PDA，创建注册表时验证升级权限
+pub fn program_data_address() -> Address {
+    Address::find_program_address(&[ID.as_ref()], &BPF_LOADER_UPGRADEABLE_ID).0
+}
+
+// 把两个 mint 按注册表要求的规范顺序排列为 (mint_x, mint_y)
+pub fn canonical_mints<'a>(mint_a: &'a Address, mint_b: &'a Address) -> (&'a Address, &'a Address) {
+    if is_canonical(mint_a, mint_b) {
+        (mint_a, mint_b)
+    } else {
+        (mint_b, mint_a)
+    }
+}
+
+// 交易对记录：[b"pair", mint_x, mint_y]，两个 mint 可以按任意顺序传入
+pub fn pair_record_address(mint_a: &Address, mint_b: &Address) -> (Address, u8) {
+    let (mint_x, mint_y) = canonical_mints(mint_a, mint_b);
+    Address::find_program_address(&[PAIR_SEED, mint_x.as_ref(), mint_y.as_ref()], &ID)
+}
+
+// ATA：[owner, token_program, mint]，金库是 config 的 ATA
+pub fn associated_token_address(owner: &Address, mint: &Address, token_program: &Address) -> Address {
+    Address::find_program_address(
+        &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+// =============================================================================
+// 一个池子的全部地址
+// =============================================================================
+// 每一侧可以分别使用旧版 Token Program 或 Token-2022，Config 中没有记录，
+// 需要由调用者根据 Mint 账户的 owner 提供；LP Mint 始终由旧版 Token Program 创建
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PoolKeys {
+    pub config: Address,
+    pub mint_lp: Address,
+    pub mint_x: Address,
+    pub mint_y: Address,
+    pub vault_x: Address,
+    pub vault_y: Address,
+    pub token_program_x: Address,
+    pub token_program_y: Address,
+}
+
+impl PoolKeys {
+    pub fn new(
+        seed: u64,
+        mint_x: &Address,
+        mint_y: &Address,
+        token_program_x: &Address,
+        token_program_y: &Address,
+    ) -> Self {
+        let (config, _) = config_address(seed, mint_x, mint_y);
+        let (mint_lp, _) = mint_lp_address(&config);
+
+        Self {
+            config,
+            mint_lp,
+            mint_x: *mint_x,
+            mint_y: *mint_y,
+            vault_x: associated_token_address(&config, mint_x, token_program_x),
+            vault_y: associated_token_address(&config, mint_y, token_program_y),
+            token_program_x: *token_program_x,
+            token_program_y: *token_program_y,
+        }
+    }
+
+    // 由已解析的 Config 账户得到池子的地址
+    pub fn from_config(config: &Config, token_program_x: &Address, token_program_y: &Address) -> Self {
+        Self::new(config.seed(), config.mint_x(), config.mint_y(), token_program_x, token_program_y)
+    }
+
+    pub fn user_x_ata(&self, user: &Address) -> Address {
+        associated_token_address(user, &self.mint_x, &self.token_program_x)
+    }
+
+    pub fn user_y_ata(&self, user: &Address) -> Address {
+        associated_token_address(user, &self.mint_y, &self.token_program_y)
+    }
+
+    pub fn user_lp_ata(&self, user: &Address) -> Address {
+        associated_token_address(user, &self.mint_lp, &TOKEN_PROGRAM_ID)
+    }
+
+    // 首次存款时锁定的 MINIMUM_LIQUIDITY 所在的 ATA
+    pub fn locked_lp_ata(&self) -> Address {
+        associated_token_address(&locked_lp_owner_address(&self.config), &self.mint_lp, &TOKEN_PROGRAM_ID)
+    }
+}
@@ -0,0 +1,159 @@
+// =============================================================================
+// Config 解析测试
+// =============================================================================
+
+use core::mem::offset_of;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use solana_address::Address;
+
+use crate::curve::Curve;
+use crate::events::FeeUpdatedEvent;
+use crate::{
+    decode_config, decode_pair_record, parse_events, registered_pools, quote, quote_swap_exact_in, AmmEvent, AmmState, Config, CurveType,
+    DecodeError, Event, PairRecord, PoolEntry, PoolKeys, EVENT_VERSION, ID, TOKEN_2022_PROGRAM_ID,
+    TOKEN_PROGRAM_ID,
+};
+
+fn config_bytes() -> Vec<u8> {
+    let mut data = vec![0u8; Config::LEN];
+    data[offset_of!(Config, state)] = AmmState::Initialized as u8;
+    data[offset_of!(Config, seed)..][..8].copy_from_slice(&42u64.to_le_bytes());
+    data[offset_of!(Config, mint_x)..][..32].copy_from_slice(&[1u8; 32]);
+    data[offset_of!(Config, mint_y)..][..32].copy_from_slice(&[2u8; 32]);
+    data[offset_of!(Config, fee)..][..2].copy_from_slice(&30u16.to_le_bytes());
+    data[offset_of!(Config, version)] = Config::VERSION;
+    data[offset_of!(Config, protocol_fees_x)..][..8].copy_from_slice(&7u64.to_le_bytes());
+    data[offset_of!(Config, curve_type)] = CurveType::StableSwap as u8;
+    data[offset_of!(Config, amp_target)..][..8].copy_from_slice(&100u64.to_le_bytes());
+    data
+}
+
+#[test]
+fn test_decode_current_config() {
+    let data = config_bytes();
+    let config = decode_config(&data).unwrap();
+
+    assert_eq!(config.state(), AmmState::Initialized as u8);
+    assert_eq!(config.seed(), 42);
+    assert_eq!(config.mint_x(), &Address::new_from_array([1u8; 32]));
+    assert_eq!(config.mint_y(), &Address::new_from_array([2u8; 32]));
+    assert_eq!(u16::from_le_bytes(*config.fee()), 30);
+    assert_eq!(config.protocol_fees_x(), 7);
+    assert_eq!(config.curve_type(), CurveType::StableSwap as u8);
+    assert_eq!(config.amp_target(), 100);
+    assert_eq!(config.has_authority(), None);
+
+    // 由 Config 派生的池子地址与由参数派生的一致
+    let pool = PoolKeys::from_config(config, &TOKEN_PROGRAM_ID, &TOKEN_PROGRAM_ID);
+    assert_eq!(pool, PoolKeys::new(42, config.mint_x(), config.mint_y(), &TOKEN_PROGRAM_ID, &TOKEN_PROGRAM_ID));
+}
+
+#[test]
+fn test_decode_outdated_config() {
+    let data = config_bytes();
+
+    // 版本 0 没有版本字节，只能通过长度识别，最初发布的程序创建的池子为 108 字节
+    assert_eq!(Config::LEN_V0, 108);
+    assert_eq!(decode_config(&data[..Config::LEN_V0]).err(), Some(DecodeError::OutdatedVersion(0)));
+
+    let mut v2 = data[..Config::LEN_V2].to_vec();
+    v2[Config::VERSION_OFFSET] = 2;
+    assert_eq!(decode_config(&v2).err(), Some(DecodeError::OutdatedVersion(2)));
+}
+
+#[test]
+fn test_decode_invalid_config() {
+    let mut data = config_bytes();
+
+    assert_eq!(decode_config(&data[..Config::LEN - 1]).err(), Some(DecodeError::InvalidData));
+
+    // 长度与版本字节不一致
+    data[Config::VERSION_OFFSET] = 1;
+    assert_eq!(decode_config(&data).err(), Some(DecodeError::InvalidData));
+}
+
+#[test]
+fn test_quote_from_decoded_config() {
+    let data = config_bytes();
+    let config = decode_config(&data).unwrap();
+
+    // 金库余额中未领取的协议费不计入储备
+    let (reserve_x, reserve_y) = quote::pool_reserves(config, 1_000_007, 1_000_000).unwrap();
+    assert_eq!((reserve_x, reserve_y), (1_000_000, 1_000_000));
+    assert_eq!(quote::pool_reserves(config, 6, 1_000_000), None);
+
+    // 没有调整中的放大系数时使用 amp_target
+    let curve = quote::pool_curve(config, 0).unwrap();
+    assert_eq!(curve, Curve::StableSwap { amp: 100 });
+
+    let fee = u16::from_le_bytes(*config.fee());
+    let quote = quote_swap_exact_in(curve, reserve_x, reserve_y, 10_000, fee, config.protocol_fee()).unwrap();
+    assert_eq!(quote.amount_out, curve.swap_exact_in(reserve_x, reserve_y, 10_000, fee).unwrap().amount_out);
+}
+
+#[test]
+fn test_parse_events_from_transaction_logs() {
+    let event = FeeUpdatedEvent {
+        pool: [1u8; 32],
+        authority: [2u8; 32],
+        fee: 25,
+        protocol_fee: 2_000,
+        fee_recipient: [3u8; 32],
+    };
+    let data = format!(
+        "Program data: {} {}",
+        STANDARD.encode([FeeUpdatedEvent::KIND, EVENT_VERSION]),
+        STANDARD.encode(event.as_bytes()),
+    );
+
+    let logs = [
+        format!("Program {ID} invoke [1]"),
+        "Program log: Instruction: UpdateFee".to_string(),
+        data.clone(),
+        format!("Program {ID} success"),
+        // 不在本程序中输出的数据被忽略
+        data,
+    ];
+
+    assert_eq!(parse_events(&logs), vec![AmmEvent::FeeUpdated(event)]);
+}
+
+// 交易对记录：头部之后按注册顺序排列 (seed, fee_tier)
+fn pair_record_bytes(pools: &[(u64, u16)]) -> Vec<u8> {
+    let mut data = vec![0u8; PairRecord::len_with(pools.len())];
+    data[offset_of!(PairRecord, mint_x)..][..32].copy_from_slice(&[1u8; 32]);
+    data[offset_of!(PairRecord, mint_y)..][..32].copy_from_slice(&[2u8; 32]);
+    for (i, (seed, fee_tier)) in pools.iter().enumerate() {
+        let entry = &mut data[PairRecord::LEN + i * PoolEntry::LEN..][..PoolEntry::LEN];
+        entry[offset_of!(PoolEntry, seed)..][..8].copy_from_slice(&seed.to_le_bytes());
+        entry[offset_of!(PoolEntry, fee_tier)..][..2].copy_from_slice(&fee_tier.to_le_bytes());
+    }
+    data
+}
+
+#[test]
+fn test_registered_pools_are_sorted_by_fee_tier() {
+    let data = pair_record_bytes(&[(1, 30), (2, 5), (3, 30), (4, 100)]);
+    let mint_x = Address::new_from_array([1u8; 32]);
+    let mint_y = Address::new_from_array([2u8; 32]);
+
+    let pools = registered_pools(&data, &TOKEN_PROGRAM_ID, &TOKEN_2022_PROGRAM_ID).unwrap();
+    let order: Vec<(u64, u16)> = pools.iter().map(|pool| (pool.seed, pool.fee_tier)).collect();
+    // 同一档位保留注册顺序
+    assert_eq!(order, vec![(2, 5), (1, 30), (3, 30), (4, 100)]);
+    assert_eq!(
+        pools[0].keys,
+        PoolKeys::new(2, &mint_x, &mint_y, &TOKEN_PROGRAM_ID, &TOKEN_2022_PROGRAM_ID)
+    );
+}
+
+#[test]
+fn test_decode_invalid_pair_record() {
+    let data = pair_record_bytes(&[(1, 30)]);
+
+    assert_eq!(decode_pair_record(&data).unwrap().1.len(), 1);
+    assert_eq!(decode_pair_record(&data[..data.len() - 1]).err(), Some(DecodeError::InvalidData));
+    assert_eq!(decode_pair_record(&data[..PairRecord::LEN - 1]).err(), Some(DecodeError::InvalidData));
+}
@@ -0,0 +1,298 @@
+// =============================================================================
+// 指令构造测试
+// =============================================================================
+// 指令数据按程序的字节格式手工拼出来对比，账户顺序与程序的 try_from 对比
+
+use solana_address::Address;
+use solana_instruction::AccountMeta;
+use solana_pubkey::Pubkey;
+
+use crate::{
+    create_lp_metadata, deposit, discriminator, first_deposit, flash_loan, initialize, initialize_registry, locked_lp_owner_address, lp_metadata_address, metadata_address,
+    pair_record_address, program_data_address, register_pool, registry_address, swap, swap_exact_out, update_lp_metadata, update_registry, withdraw, CurveType, InitializeInstructionData, PoolKeys, ASSOCIATED_TOKEN_PROGRAM_ID, ID,
+    METADATA_PROGRAM_ID, SYSTEM_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID,
+};
+
+const SEED: u64 = 42;
+const EXPIRATION: i64 = 1_700_000_060;
+
+fn address(byte: u8) -> Address {
+    Address::new_from_array([byte; 32])
+}
+
+fn key(address: &Address) -> Pubkey {
+    Pubkey::new_from_array(address.to_bytes())
+}
+
+fn pool() -> PoolKeys {
+    PoolKeys::new(SEED, &address(1), &address(2), &TOKEN_PROGRAM_ID, &TOKEN_2022_PROGRAM_ID)
+}
+
+#[test]
+fn test_initialize_data_matches_program_layout() {
+    let authority = address(9);
+    let ix = initialize(&address(7), &pool(), SEED, 30, 6, Some(&authority), CurveType::StableSwap, 100);
+
+    let mut expected = vec![discriminator::INITIALIZE];
+    expected.extend_from_slice(&SEED.to_le_bytes());
+    expected.extend_from_slice(&30u16.to_le_bytes());
+    expected.push(6);
+    expected.extend_from_slice(authority.as_ref());
+    expected.push(CurveType::StableSwap as u8);
+    expected.extend_from_slice(&100u64.to_le_bytes());
+
+    assert_eq!(ix.program_id, key(&ID));
+    assert_eq!(ix.data, expected);
+    assert_eq!(ix.data.len(), 1 + InitializeInstructionData::LEN);
+}
+
+#[test]
+fn test_initialize_without_authority_is_immutable() {
+    let ix = initialize(&address(7), &pool(), SEED, 30, 6, None, CurveType::ConstantProduct, 0);
+
+    let authority_start = 1 + InitializeInstructionData::LEN_WITHOUT_AUTHORITY;
+    assert_eq!(ix.data[authority_start..authority_start + 32], [0u8; 32]);
+}
+
+#[test]
+fn test_initialize_accounts() {
+    let pool = pool();
+    let initializer = address(7);
+    let ix = initialize(&initializer, &pool, SEED, 30, 6, None, CurveType::ConstantProduct, 0);
+
+    let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(keys.len(), 18);
+    assert_eq!(keys[0], key(&initializer));
+    assert_eq!(keys[1], key(&pool.mint_lp));
+    assert_eq!(keys[6], key(&pool.config));
+    assert_eq!(keys[7], key(&TOKEN_PROGRAM_ID));
+    assert_eq!(keys[9], key(&TOKEN_2022_PROGRAM_ID));
+    assert_eq!(keys[12], key(&registry_address().0));
+    assert_eq!(keys[13], key(&pair_record_address(&pool.mint_x, &pool.mint_y).0));
+    assert_eq!(keys[14], key(&lp_metadata_address(&pool.mint_lp)));
+    assert_eq!(keys[15], key(&METADATA_PROGRAM_ID));
+    assert_eq!(keys[16], key(&metadata_address(&pool.mint_x)));
+    assert_eq!(keys[17], key(&metadata_address(&pool.mint_y)));
+
+    assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+    assert!(ix.accounts.iter().skip(1).all(|meta| !meta.is_signer));
+    // mint_lp、两个金库、config 和 LP 元数据由 Initialize 创建，注册表和交易对记录同时更新
+    let writable: Vec<usize> = (0..18).filter(|i| ix.accounts[*i].is_writable).collect();
+    assert_eq!(writable, vec![0, 1, 4, 5, 6, 12, 13, 14]);
+}
+
+#[test]
+fn test_create_lp_metadata() {
+    let pool = pool();
+    let authority = address(9);
+    let ix = create_lp_metadata(&authority, &pool);
+
+    assert_eq!(ix.data, vec![discriminator::CREATE_LP_METADATA]);
+    assert_eq!(
+        ix.accounts,
+        vec![
+            AccountMeta::new(key(&authority), true),
+            AccountMeta::new_readonly(key(&pool.config), false),
+            AccountMeta::new_readonly(key(&pool.mint_lp), false),
+            AccountMeta::new(key(&lp_metadata_address(&pool.mint_lp)), false),
+            AccountMeta::new_readonly(key(&METADATA_PROGRAM_ID), false),
+            AccountMeta::new_readonly(key(&SYSTEM_PROGRAM_ID), false),
+            AccountMeta::new_readonly(key(&metadata_address(&pool.mint_x)), false),
+            AccountMeta::new_readonly(key(&metadata_address(&pool.mint_y)), false),
+        ]
+    );
+}
+
+#[test]
+fn test_update_lp_metadata() {
+    let pool = pool();
+    let authority = address(9);
+    let ix = update_lp_metadata(&authority, &pool, "https://example.com/lp.json");
+
+    let mut expected = vec![discriminator::UPDATE_LP_METADATA];
+    expected.extend_from_slice(b"https://example.com/lp.json");
+    assert_eq!(ix.data, expected);
+
+    let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(
+        keys,
+        vec![
+            key(&authority),
+            key(&pool.config),
+            key(&lp_metadata_address(&pool.mint_lp)),
+            key(&METADATA_PROGRAM_ID),
+        ]
+    );
+    assert!(ix.accounts[0].is_signer && !ix.accounts[0].is_writable);
+}
+
+#[test]
+fn test_deposit_and_withdraw_data() {
+    let pool = pool();
+    let user = address(7);
+
+    let ix = deposit(&user, &pool, 1_000, 2_000, 3_000, EXPIRATION);
+    let mut expected = vec![discriminator::DEPOSIT];
+    for value in [1_000u64, 2_000, 3_000] {
+        expected.extend_from_slice(&value.to_le_bytes());
+    }
+    expected.extend_from_slice(&EXPIRATION.to_le_bytes());
+    assert_eq!(ix.data, expected);
+
+    let ix = withdraw(&user, &pool, 1_000, 2_000, 3_000, EXPIRATION);
+    expected[0] = discriminator::WITHDRAW;
+    assert_eq!(ix.data, expected);
+}
+
+#[test]
+fn test_liquidity_accounts_use_user_atas() {
+    let pool = pool();
+    let user = address(7);
+    let ix = deposit(&user, &pool, 1_000, 2_000, 3_000, EXPIRATION);
+
+    let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(
+        keys,
+        vec![
+            key(&user),
+            key(&pool.mint_lp),
+            key(&pool.mint_x),
+            key(&pool.mint_y),
+            key(&pool.vault_x),
+            key(&pool.vault_y),
+            key(&pool.user_x_ata(&user)),
+            key(&pool.user_y_ata(&user)),
+            key(&pool.user_lp_ata(&user)),
+            key(&pool.config),
+            key(&TOKEN_PROGRAM_ID),
+            key(&pool.token_program_x),
+            key(&pool.token_program_y),
+        ]
+    );
+}
+
+#[test]
+fn test_first_deposit_appends_locked_lp_accounts() {
+    let pool = pool();
+    let user = address(7);
+    let ix = first_deposit(&user, &pool, 1_000, 2_000, 3_000, EXPIRATION);
+
+    // 指令数据与普通存款相同，amount 是至少铸造的 LP
+    assert_eq!(ix.data, deposit(&user, &pool, 1_000, 2_000, 3_000, EXPIRATION).data);
+
+    assert_eq!(ix.accounts.len(), 17);
+    let locked_owner = locked_lp_owner_address(&pool.config);
+    let keys: Vec<Pubkey> = ix.accounts[13..].iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(
+        keys,
+        vec![
+            key(&locked_owner),
+            key(&pool.locked_lp_ata()),
+            key(&SYSTEM_PROGRAM_ID),
+            key(&ASSOCIATED_TOKEN_PROGRAM_ID),
+        ]
+    );
+    assert!(ix.accounts[14].is_writable);
+    assert!(ix.accounts[13..].iter().all(|meta| !meta.is_signer));
+}
+
+#[test]
+fn test_swap_data_and_accounts() {
+    let pool = pool();
+    let user = address(7);
+
+    let ix = swap(&user, &pool, true, 10_000, 9_000, EXPIRATION);
+    let mut expected = vec![discriminator::SWAP, 1];
+    expected.extend_from_slice(&10_000u64.to_le_bytes());
+    expected.extend_from_slice(&9_000u64.to_le_bytes());
+    expected.extend_from_slice(&EXPIRATION.to_le_bytes());
+    assert_eq!(ix.data, expected);
+
+    let ix = swap_exact_out(&user, &pool, true, 10_000, 9_000, EXPIRATION);
+    expected[0] = discriminator::SWAP_EXACT_OUT;
+    assert_eq!(ix.data, expected);
+
+    let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(keys.len(), 10);
+    assert_eq!(keys[3], key(&pool.user_x_ata(&user)));
+    assert_eq!(keys[5], key(&pool.vault_x));
+    assert_eq!(keys[7], key(&pool.config));
+    assert!(!ix.accounts[1].is_writable && !ix.accounts[2].is_writable);
+}
+
+#[test]
+fn test_flash_loan_appends_receiver() {
+    let pool = pool();
+    let user = address(7);
+    let receiver = address(9);
+    let receiver_account = AccountMeta::new(key(&address(10)), false);
+
+    let ix = flash_loan(&user, &pool, 1_000, 2_000, &receiver, vec![receiver_account.clone()], &[0xaa, 0xbb]);
+    let mut expected = vec![discriminator::FLASH_LOAN];
+    expected.extend_from_slice(&1_000u64.to_le_bytes());
+    expected.extend_from_slice(&2_000u64.to_le_bytes());
+    expected.extend_from_slice(&[0xaa, 0xbb]);
+    assert_eq!(ix.data, expected);
+
+    // 前 10 个账户与 Swap 相同
+    let swap_ix = swap(&user, &pool, true, 0, 0, EXPIRATION);
+    assert_eq!(ix.accounts[..10], swap_ix.accounts[..]);
+    assert_eq!(ix.accounts[10], AccountMeta::new_readonly(key(&receiver), false));
+    assert_eq!(ix.accounts[11], receiver_account);
+}
+
+#[test]
+fn test_pool_keys_are_deterministic() {
+    let pool = pool();
+
+    assert_eq!(pool, PoolKeys::new(SEED, &address(1), &address(2), &TOKEN_PROGRAM_ID, &TOKEN_2022_PROGRAM_ID));
+    assert_ne!(pool.config, PoolKeys::new(SEED + 1, &address(1), &address(2), &TOKEN_PROGRAM_ID, &TOKEN_2022_PROGRAM_ID).config);
+    // 两侧的 token program 不同，金库按各自的 token program 派生
+    assert_ne!(pool.vault_x, PoolKeys::new(SEED, &address(1), &address(2), &TOKEN_2022_PROGRAM_ID, &TOKEN_2022_PROGRAM_ID).vault_x);
+}
+
+#[test]
+fn test_registry_instructions() {
+    let payer = address(7);
+    let authority = address(8);
+    let registry = registry_address().0;
+
+    let ix = initialize_registry(&payer, &authority, true);
+    let mut expected = vec![discriminator::INITIALIZE_REGISTRY];
+    expected.extend_from_slice(authority.as_ref());
+    expected.push(1);
+    assert_eq!(ix.data, expected);
+    assert_eq!(
+        ix.accounts,
+        vec![
+            AccountMeta::new(key(&payer), true),
+            AccountMeta::new(key(&registry), false),
+            AccountMeta::new_readonly(key(&program_data_address()), false),
+            AccountMeta::new_readonly(key(&SYSTEM_PROGRAM_ID), false),
+        ]
+    );
+
+    let ix = update_registry(&authority, false);
+    assert_eq!(ix.data, vec![discriminator::UPDATE_REGISTRY, 0]);
+    assert_eq!(ix.accounts[0], AccountMeta::new_readonly(key(&authority), true));
+
+    let pool = pool();
+    let ix = register_pool(&payer, &pool);
+    assert_eq!(ix.data, vec![discriminator::REGISTER_POOL]);
+    assert_eq!(
+        ix.accounts,
+        vec![
+            AccountMeta::new(key(&payer), true),
+            AccountMeta::new(key(&registry), false),
+            AccountMeta::new_readonly(key(&pool.config), false),
+            AccountMeta::new(key(&pair_record_address(&pool.mint_x, &pool.mint_y).0), false),
+            AccountMeta::new_readonly(key(&SYSTEM_PROGRAM_ID), false),
+        ]
+    );
+}
+
+#[test]
+fn test_pair_record_address_ignores_mint_order() {
+    assert_eq!(pair_record_address(&address(1), &address(2)), pair_record_address(&address(2), &address(1)));
+    assert_ne!(pair_record_address(&address(1), &address(2)).0, pair_record_address(&address(1), &address(3)).0);
+}